@@ -0,0 +1,267 @@
+use crate::{AnySliceMut, AnySliceRef, AnySliceUninit, TypeInfo};
+use core::{any::TypeId, marker::PhantomData, mem::MaybeUninit};
+
+/// A type-erased vector with a fixed capacity, stored in a borrowed buffer.
+///
+/// This works like a `Vec<T>` whose element type is only known at runtime, except that it never
+/// allocates. Instead, its elements live in a caller-supplied [`AnySliceUninit`], which makes it
+/// usable in realtime code and without the `alloc` feature. Pushing beyond the capacity of the buffer
+/// fails instead of growing.
+///
+/// Elements that are still in the vector when it is dropped or cleared are dropped as well.
+///
+/// ```
+/// use std::mem::MaybeUninit;
+///
+/// let mut buffer = [MaybeUninit::<i32>::uninit(); 2];
+/// let mut vec = sashay::AnyArrayVec::new(sashay::AnySliceUninit::erase(buffer.as_mut_slice()));
+///
+/// vec.push(1i32);
+/// vec.push(2i32);
+/// assert_eq!(vec.try_push(3i32), Err(3));
+/// assert_eq!(vec.as_slice().unerase::<i32>(), Some([1, 2].as_slice()));
+///
+/// assert_eq!(vec.pop::<i32>(), Some(2));
+/// assert_eq!(vec.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct AnyArrayVec<'a> {
+    /// A raw pointer to the start of the buffer
+    ///
+    /// Note: this pointer must be aligned and point to `capacity` possibly uninitialized values of `T`,
+    /// of which the first `len` are initialized
+    ptr: *mut u8,
+
+    /// The number of initialized elements at the front of the buffer
+    len: usize,
+
+    /// The number of elements that fit in the buffer
+    capacity: usize,
+
+    /// The type of the elements
+    info: TypeInfo,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a mut ()>,
+}
+
+impl<'a> AnyArrayVec<'a> {
+    /// Construct a vector on top of an uninitialized buffer.
+    ///
+    /// The capacity is the length of the buffer. Elements already in its initialized prefix become the
+    /// initial contents of the vector.
+    pub fn new(mut storage: AnySliceUninit<'a>) -> Self {
+        Self {
+            ptr: storage.as_mut_ptr().cast::<u8>(),
+            len: storage.initialized_len(),
+            capacity: storage.len(),
+            info: *storage.type_info(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Construct an empty vector on top of a typed uninitialized buffer.
+    pub fn erase<T: 'static>(storage: &'a mut [MaybeUninit<T>]) -> Self {
+        Self::new(AnySliceUninit::erase(storage))
+    }
+
+    /// Append an element to the back of the vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is full, or if `T` is not the element type.
+    pub fn push<T: 'static>(&mut self, value: T) {
+        assert!(self.contains::<T>(), "pushed a value of the wrong type");
+        if self.try_push(value).is_err() {
+            panic!("pushed beyond the capacity of {}", self.capacity);
+        }
+    }
+
+    /// Append an element to the back of the vector, if there is room for it.
+    ///
+    /// If the vector is full, or if `T` is not the element type, you get the value back.
+    pub fn try_push<T: 'static>(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() || !self.contains::<T>() {
+            return Err(value);
+        }
+
+        // SAFETY: The element is within the buffer and uninitialized, and of type T
+        unsafe { self.element(self.len).cast::<T>().write(value) };
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Remove the last element from the vector and return it.
+    ///
+    /// If the vector is empty, or if `T` is not the element type, you get `None`.
+    pub fn pop<T: 'static>(&mut self) -> Option<T> {
+        if self.is_empty() || !self.contains::<T>() {
+            return None;
+        }
+
+        self.len -= 1;
+
+        // SAFETY: The element was initialized, and is no longer part of the vector
+        Some(unsafe { self.element(self.len).cast::<T>().read() })
+    }
+
+    /// Shorten the vector to `len` elements, dropping the rest.
+    ///
+    /// If the vector is already shorter, nothing happens.
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.len -= 1;
+
+            // SAFETY: The element was initialized, and is no longer part of the vector
+            unsafe { self.info.drop_in_place(self.element(self.len).cast::<()>()) };
+        }
+    }
+
+    /// Remove and drop all elements.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Access the elements as an erased slice.
+    pub fn as_slice(&self) -> AnySliceRef<'_> {
+        // SAFETY: The first `len` elements are initialized, and the type information is valid
+        unsafe {
+            AnySliceRef::from_raw_parts(
+                self.ptr.cast_const().cast::<()>(),
+                self.len,
+                self.info.stride(),
+                *self.info.type_id(),
+            )
+        }
+    }
+
+    /// Access the elements as a mutable erased slice.
+    pub fn as_mut_slice(&mut self) -> AnySliceMut<'_> {
+        // SAFETY: The first `len` elements are initialized, and the type information is valid
+        unsafe {
+            AnySliceMut::from_raw_parts(
+                self.ptr.cast::<()>(),
+                self.len,
+                self.info.stride(),
+                *self.info.type_id(),
+            )
+        }
+    }
+
+    /// How many elements does the vector contain?
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Does the vector contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many elements fit in the vector?
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Is the vector at its capacity?
+    pub const fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    /// Are the elements of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.info.is::<T>()
+    }
+
+    /// The `size_of()` of the elements.
+    pub const fn stride(&self) -> usize {
+        self.info.stride()
+    }
+
+    /// A unique type id representing the element type.
+    pub const fn type_id(&self) -> &TypeId {
+        self.info.type_id()
+    }
+
+    /// The runtime information about the element type.
+    pub const fn type_info(&self) -> &TypeInfo {
+        &self.info
+    }
+
+    /// A pointer to the element at `index`, which doesn't need to be in bounds
+    fn element(&self, index: usize) -> *mut u8 {
+        self.ptr.wrapping_add(index * self.info.stride())
+    }
+}
+
+impl Drop for AnyArrayVec<'_> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<'a> From<AnySliceUninit<'a>> for AnyArrayVec<'a> {
+    fn from(storage: AnySliceUninit<'a>) -> Self {
+        Self::new(storage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{DropCounter, Tracked};
+
+    // These tests use an (u8, u16) because it has padding, and `Tracked` values to count drops
+
+    #[test]
+    fn push_pop() {
+        let mut buffer = [MaybeUninit::<(u8, u16)>::uninit(); 3];
+        let mut storage = AnySliceUninit::erase(buffer.as_mut_slice());
+        storage.write(0, (1u8, 2u16)).unwrap();
+
+        let mut vec = AnyArrayVec::new(storage);
+        assert_eq!(vec.len(), 1);
+        assert_eq!(vec.capacity(), 3);
+
+        assert_eq!(vec.try_push(0u8), Err(0u8));
+        vec.push((3u8, 4u16));
+        vec.push((5u8, 6u16));
+        assert!(vec.is_full());
+        assert_eq!(vec.try_push((7u8, 8u16)), Err((7u8, 8u16)));
+
+        vec.as_mut_slice().unerase_mut::<(u8, u16)>().unwrap()[0].0 = 9;
+        assert_eq!(
+            vec.as_slice().unerase::<(u8, u16)>(),
+            Some([(9u8, 2u16), (3u8, 4u16), (5u8, 6u16)].as_slice())
+        );
+
+        assert_eq!(vec.pop::<u8>(), None);
+        assert_eq!(vec.pop::<(u8, u16)>(), Some((5u8, 6u16)));
+        vec.clear();
+        assert!(vec.is_empty());
+        assert_eq!(vec.pop::<(u8, u16)>(), None);
+    }
+
+    #[test]
+    fn drops() {
+        static DROPS: DropCounter = DropCounter::new();
+
+        let mut buffer: [MaybeUninit<Tracked>; 4] = [const { MaybeUninit::uninit() }; 4];
+        let mut vec = AnyArrayVec::erase(buffer.as_mut_slice());
+        for _ in 0..4 {
+            vec.push(DROPS.track(()));
+        }
+
+        vec.truncate(3);
+        assert_eq!(DROPS.drops(), 1);
+
+        let popped = vec.pop::<Tracked>();
+        assert_eq!(DROPS.drops(), 1);
+        drop(popped);
+        assert_eq!(DROPS.drops(), 2);
+
+        drop(vec);
+        assert_eq!(DROPS.drops(), 4);
+    }
+}
@@ -0,0 +1,386 @@
+use crate::{AnySliceMut, AnySliceRef, TypeInfo};
+use alloc::vec::Vec;
+use core::{
+    any::TypeId,
+    mem::ManuallyDrop,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A pool of reusable heap buffers, handed out as type-erased slices.
+///
+/// Processing graphs often need temporary buffers of many different element types every block.
+/// Instead of allocating those over and over, check them out of a pool and check them back in when
+/// done. Storage is reused for any element type with the same size and alignment, so once the pool is
+/// warmed up, checking out buffers doesn't allocate.
+///
+/// Checked-out buffers own their storage, so they can outlive the pool. Buffers that are dropped
+/// instead of checked in simply free their storage.
+///
+/// ```
+/// let mut pool = sashay::AnyBufferPool::new();
+///
+/// let mut buffer = pool.checkout::<f32>(64);
+/// buffer.as_mut_slice().unerase_mut::<f32>().unwrap()[0] = 1.0;
+/// pool.checkin(buffer).unwrap();
+///
+/// // u32 has the same size and alignment as f32, so this reuses the storage
+/// let buffer = pool.checkout::<u32>(32);
+/// assert_eq!(buffer.as_slice().unerase::<u32>(), Some([0; 32].as_slice()));
+/// assert_eq!(pool.idle_len(), 0);
+/// ```
+#[derive(Debug)]
+pub struct AnyBufferPool {
+    /// A unique id, so buffers from other pools can be detected
+    id: usize,
+
+    /// Storage that is not checked out
+    idle: Vec<Block>,
+}
+
+impl AnyBufferPool {
+    /// Construct a pool without any storage.
+    pub fn new() -> Self {
+        /// The id of the next pool
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            idle: Vec::new(),
+        }
+    }
+
+    /// Check out a buffer of `len` default values of type `T`.
+    ///
+    /// This reuses idle storage with the same element size and alignment and enough room, if there is
+    /// any. Otherwise new storage is allocated.
+    pub fn checkout<T: Default + 'static>(&mut self, len: usize) -> AnyPooledBuffer {
+        let info = TypeInfo::of::<T>();
+
+        let position = self.idle.iter().position(|block| {
+            block.info.stride() == info.stride()
+                && block.info.align() == info.align()
+                && block.capacity >= len
+        });
+
+        let block = match position {
+            Some(position) => self.idle.swap_remove(position),
+            None => Block {
+                ptr: info.allocate_array(len),
+                capacity: len,
+                info,
+            },
+        };
+
+        // Start out empty and count every written value, so a panicking `T::default()` drops the values
+        // written so far and frees the storage
+        let mut buffer = AnyPooledBuffer {
+            ptr: block.ptr,
+            len: 0,
+            capacity: block.capacity,
+            info,
+            pool: self.id,
+        };
+
+        let ptr = buffer.ptr.cast::<T>();
+        while buffer.len < len {
+            // SAFETY: The storage fits `len` values of the same size and alignment as T
+            unsafe { ptr.add(buffer.len).write(T::default()) };
+            buffer.len += 1;
+        }
+
+        buffer
+    }
+
+    /// Check a buffer back in, so its storage can be reused.
+    ///
+    /// The elements are dropped. If the buffer was checked out of a different pool, you get it back.
+    pub fn checkin(&mut self, buffer: AnyPooledBuffer) -> Result<(), AnyPooledBuffer> {
+        if buffer.pool != self.id {
+            return Err(buffer);
+        }
+
+        let block = buffer.into_block();
+        if block.capacity > 0 && block.info.stride() > 0 {
+            self.idle.push(block);
+        } else {
+            // SAFETY: The storage doesn't hold any elements anymore, and nobody else refers to it
+            unsafe { block.free() };
+        }
+
+        Ok(())
+    }
+
+    /// How many idle buffers are waiting to be reused?
+    pub fn idle_len(&self) -> usize {
+        self.idle.len()
+    }
+
+    /// Free the storage of all idle buffers.
+    pub fn clear(&mut self) {
+        for block in self.idle.drain(..) {
+            // SAFETY: Idle storage doesn't hold any elements, and nobody else refers to it
+            unsafe { block.free() };
+        }
+    }
+}
+
+impl Default for AnyBufferPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for AnyBufferPool {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// A buffer checked out of an [`AnyBufferPool`].
+///
+/// Access its elements through [`AnyPooledBuffer::as_slice()`] and [`AnyPooledBuffer::as_mut_slice()`],
+/// and hand it back with [`AnyBufferPool::checkin()`] once you're done.
+#[derive(Debug)]
+pub struct AnyPooledBuffer {
+    /// A raw pointer to the storage
+    ///
+    /// Note: this pointer must be aligned and point to `capacity` possibly uninitialized values of `T`,
+    /// of which the first `len` are initialized
+    ptr: *mut u8,
+
+    /// The number of elements in the buffer
+    len: usize,
+
+    /// The number of elements that fit in the storage
+    capacity: usize,
+
+    /// The type of the elements
+    info: TypeInfo,
+
+    /// The id of the pool this buffer was checked out of
+    pool: usize,
+}
+
+impl AnyPooledBuffer {
+    /// Access the elements as an erased slice.
+    pub fn as_slice(&self) -> AnySliceRef<'_> {
+        // SAFETY: The first `len` elements are initialized, and the type information is valid
+        unsafe {
+            AnySliceRef::from_raw_parts(
+                self.ptr.cast_const().cast::<()>(),
+                self.len,
+                self.info.stride(),
+                *self.info.type_id(),
+            )
+        }
+    }
+
+    /// Access the elements as a mutable erased slice.
+    pub fn as_mut_slice(&mut self) -> AnySliceMut<'_> {
+        // SAFETY: The first `len` elements are initialized, and the type information is valid
+        unsafe {
+            AnySliceMut::from_raw_parts(
+                self.ptr.cast::<()>(),
+                self.len,
+                self.info.stride(),
+                *self.info.type_id(),
+            )
+        }
+    }
+
+    /// How many elements does the buffer contain?
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Does the buffer contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many elements would fit in the underlying storage?
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Are the elements of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.info.is::<T>()
+    }
+
+    /// The `size_of()` of the elements.
+    pub const fn stride(&self) -> usize {
+        self.info.stride()
+    }
+
+    /// A unique type id representing the element type.
+    pub const fn type_id(&self) -> &TypeId {
+        self.info.type_id()
+    }
+
+    /// The runtime information about the element type.
+    pub const fn type_info(&self) -> &TypeInfo {
+        &self.info
+    }
+
+    /// Drop the elements, and take the storage out of the buffer
+    fn into_block(self) -> Block {
+        let mut buffer = ManuallyDrop::new(self);
+        buffer.drop_elements();
+
+        Block {
+            ptr: buffer.ptr,
+            capacity: buffer.capacity,
+            info: buffer.info,
+        }
+    }
+
+    /// Drop all elements, leaving the buffer empty
+    fn drop_elements(&mut self) {
+        while self.len > 0 {
+            self.len -= 1;
+            let element = self.ptr.wrapping_add(self.len * self.info.stride());
+
+            // SAFETY: The element was initialized, and is no longer part of the buffer
+            unsafe { self.info.drop_in_place(element.cast::<()>()) };
+        }
+    }
+}
+
+impl Drop for AnyPooledBuffer {
+    fn drop(&mut self) {
+        self.drop_elements();
+
+        // SAFETY: The storage was allocated for the same size and alignment, and the elements were dropped
+        unsafe { self.info.deallocate_array(self.ptr, self.capacity) };
+    }
+}
+
+/// A piece of idle heap storage, which can be reused for any type with the same size and alignment
+#[derive(Debug)]
+struct Block {
+    /// A pointer to the storage
+    ptr: *mut u8,
+
+    /// The number of elements the storage was allocated for
+    capacity: usize,
+
+    /// The type the storage was last used for
+    info: TypeInfo,
+}
+
+impl Block {
+    /// Deallocate the storage
+    ///
+    /// # Safety
+    ///
+    /// The storage must not hold any elements that still need to be dropped, and must not be used again.
+    unsafe fn free(&self) {
+        // SAFETY: The storage was allocated for the same size, alignment and capacity
+        unsafe { self.info.deallocate_array(self.ptr, self.capacity) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{DropCounter, Tracked};
+
+    #[test]
+    fn reuse() {
+        let mut pool = AnyBufferPool::new();
+
+        let mut buffer = pool.checkout::<(u8, u16)>(4);
+        assert_eq!(buffer.len(), 4);
+        buffer.as_mut_slice().unerase_mut::<(u8, u16)>().unwrap()[3] = (1, 2);
+        let ptr = buffer.as_slice().as_ptr();
+        pool.checkin(buffer).unwrap();
+        assert_eq!(pool.idle_len(), 1);
+
+        // Different size, no reuse
+        let other = pool.checkout::<u64>(1);
+        assert_eq!(pool.idle_len(), 1);
+        pool.checkin(other).unwrap();
+
+        // Same size and alignment, shorter, so reuse
+        let buffer = pool.checkout::<[u16; 2]>(3);
+        assert_eq!(buffer.as_slice().as_ptr(), ptr);
+        assert_eq!(buffer.capacity(), 4);
+        assert_eq!(
+            buffer.as_slice().unerase::<[u16; 2]>(),
+            Some([[0u16; 2]; 3].as_slice())
+        );
+
+        // Same size, but not the same alignment
+        let other = pool.checkout::<u32>(4);
+        assert_ne!(other.as_slice().as_ptr(), ptr);
+        assert_eq!(pool.idle_len(), 1);
+        drop((buffer, other));
+
+        pool.clear();
+        assert_eq!(pool.idle_len(), 0);
+    }
+
+    #[test]
+    fn foreign() {
+        static DROPS: DropCounter = DropCounter::new();
+
+        // Checking out needs a default value, which reports to the counter of this test
+        struct Defaulted {
+            _tracked: Tracked,
+        }
+
+        impl Default for Defaulted {
+            fn default() -> Self {
+                Self {
+                    _tracked: DROPS.track(()),
+                }
+            }
+        }
+
+        let mut first = AnyBufferPool::new();
+        let mut second = AnyBufferPool::new();
+
+        let buffer = first.checkout::<Defaulted>(3);
+        let buffer = second.checkin(buffer).unwrap_err();
+        assert_eq!(DROPS.drops(), 0);
+
+        first.checkin(buffer).unwrap();
+        assert_eq!(DROPS.drops(), 3);
+
+        drop(first.checkout::<Defaulted>(2));
+        assert_eq!(DROPS.drops(), 5);
+    }
+
+    #[test]
+    fn panicking_default() {
+        extern crate std;
+
+        static DROPS: DropCounter = DropCounter::new();
+        static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+        // The third default value panics
+        struct Defaulted {
+            _tracked: Tracked,
+        }
+
+        impl Default for Defaulted {
+            fn default() -> Self {
+                assert!(CREATED.fetch_add(1, Ordering::Relaxed) < 2, "boom");
+                Self {
+                    _tracked: DROPS.track(()),
+                }
+            }
+        }
+
+        let mut pool = AnyBufferPool::new();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            pool.checkout::<Defaulted>(4)
+        }));
+        assert!(result.is_err());
+
+        // The values written before the panic were dropped
+        assert_eq!(DROPS.drops(), 2);
+        assert_eq!(pool.idle_len(), 0);
+    }
+}
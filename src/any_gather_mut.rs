@@ -0,0 +1,221 @@
+use crate::{
+    gather::Selection, AnyGatherRef, AnyMut, AnyRef, AnySliceMut, GatherError, GatherMut, GatherRef,
+};
+use core::any::TypeId;
+
+/// A type-erased mutable view of a selection of elements from a slice.
+///
+/// Every element can be selected at most once, so that no element is aliased mutably.
+/// See [`AnyGatherRef`] for more information.
+///
+/// Created with [`AnySliceMut::gather_mut()`] or [`AnySliceMut::select_mut()`]. Unerasing gives you a [`GatherMut`].
+///
+/// ```
+/// let mut voices : [f32; 4] = [0.1, 0.2, 0.3, 0.4];
+/// let mut any = sashay::AnySliceMut::erase(voices.as_mut_slice());
+///
+/// // Indices may not repeat
+/// assert!(any.gather_mut(&[1, 1]).is_none());
+///
+/// let mut active = any.gather_mut(&[3, 0]).unwrap();
+/// active.scatter_from(&[1.0f32, 2.0]).unwrap();
+///
+/// assert_eq!(voices, [2.0, 0.2, 0.3, 1.0]);
+/// ```
+#[derive(Debug)]
+pub struct AnyGatherMut<'a> {
+    /// The erased slice the elements are selected from
+    slice: AnySliceMut<'a>,
+
+    /// Which elements of the slice are part of the view
+    ///
+    /// Note: all selected indices must be within the slice, and unique
+    selection: Selection<'a>,
+}
+
+impl<'a> AnyGatherMut<'a> {
+    /// Select elements from an erased slice, where every index in the selection is unique and within the slice
+    pub(crate) const fn new(slice: AnySliceMut<'a>, selection: Selection<'a>) -> Self {
+        Self { slice, selection }
+    }
+
+    /// Erase the type of a mutable gather view's elements.
+    pub fn erase<T: 'static>(view: GatherMut<'a, T>) -> AnyGatherMut<'a> {
+        let selection = view.selection();
+        Self::new(AnySliceMut::erase(view.into_source()), selection)
+    }
+
+    /// Borrow this mutable view as an immutable one.
+    pub fn borrow(&self) -> AnyGatherRef<'_> {
+        AnyGatherRef::new(self.slice.borrow(), self.selection)
+    }
+
+    /// Reborrow this mutable view with a shorter lifetime.
+    pub fn borrow_mut(&mut self) -> AnyGatherMut<'_> {
+        AnyGatherMut::new(self.slice.borrow_mut(), self.selection)
+    }
+
+    /// Unerase back to an immutable gather view.
+    pub fn unerase<T: 'static>(&self) -> Option<GatherRef<'_, T>> {
+        let slice = self.slice.unerase::<T>()?;
+        Some(GatherRef::from_parts(slice, self.selection))
+    }
+
+    /// Unerase back to a mutable gather view.
+    ///
+    /// If the original element type was `T`, a valid view is returned. Otherwise, you get `None`.
+    pub fn unerase_mut<T: 'static>(&mut self) -> Option<GatherMut<'_, T>> {
+        self.borrow_mut().unerase_into()
+    }
+
+    /// Unerase back into a mutable gather view, transferring ownership into it.
+    pub fn unerase_into<T: 'static>(self) -> Option<GatherMut<'a, T>> {
+        let slice = self.slice.unerase_into::<T>()?;
+        Some(GatherMut::from_parts(slice, self.selection))
+    }
+
+    /// Retrieve an immutable reference to one of the selected elements.
+    pub fn get(&self, index: usize) -> Option<AnyRef<'_>> {
+        self.slice.get(self.selection.get(index)?)
+    }
+
+    /// Retrieve a mutable reference to one of the selected elements.
+    pub fn get_mut(&mut self, index: usize) -> Option<AnyMut<'_>> {
+        self.slice.get_mut(self.selection.get(index)?)
+    }
+
+    /// Iterate over references to all selected elements, in order.
+    pub fn iter(&self) -> impl Iterator<Item = AnyRef<'_>> {
+        // The selection only contains indices within the slice, so nothing is skipped
+        self.selection
+            .iter()
+            .filter_map(|index| self.slice.get(index))
+    }
+
+    /// Iterate over mutable references to all selected elements, in order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = AnyMut<'_>> {
+        let (stride, type_id) = (self.slice.stride(), *self.slice.type_id());
+        let ptr = self.slice.as_mut_ptr().cast::<u8>();
+
+        // SAFETY:
+        // - The selection only contains indices within the slice
+        // - Every index is selected at most once, so the references are unique
+        self.selection.iter().map(move |index| unsafe {
+            AnyMut::from_raw_parts(ptr.add(index * stride).cast::<()>(), type_id)
+        })
+    }
+
+    /// Copy values into the selected elements, in order.
+    ///
+    /// The values must be of the original element type, and there must be exactly one for every
+    /// selected element. Otherwise, nothing is written and you get an error.
+    pub fn scatter_from<T: Copy + 'static>(&mut self, values: &[T]) -> Result<(), GatherError> {
+        let expected = *self.type_id();
+        let mut view = self.unerase_mut::<T>().ok_or(GatherError::TypeMismatch {
+            expected,
+            found: TypeId::of::<T>(),
+        })?;
+
+        if values.len() != view.len() {
+            return Err(GatherError::LengthMismatch {
+                expected: view.len(),
+                found: values.len(),
+            });
+        }
+
+        view.scatter_from(values);
+        Ok(())
+    }
+
+    /// Iterate over the indices of the selected elements within the slice.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + 'a {
+        self.selection.iter()
+    }
+
+    /// How many elements are selected?
+    pub const fn len(&self) -> usize {
+        self.selection.len()
+    }
+
+    /// Are any elements selected at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Was the original element of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.slice.contains::<T>()
+    }
+
+    /// The `size_of()` of the original elements of type `T`.
+    pub const fn stride(&self) -> usize {
+        self.slice.stride()
+    }
+
+    /// A unique type id representing the original element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        self.slice.type_id()
+    }
+}
+
+impl<'a, T: 'static> From<GatherMut<'a, T>> for AnyGatherMut<'a> {
+    fn from(view: GatherMut<'a, T>) -> Self {
+        Self::erase(view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // All these tests use an (u8, u16) because it has padding
+
+    #[test]
+    fn write() {
+        let mut data = [(0u8, 1u16), (2u8, 3u16), (4u8, 5u16)];
+        let mut any = AnySliceMut::erase(data.as_mut_slice());
+
+        assert!(any.gather_mut(&[0, 2, 0]).is_none());
+        let mut view = any.gather_mut(&[2, 0]).unwrap();
+        view.get_mut(0)
+            .unwrap()
+            .unerase_mut::<(u8, u16)>()
+            .unwrap()
+            .0 = 40;
+        for mut element in view.iter_mut() {
+            element.unerase_mut::<(u8, u16)>().unwrap().1 = 0;
+        }
+        assert_eq!(
+            view.borrow().get(0).unwrap().unerase::<(u8, u16)>(),
+            Some(&(40u8, 0u16))
+        );
+
+        assert_eq!(data, [(0, 0), (2, 3), (40, 0)]);
+    }
+
+    #[test]
+    fn scatter() {
+        let mut data = [(0u8, 1u16), (2u8, 3u16), (4u8, 5u16)];
+        let mut any = AnySliceMut::erase(data.as_mut_slice());
+
+        let mut view = any.select_mut(&[true, false, true]).unwrap();
+        assert!(matches!(
+            view.scatter_from(&[0u8, 0]),
+            Err(GatherError::TypeMismatch { .. })
+        ));
+        assert_eq!(
+            view.scatter_from(&[(9u8, 9u16)]),
+            Err(GatherError::LengthMismatch {
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(view.scatter_from(&[(6u8, 7u16), (8u8, 9u16)]), Ok(()));
+        assert!(view.indices().eq([0, 2]));
+
+        let erased = AnyGatherMut::erase(view.unerase_into::<(u8, u16)>().unwrap());
+        assert_eq!(erased.len(), 2);
+
+        assert_eq!(data, [(6, 7), (2, 3), (8, 9)]);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{gather::Selection, AnyRef, AnySliceRef, GatherRef};
+use core::any::TypeId;
+
+/// A type-erased immutable view of a selection of elements from a slice.
+///
+/// Selections such as "the voices that are active" are often stored as a list of indices, or as a mask
+/// of booleans, into a larger buffer. This view behaves like an erased slice of only the selected
+/// elements, without copying them. Indices may repeat and be in any order.
+///
+/// Created with [`AnySliceRef::gather()`] or [`AnySliceRef::select()`]. Unerasing gives you a [`GatherRef`].
+///
+/// ```
+/// let voices : [f32; 4] = [0.1, 0.2, 0.3, 0.4];
+/// let any = sashay::AnySliceRef::erase(voices.as_slice());
+///
+/// let active = any.gather(&[3, 0]).unwrap();
+/// assert_eq!(active.len(), 2);
+/// assert_eq!(active.get(0).unwrap().unerase_into::<f32>(), Some(&0.4));
+///
+/// let masked = any.select(&[false, true, true, false]).unwrap();
+/// assert!(masked.unerase::<f32>().unwrap().iter().eq(&[0.2, 0.3]));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AnyGatherRef<'a> {
+    /// The erased slice the elements are selected from
+    slice: AnySliceRef<'a>,
+
+    /// Which elements of the slice are part of the view
+    ///
+    /// Note: all selected indices must be within the slice
+    selection: Selection<'a>,
+}
+
+impl<'a> AnyGatherRef<'a> {
+    /// Select elements from an erased slice, where every index in the selection is within the slice
+    pub(crate) const fn new(slice: AnySliceRef<'a>, selection: Selection<'a>) -> Self {
+        Self { slice, selection }
+    }
+
+    /// Erase the type of a gather view's elements.
+    pub fn erase<T: 'static>(view: GatherRef<'a, T>) -> AnyGatherRef<'a> {
+        Self::new(AnySliceRef::erase(view.source()), view.selection())
+    }
+
+    /// Unerase back to an immutable gather view.
+    ///
+    /// If the original element type was `T`, a valid view is returned. Otherwise, you get `None`.
+    pub fn unerase<T: 'static>(&self) -> Option<GatherRef<'_, T>> {
+        self.unerase_into()
+    }
+
+    /// Unerase back into an immutable gather view, transferring ownership into it.
+    pub fn unerase_into<T: 'static>(self) -> Option<GatherRef<'a, T>> {
+        let slice = self.slice.unerase_into::<T>()?;
+        Some(GatherRef::from_parts(slice, self.selection))
+    }
+
+    /// Retrieve an immutable reference to one of the selected elements.
+    ///
+    /// For views created from a mask, this has to scan the mask to find the element.
+    pub fn get(&self, index: usize) -> Option<AnyRef<'_>> {
+        self.slice.get(self.selection.get(index)?)
+    }
+
+    /// Iterate over references to all selected elements, in order.
+    pub fn iter(&self) -> impl Iterator<Item = AnyRef<'_>> {
+        // The selection only contains indices within the slice, so nothing is skipped
+        self.selection
+            .iter()
+            .filter_map(|index| self.slice.get(index))
+    }
+
+    /// Iterate over the indices of the selected elements within the slice.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + 'a {
+        self.selection.iter()
+    }
+
+    /// Access the erased slice the elements are selected from.
+    pub const fn source(&self) -> AnySliceRef<'a> {
+        self.slice
+    }
+
+    /// How many elements are selected?
+    pub const fn len(&self) -> usize {
+        self.selection.len()
+    }
+
+    /// Are any elements selected at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Was the original element of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.slice.contains::<T>()
+    }
+
+    /// The `size_of()` of the original elements of type `T`.
+    pub const fn stride(&self) -> usize {
+        self.slice.stride()
+    }
+
+    /// A unique type id representing the original element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        self.slice.type_id()
+    }
+}
+
+impl<'a, T: 'static> From<GatherRef<'a, T>> for AnyGatherRef<'a> {
+    fn from(view: GatherRef<'a, T>) -> Self {
+        Self::erase(view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // All these tests use an (u8, u16) because it has padding
+
+    #[test]
+    fn gather() {
+        let data = [(0u8, 1u16), (2u8, 3u16), (4u8, 5u16)];
+        let any = AnySliceRef::erase(data.as_slice());
+
+        assert!(any.gather(&[0, 3]).is_none());
+        let view = any.gather(&[2, 0, 2]).unwrap();
+        assert_eq!(view.len(), 3);
+        assert_eq!(view.stride(), core::mem::size_of::<(u8, u16)>());
+        assert_eq!(
+            view.get(2).unwrap().unerase::<(u8, u16)>(),
+            Some(&(4u8, 5u16))
+        );
+        assert!(view.get(3).is_none());
+        assert!(view.indices().eq([2, 0, 2]));
+        assert!(view.unerase::<u8>().is_none());
+
+        let typed = view.unerase_into::<(u8, u16)>().unwrap();
+        assert!(typed.iter().map(|pair| pair.0).eq([4, 0, 4]));
+
+        let erased = AnyGatherRef::erase(typed);
+        assert_eq!(erased.iter().count(), 3);
+    }
+
+    #[test]
+    fn select() {
+        let data = [(0u8, 1u16), (2u8, 3u16), (4u8, 5u16)];
+        let any = AnySliceRef::erase(data.as_slice());
+
+        assert!(any.select(&[true, false]).is_none());
+        let view = any.select(&[false, true, true]).unwrap();
+        assert_eq!(view.len(), 2);
+        assert!(view
+            .iter()
+            .map(|element| element.unerase::<(u8, u16)>().unwrap().0)
+            .eq([2, 4]));
+
+        let empty = any.select(&[false; 3]).unwrap();
+        assert!(empty.is_empty());
+        assert!(empty.get(0).is_none());
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{AnyMut, AnyRef};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::any::TypeId;
+
+/// A heterogeneous list of type-erased references.
+///
+/// The list stores [`AnyRef`]s and [`AnyMut`]s of mixed types, and lets you pick out all elements
+/// of one particular type. It stores its first `N` entries inline, so it works without heap allocation.
+/// If the `alloc` feature is enabled, entries beyond `N` spill over onto the heap. Without it, [`AnyList::push()`]
+/// fails once the list is full.
+///
+/// ```
+/// let a : i32 = 1;
+/// let b : char = '🦀';
+/// let mut c : i32 = 3;
+///
+/// let mut list = sashay::AnyList::<4>::new();
+/// list.push(sashay::AnyRef::erase(&a)).unwrap();
+/// list.push(sashay::AnyRef::erase(&b)).unwrap();
+/// list.push(sashay::AnyMut::erase(&mut c)).unwrap();
+///
+/// assert_eq!(list.count_of::<i32>(), 2);
+/// assert_eq!(list.iter_of::<i32>().sum::<i32>(), 4);
+/// assert_eq!(list.first_of::<char>(), Some(&'🦀'));
+///
+/// // Only the mutable entries can be accessed mutably
+/// for c in list.iter_mut_of::<i32>() {
+///     *c = 5;
+/// }
+///
+/// drop(list);
+/// assert_eq!(c, 5);
+/// ```
+#[derive(Debug)]
+pub struct AnyList<'a, const N: usize> {
+    /// The entries stored inline, of which the first `len` are `Some`
+    inline: [Option<AnyEntry<'a>>; N],
+
+    /// The number of entries stored inline
+    len: usize,
+
+    /// The entries that didn't fit inline anymore
+    #[cfg(feature = "alloc")]
+    spilled: Vec<AnyEntry<'a>>,
+}
+
+impl<'a, const N: usize> AnyList<'a, N> {
+    /// Construct an empty list.
+    pub fn new() -> Self {
+        Self {
+            inline: core::array::from_fn(|_| None),
+            len: 0,
+            #[cfg(feature = "alloc")]
+            spilled: Vec::new(),
+        }
+    }
+
+    /// Append an entry to the back of the list.
+    ///
+    /// If the list is full, the entry is handed back as an error. With the `alloc` feature enabled
+    /// the list is never full, because entries beyond the inline capacity are moved to the heap.
+    pub fn push(&mut self, entry: impl Into<AnyEntry<'a>>) -> Result<(), AnyEntry<'a>> {
+        let entry = entry.into();
+
+        if self.len < N {
+            self.inline[self.len] = Some(entry);
+            self.len += 1;
+            return Ok(());
+        }
+
+        #[cfg(feature = "alloc")]
+        {
+            self.spilled.push(entry);
+            Ok(())
+        }
+
+        #[cfg(not(feature = "alloc"))]
+        Err(entry)
+    }
+
+    /// Iterate over all entries, in the order they were pushed.
+    pub fn iter(&self) -> impl Iterator<Item = &AnyEntry<'a>> {
+        let (inline, spilled) = self.parts();
+        inline.iter().flatten().chain(spilled)
+    }
+
+    /// Iterate mutably over all entries, in the order they were pushed.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut AnyEntry<'a>> {
+        #[cfg(feature = "alloc")]
+        let spilled = self.spilled.as_mut_slice();
+        #[cfg(not(feature = "alloc"))]
+        let spilled: &mut [AnyEntry<'a>] = &mut [];
+
+        self.inline[..self.len].iter_mut().flatten().chain(spilled)
+    }
+
+    /// Iterate over all entries of type `T`.
+    pub fn iter_of<T: 'static>(&self) -> impl Iterator<Item = &T> {
+        self.iter().filter_map(AnyEntry::unerase::<T>)
+    }
+
+    /// Iterate mutably over all entries of type `T`.
+    ///
+    /// Note that only entries that were pushed as [`AnyMut`] are returned, because the others can't be mutated.
+    pub fn iter_mut_of<T: 'static>(&mut self) -> impl Iterator<Item = &mut T> + use<'_, 'a, T, N> {
+        self.iter_mut().filter_map(AnyEntry::unerase_mut::<T>)
+    }
+
+    /// How many entries of type `T` does the list contain?
+    pub fn count_of<T: 'static>(&self) -> usize {
+        self.iter().filter(|entry| entry.contains::<T>()).count()
+    }
+
+    /// The first entry of type `T`, if any.
+    pub fn first_of<T: 'static>(&self) -> Option<&T> {
+        self.iter_of::<T>().next()
+    }
+
+    /// Group the entries by their type.
+    ///
+    /// Every distinct type in the list results in one group, in the order in which the types
+    /// first appear. This doesn't allocate, at the cost of scanning the list once per entry.
+    ///
+    /// ```
+    /// let (a, b, c) = (1i32, 'x', 2i32);
+    ///
+    /// let mut list = sashay::AnyList::<3>::new();
+    /// list.push(sashay::AnyRef::erase(&a)).unwrap();
+    /// list.push(sashay::AnyRef::erase(&b)).unwrap();
+    /// list.push(sashay::AnyRef::erase(&c)).unwrap();
+    ///
+    /// let groups : Vec<_> = list.group_by_type().map(|group| group.len()).collect();
+    /// assert_eq!(groups, [2, 1]);
+    /// ```
+    pub fn group_by_type(&self) -> impl Iterator<Item = AnyListGroup<'_, 'a>> {
+        let (inline, spilled) = self.parts();
+
+        self.iter().filter_map(move |entry| {
+            let group = AnyListGroup {
+                type_id: *entry.type_id(),
+                inline,
+                spilled,
+            };
+
+            // Only the first entry of a type starts a group
+            let first = group.iter().next().map(|first| core::ptr::eq(first, entry));
+            first.unwrap_or(false).then_some(group)
+        })
+    }
+
+    /// How many entries does the list contain?
+    pub fn len(&self) -> usize {
+        let (inline, spilled) = self.parts();
+        inline.len() + spilled.len()
+    }
+
+    /// Does the list contain any entries at all?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all entries from the list.
+    pub fn clear(&mut self) {
+        self.inline[..self.len].fill_with(|| None);
+        self.len = 0;
+
+        #[cfg(feature = "alloc")]
+        self.spilled.clear();
+    }
+
+    /// The used inline entries and the spilled entries
+    fn parts(&self) -> (&[Option<AnyEntry<'a>>], &[AnyEntry<'a>]) {
+        #[cfg(feature = "alloc")]
+        let spilled = self.spilled.as_slice();
+        #[cfg(not(feature = "alloc"))]
+        let spilled = &[];
+
+        (&self.inline[..self.len], spilled)
+    }
+}
+
+impl<const N: usize> Default for AnyList<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// All entries of one type within an [`AnyList`].
+///
+/// Created by [`AnyList::group_by_type()`].
+#[derive(Debug, Clone, Copy)]
+pub struct AnyListGroup<'l, 'a> {
+    type_id: TypeId,
+    inline: &'l [Option<AnyEntry<'a>>],
+    spilled: &'l [AnyEntry<'a>],
+}
+
+impl<'l, 'a> AnyListGroup<'l, 'a> {
+    /// Iterate over the entries in this group.
+    pub fn iter(&self) -> impl Iterator<Item = &'l AnyEntry<'a>> {
+        let type_id = self.type_id;
+
+        self.inline
+            .iter()
+            .flatten()
+            .chain(self.spilled)
+            .filter(move |entry| *entry.type_id() == type_id)
+    }
+
+    /// Iterate over the entries in this group, unerased to `T`.
+    ///
+    /// If `T` is not the type of this group, the iterator is empty.
+    pub fn iter_of<T: 'static>(&self) -> impl Iterator<Item = &'l T> {
+        self.iter().filter_map(AnyEntry::unerase::<T>)
+    }
+
+    /// How many entries does the group contain?
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Does the group contain any entries at all?
+    ///
+    /// Groups handed out by [`AnyList::group_by_type()`] never are.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// A unique type id representing the type `T` shared by all entries in the group.
+    pub const fn type_id(&self) -> &TypeId {
+        &self.type_id
+    }
+}
+
+/// An entry in an [`AnyList`], either an immutable or a mutable erased reference.
+#[derive(Debug)]
+pub enum AnyEntry<'a> {
+    /// An immutable erased reference
+    Ref(AnyRef<'a>),
+
+    /// A mutable erased reference
+    Mut(AnyMut<'a>),
+}
+
+impl AnyEntry<'_> {
+    /// Unerase back to an immutable reference.
+    ///
+    /// If the original reference's type was `T`, a valid reference is returned. Otherwise, you get `None`.
+    pub fn unerase<T: 'static>(&self) -> Option<&T> {
+        match self {
+            Self::Ref(any) => any.unerase(),
+            Self::Mut(any) => any.unerase(),
+        }
+    }
+
+    /// Unerase back to a mutable reference.
+    ///
+    /// If the entry is mutable and the original reference's type was `T`, a valid reference is returned.
+    /// Otherwise, you get `None`.
+    pub fn unerase_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        match self {
+            Self::Ref(_) => None,
+            Self::Mut(any) => any.unerase_mut(),
+        }
+    }
+
+    /// Borrow the entry as an immutable erased reference.
+    pub fn borrow(&self) -> AnyRef<'_> {
+        match self {
+            Self::Ref(any) => *any,
+            Self::Mut(any) => any.borrow(),
+        }
+    }
+
+    /// Borrow the entry as a mutable erased reference, if it is mutable.
+    pub fn borrow_mut(&mut self) -> Option<AnyMut<'_>> {
+        match self {
+            Self::Ref(_) => None,
+            Self::Mut(any) => Some(any.borrow_mut()),
+        }
+    }
+
+    /// Was the original referee of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        TypeId::of::<T>() == *self.type_id()
+    }
+
+    /// A unique type id representing the original reference type `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        match self {
+            Self::Ref(any) => any.type_id(),
+            Self::Mut(any) => any.type_id(),
+        }
+    }
+}
+
+impl<'a> From<AnyRef<'a>> for AnyEntry<'a> {
+    fn from(any: AnyRef<'a>) -> Self {
+        Self::Ref(any)
+    }
+}
+
+impl<'a> From<AnyMut<'a>> for AnyEntry<'a> {
+    fn from(any: AnyMut<'a>) -> Self {
+        Self::Mut(any)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflow() {
+        let data = [1u8, 2, 3];
+        let mut list = AnyList::<2>::new();
+
+        assert!(list.push(AnyRef::erase(&data[0])).is_ok());
+        assert!(list.push(AnyRef::erase(&data[1])).is_ok());
+
+        // Only with alloc can the list grow beyond its inline capacity
+        let pushed = list.push(AnyRef::erase(&data[2]));
+        assert_eq!(pushed.is_ok(), cfg!(feature = "alloc"));
+        assert_eq!(list.len(), if cfg!(feature = "alloc") { 3 } else { 2 });
+        assert_eq!(list.first_of::<u8>(), Some(&1));
+
+        list.clear();
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn groups() {
+        let (a, b, c, d) = (1u8, 'x', 2u8, 3u16);
+        let mut list = AnyList::<4>::new();
+
+        list.push(AnyRef::erase(&a)).unwrap();
+        list.push(AnyRef::erase(&b)).unwrap();
+        list.push(AnyRef::erase(&c)).unwrap();
+        list.push(AnyRef::erase(&d)).unwrap();
+
+        let mut groups = list.group_by_type();
+
+        let first = groups.next().unwrap();
+        assert_eq!(first.type_id(), &TypeId::of::<u8>());
+        assert!(first.iter_of::<u8>().eq([&1, &2]));
+
+        assert_eq!(groups.next().unwrap().type_id(), &TypeId::of::<char>());
+        assert_eq!(groups.next().unwrap().len(), 1);
+        assert!(groups.next().is_none());
+    }
+}
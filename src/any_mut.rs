@@ -1,4 +1,6 @@
-use crate::AnyRef;
+#[cfg(feature = "alloc")]
+use crate::TransparentRegistry;
+use crate::{AnyRef, PrimitiveMut, PrimitiveRef, TransparentAs};
 use core::{any::TypeId, marker::PhantomData};
 
 /// A type-erased mutable reference.
@@ -170,6 +172,56 @@ impl<'a> AnyMut<'a> {
         })
     }
 
+    /// Reinterpret the reference as one of a layout-compatible type `U`, without copying.
+    ///
+    /// This is allowed if the original type `T` declares compatibility by implementing [`TransparentAs<U>`].
+    /// If the original type wasn't `T`, you get `None`.
+    ///
+    /// This consumes the erased reference, so [reborrow](AnyMut::borrow_mut) it first to keep using the original.
+    ///
+    /// ```
+    /// #[repr(transparent)]
+    /// struct Sample(f32);
+    ///
+    /// // SAFETY: Sample is a transparent wrapper around f32
+    /// unsafe impl sashay::TransparentAs<f32> for Sample {}
+    ///
+    /// let mut value = Sample(0.5);
+    /// let mut any = sashay::AnyMut::erase(&mut value);
+    ///
+    /// let mut float = any.borrow_mut().reinterpret::<Sample, f32>().unwrap();
+    /// *float.unerase_mut::<f32>().unwrap() = 2.0;
+    ///
+    /// assert_eq!(value.0, 2.0);
+    /// ```
+    pub fn reinterpret<T, U>(self) -> Option<AnyMut<'a>>
+    where
+        T: TransparentAs<U>,
+        U: 'static,
+    {
+        self.contains::<T>().then(|| Self {
+            type_id: TypeId::of::<U>(),
+            ..self
+        })
+    }
+
+    /// Reinterpret the reference as one of a layout-compatible type `U`, if the registry allows it.
+    ///
+    /// Unlike [`AnyMut::reinterpret()`], this doesn't require naming the original type. If the registry
+    /// doesn't declare the original type compatible with `U`, you get `None`.
+    #[cfg(feature = "alloc")]
+    pub fn reinterpret_with<U: 'static>(
+        self,
+        registry: &TransparentRegistry,
+    ) -> Option<AnyMut<'a>> {
+        registry
+            .is_compatible(self.type_id, TypeId::of::<U>())
+            .then(|| Self {
+                type_id: TypeId::of::<U>(),
+                ..self
+            })
+    }
+
     /// Borrow this mutable reference as an immutable one.
     ///
     /// Even though you have mutable and unique access to a reference, this fuction lets you
@@ -214,6 +266,33 @@ impl<'a> AnyMut<'a> {
         unsafe { AnyMut::from_raw_parts(self.ptr, self.type_id) }
     }
 
+    /// Unerase to an _immutable_ reference to whichever primitive type it refers to.
+    ///
+    /// If the original referee is a Rust primitive (any integer or float, `bool` or `char`), a
+    /// [`PrimitiveRef`] holding the typed reference is returned. Otherwise, you get `None`.
+    pub fn primitive(&self) -> Option<PrimitiveRef<'_>> {
+        PrimitiveRef::from_any(self.borrow())
+    }
+
+    /// Unerase to a mutable reference to whichever primitive type it refers to.
+    ///
+    /// If the original referee is a Rust primitive (any integer or float, `bool` or `char`), a
+    /// [`PrimitiveMut`] holding the typed reference is returned. Otherwise, you get `None`.
+    ///
+    /// ```
+    /// let mut data : f32 = 1.0;
+    /// let mut any = sashay::AnyMut::erase(&mut data);
+    ///
+    /// if let Some(sashay::PrimitiveMut::F32(value)) = any.primitive_mut() {
+    ///     *value = 2.0;
+    /// }
+    ///
+    /// assert_eq!(data, 2.0);
+    /// ```
+    pub fn primitive_mut(&mut self) -> Option<PrimitiveMut<'_>> {
+        PrimitiveMut::from_any(self.borrow_mut())
+    }
+
     /// Retrieve an unsafe immutable pointer to the raw data.
     pub const fn as_ptr(&self) -> *const () {
         self.ptr.cast_const()
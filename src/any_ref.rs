@@ -1,3 +1,6 @@
+#[cfg(feature = "alloc")]
+use crate::TransparentRegistry;
+use crate::{PrimitiveRef, TransparentAs};
 use core::{any::TypeId, marker::PhantomData};
 
 /// A type-erased immutable reference.
@@ -134,6 +137,70 @@ impl<'a> AnyRef<'a> {
         })
     }
 
+    /// Reinterpret the reference as one of a layout-compatible type `U`, without copying.
+    ///
+    /// This is allowed if the original type `T` declares compatibility by implementing [`TransparentAs<U>`].
+    /// If the original type wasn't `T`, you get `None`.
+    ///
+    /// ```
+    /// #[repr(transparent)]
+    /// struct Sample(f32);
+    ///
+    /// // SAFETY: Sample is a transparent wrapper around f32
+    /// unsafe impl sashay::TransparentAs<f32> for Sample {}
+    ///
+    /// let value = Sample(0.5);
+    /// let any = sashay::AnyRef::erase(&value);
+    ///
+    /// let float = any.reinterpret::<Sample, f32>().unwrap();
+    /// assert_eq!(float.unerase::<f32>(), Some(&0.5));
+    /// ```
+    pub fn reinterpret<T, U>(self) -> Option<AnyRef<'a>>
+    where
+        T: TransparentAs<U>,
+        U: 'static,
+    {
+        self.contains::<T>().then(|| Self {
+            type_id: TypeId::of::<U>(),
+            ..self
+        })
+    }
+
+    /// Reinterpret the reference as one of a layout-compatible type `U`, if the registry allows it.
+    ///
+    /// Unlike [`AnyRef::reinterpret()`], this doesn't require naming the original type. If the registry
+    /// doesn't declare the original type compatible with `U`, you get `None`.
+    #[cfg(feature = "alloc")]
+    pub fn reinterpret_with<U: 'static>(
+        self,
+        registry: &TransparentRegistry,
+    ) -> Option<AnyRef<'a>> {
+        registry
+            .is_compatible(self.type_id, TypeId::of::<U>())
+            .then(|| Self {
+                type_id: TypeId::of::<U>(),
+                ..self
+            })
+    }
+
+    /// Unerase to a reference to whichever primitive type it refers to.
+    ///
+    /// If the original referee is a Rust primitive (any integer or float, `bool` or `char`), a
+    /// [`PrimitiveRef`] holding the typed reference is returned. Otherwise, you get `None`.
+    ///
+    /// ```
+    /// let data : u16 = 7;
+    /// let any = sashay::AnyRef::erase(&data);
+    ///
+    /// match any.primitive() {
+    ///     Some(sashay::PrimitiveRef::U16(value)) => assert_eq!(value, &7),
+    ///     _ => panic!("not a u16"),
+    /// }
+    /// ```
+    pub fn primitive(&self) -> Option<PrimitiveRef<'a>> {
+        PrimitiveRef::from_any(*self)
+    }
+
     /// Retrieve an unsafe immutable pointer to the raw data.
     pub const fn as_ptr(&self) -> *const () {
         self.ptr
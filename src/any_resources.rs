@@ -0,0 +1,504 @@
+use crate::{AnyMut, AnyRef};
+use alloc::vec::Vec;
+use core::{
+    any::TypeId,
+    cell::Cell,
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+/// A map of type-erased mutable references, keyed by their type and borrow-checked at runtime.
+///
+/// Every resource is an [`AnyMut`] and there can be at most one resource per type. Just like a
+/// [`RefCell`](core::cell::RefCell), borrowing a resource hands out a guard and keeps a counter,
+/// so that handing out the same resource mutably twice results in an error instead of aliasing.
+///
+/// ```
+/// let mut number : i32 = 7;
+/// let mut text : &str = "crab";
+///
+/// let mut resources = sashay::AnyResources::new();
+/// resources.insert(sashay::AnyMut::erase(&mut number));
+/// resources.insert(sashay::AnyMut::erase(&mut text));
+///
+/// {
+///     let mut number = resources.borrow_mut::<i32>().unwrap();
+///     *number += 1;
+///
+///     // The number is already mutably borrowed, so it can't be borrowed again
+///     assert!(resources.borrow::<i32>().is_err());
+///
+///     // But the text is still free
+///     assert_eq!(*resources.borrow::<&str>().unwrap(), "crab");
+/// }
+///
+/// drop(resources);
+/// assert_eq!(number, 8);
+/// ```
+#[derive(Debug, Default)]
+pub struct AnyResources<'a> {
+    /// The resources, sorted by their type id so we can binary search
+    resources: Vec<Resource<'a>>,
+}
+
+/// A single resource and its borrow state
+#[derive(Debug)]
+struct Resource<'a> {
+    /// The erased reference to the resource
+    any: AnyMut<'a>,
+
+    /// The number of active borrows
+    ///
+    /// A positive number counts the shared borrows, `EXCLUSIVE` means the resource is borrowed mutably
+    borrows: Cell<isize>,
+}
+
+/// The borrow count of a resource that is mutably borrowed
+const EXCLUSIVE: isize = -1;
+
+impl<'a> AnyResources<'a> {
+    /// Construct an empty resource map.
+    pub fn new() -> Self {
+        Self {
+            resources: Vec::new(),
+        }
+    }
+
+    /// Insert a resource.
+    ///
+    /// Resources are keyed by their type, so if a resource of the same type was already
+    /// present, it gets replaced and the old one is returned.
+    ///
+    /// ```
+    /// let mut a : i32 = 0;
+    /// let mut b : i32 = 1;
+    ///
+    /// let mut resources = sashay::AnyResources::new();
+    /// assert!(resources.insert(sashay::AnyMut::erase(&mut a)).is_none());
+    ///
+    /// let old = resources.insert(sashay::AnyMut::erase(&mut b)).unwrap();
+    /// assert_eq!(old.unerase::<i32>(), Some(&0));
+    /// ```
+    pub fn insert(&mut self, any: AnyMut<'a>) -> Option<AnyMut<'a>> {
+        let resource = Resource {
+            any,
+            borrows: Cell::new(0),
+        };
+
+        match self.find(resource.any.type_id()) {
+            Ok(index) => Some(core::mem::replace(&mut self.resources[index], resource).any),
+            Err(index) => {
+                self.resources.insert(index, resource);
+                None
+            }
+        }
+    }
+
+    /// Remove the resource of a given type, if present.
+    pub fn remove(&mut self, type_id: &TypeId) -> Option<AnyMut<'a>> {
+        self.find(type_id)
+            .ok()
+            .map(|index| self.resources.remove(index).any)
+    }
+
+    /// Immutably borrow the resource of type `T`.
+    ///
+    /// This fails if there is no resource of type `T`, or if it is currently borrowed mutably.
+    pub fn borrow<T: 'static>(&self) -> Result<ResourceRef<'_, T>, BorrowError> {
+        let resource = self.acquire_shared(&TypeId::of::<T>())?;
+
+        Ok(ResourceRef {
+            // SAFETY: The type id was the key we looked up with
+            value: unsafe { &*resource.any.as_ptr().cast::<T>() },
+            borrows: &resource.borrows,
+        })
+    }
+
+    /// Mutably borrow the resource of type `T`.
+    ///
+    /// This fails if there is no resource of type `T`, or if it is currently borrowed at all.
+    pub fn borrow_mut<T: 'static>(&self) -> Result<ResourceMut<'_, T>, BorrowError> {
+        let resource = self.acquire_exclusive(&TypeId::of::<T>())?;
+
+        Ok(ResourceMut {
+            // SAFETY:
+            // - The type id was the key we looked up with
+            // - The borrow counter guarantees that we're the only one with access
+            value: unsafe { &mut *resource.any.as_ptr().cast_mut().cast::<T>() },
+            borrows: &resource.borrows,
+        })
+    }
+
+    /// Immutably borrow a resource by its type id, without knowing the type.
+    pub fn borrow_erased(&self, type_id: &TypeId) -> Result<AnyResourceRef<'_>, BorrowError> {
+        self.acquire_shared(type_id).map(AnyResourceRef::new)
+    }
+
+    /// Mutably borrow a resource by its type id, without knowing the type.
+    pub fn borrow_mut_erased(&self, type_id: &TypeId) -> Result<AnyResourceMut<'_>, BorrowError> {
+        self.acquire_exclusive(type_id).map(AnyResourceMut::new)
+    }
+
+    /// Borrow a set of resources in one go.
+    ///
+    /// All `reads` are borrowed immutably and all `writes` mutably. The request is checked as a whole
+    /// before anything is borrowed, so either all guards are returned, or none are. Asking for the same
+    /// type twice in `writes`, or in both `reads` and `writes`, results in [`BorrowError::Conflict`].
+    ///
+    /// ```
+    /// use std::any::TypeId;
+    ///
+    /// let mut a : i32 = 0;
+    /// let mut b : f32 = 0.0;
+    ///
+    /// let mut resources = sashay::AnyResources::new();
+    /// resources.insert(sashay::AnyMut::erase(&mut a));
+    /// resources.insert(sashay::AnyMut::erase(&mut b));
+    ///
+    /// // Borrowing different types works...
+    /// let (reads, writes) = resources.borrow_many([TypeId::of::<i32>()], [TypeId::of::<f32>()]).unwrap();
+    /// assert_eq!(reads[0].get().unerase::<i32>(), Some(&0));
+    /// drop((reads, writes));
+    ///
+    /// // ...but you can't write the same type twice
+    /// let error = resources.borrow_many([], [TypeId::of::<f32>(), TypeId::of::<f32>()]).unwrap_err();
+    /// assert_eq!(error, sashay::BorrowError::Conflict(TypeId::of::<f32>()));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn borrow_many<const R: usize, const W: usize>(
+        &self,
+        reads: [TypeId; R],
+        writes: [TypeId; W],
+    ) -> Result<([AnyResourceRef<'_>; R], [AnyResourceMut<'_>; W]), BorrowError> {
+        let mut read_indices = [0; R];
+        for (type_id, index) in reads.iter().zip(&mut read_indices) {
+            if writes.contains(type_id) {
+                return Err(BorrowError::Conflict(*type_id));
+            }
+
+            *index = self.index_of(type_id)?;
+            if self.resources[*index].borrows.get() == EXCLUSIVE {
+                return Err(BorrowError::AlreadyBorrowed(*type_id));
+            }
+        }
+
+        let mut write_indices = [0; W];
+        for (i, (type_id, index)) in writes.iter().zip(&mut write_indices).enumerate() {
+            if writes[..i].contains(type_id) {
+                return Err(BorrowError::Conflict(*type_id));
+            }
+
+            *index = self.index_of(type_id)?;
+            if self.resources[*index].borrows.get() != 0 {
+                return Err(BorrowError::AlreadyBorrowed(*type_id));
+            }
+        }
+
+        // Everything checks out, so now we can actually borrow
+        let reads = read_indices.map(|index| {
+            let resource = &self.resources[index];
+            resource.borrows.set(resource.borrows.get() + 1);
+            AnyResourceRef::new(resource)
+        });
+
+        let writes = write_indices.map(|index| {
+            let resource = &self.resources[index];
+            resource.borrows.set(EXCLUSIVE);
+            AnyResourceMut::new(resource)
+        });
+
+        Ok((reads, writes))
+    }
+
+    /// Is there a resource of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.contains_erased(&TypeId::of::<T>())
+    }
+
+    /// Is there a resource with the given type id?
+    pub fn contains_erased(&self, type_id: &TypeId) -> bool {
+        self.find(type_id).is_ok()
+    }
+
+    /// How many resources does the map contain?
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// Does the map contain any resources at all?
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Binary search for the index of a resource with a given type id
+    fn find(&self, type_id: &TypeId) -> Result<usize, usize> {
+        self.resources
+            .binary_search_by(|resource| resource.any.type_id().cmp(type_id))
+    }
+
+    /// The index of a resource with a given type id, or an error if it's missing
+    fn index_of(&self, type_id: &TypeId) -> Result<usize, BorrowError> {
+        self.find(type_id)
+            .map_err(|_| BorrowError::Missing(*type_id))
+    }
+
+    /// Register a shared borrow of a resource
+    fn acquire_shared(&self, type_id: &TypeId) -> Result<&Resource<'a>, BorrowError> {
+        let resource = &self.resources[self.index_of(type_id)?];
+
+        match resource.borrows.get() {
+            EXCLUSIVE => Err(BorrowError::AlreadyBorrowed(*type_id)),
+            count => {
+                resource.borrows.set(count + 1);
+                Ok(resource)
+            }
+        }
+    }
+
+    /// Register an exclusive borrow of a resource
+    fn acquire_exclusive(&self, type_id: &TypeId) -> Result<&Resource<'a>, BorrowError> {
+        let resource = &self.resources[self.index_of(type_id)?];
+
+        match resource.borrows.get() {
+            0 => {
+                resource.borrows.set(EXCLUSIVE);
+                Ok(resource)
+            }
+            _ => Err(BorrowError::AlreadyBorrowed(*type_id)),
+        }
+    }
+}
+
+/// A typed, immutably borrowed resource from [`AnyResources`].
+///
+/// The borrow is released when the guard is dropped.
+#[derive(Debug)]
+pub struct ResourceRef<'b, T> {
+    value: &'b T,
+    borrows: &'b Cell<isize>,
+}
+
+impl<T> Deref for ResourceRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T> Drop for ResourceRef<'_, T> {
+    fn drop(&mut self) {
+        self.borrows.set(self.borrows.get() - 1);
+    }
+}
+
+/// A typed, mutably borrowed resource from [`AnyResources`].
+///
+/// The borrow is released when the guard is dropped.
+#[derive(Debug)]
+pub struct ResourceMut<'b, T> {
+    value: &'b mut T,
+    borrows: &'b Cell<isize>,
+}
+
+impl<T> Deref for ResourceMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T> DerefMut for ResourceMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+impl<T> Drop for ResourceMut<'_, T> {
+    fn drop(&mut self) {
+        self.borrows.set(0);
+    }
+}
+
+/// A type-erased, immutably borrowed resource from [`AnyResources`].
+///
+/// The borrow is released when the guard is dropped. Note that the erased reference can only be
+/// accessed through [`AnyResourceRef::get()`], so that it can't outlive the guard.
+#[derive(Debug)]
+pub struct AnyResourceRef<'b> {
+    ptr: *const (),
+    type_id: TypeId,
+    borrows: &'b Cell<isize>,
+}
+
+impl<'b> AnyResourceRef<'b> {
+    fn new(resource: &'b Resource) -> Self {
+        Self {
+            ptr: resource.any.as_ptr(),
+            type_id: *resource.any.type_id(),
+            borrows: &resource.borrows,
+        }
+    }
+
+    /// Access the erased resource.
+    pub fn get(&self) -> AnyRef<'_> {
+        // SAFETY: The parts came out of a valid `AnyMut`, and the borrow counter prevents mutable access
+        unsafe { AnyRef::from_raw_parts(self.ptr, self.type_id) }
+    }
+
+    /// A unique type id representing the resource type `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        &self.type_id
+    }
+}
+
+impl Drop for AnyResourceRef<'_> {
+    fn drop(&mut self) {
+        self.borrows.set(self.borrows.get() - 1);
+    }
+}
+
+/// A type-erased, mutably borrowed resource from [`AnyResources`].
+///
+/// The borrow is released when the guard is dropped. Note that the erased reference can only be
+/// accessed through [`AnyResourceMut::get()`] and [`AnyResourceMut::get_mut()`], so that it can't
+/// outlive the guard.
+#[derive(Debug)]
+pub struct AnyResourceMut<'b> {
+    ptr: *mut (),
+    type_id: TypeId,
+    borrows: &'b Cell<isize>,
+    _phantom: PhantomData<&'b mut ()>,
+}
+
+impl<'b> AnyResourceMut<'b> {
+    fn new(resource: &'b Resource) -> Self {
+        Self {
+            ptr: resource.any.as_ptr().cast_mut(),
+            type_id: *resource.any.type_id(),
+            borrows: &resource.borrows,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Immutably access the erased resource.
+    pub fn get(&self) -> AnyRef<'_> {
+        // SAFETY: The parts came out of a valid `AnyMut`
+        unsafe { AnyRef::from_raw_parts(self.ptr.cast_const(), self.type_id) }
+    }
+
+    /// Mutably access the erased resource.
+    pub fn get_mut(&mut self) -> AnyMut<'_> {
+        // SAFETY: The parts came out of a valid `AnyMut`, and the borrow counter guarantees unique access
+        unsafe { AnyMut::from_raw_parts(self.ptr, self.type_id) }
+    }
+
+    /// A unique type id representing the resource type `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        &self.type_id
+    }
+}
+
+impl Drop for AnyResourceMut<'_> {
+    fn drop(&mut self) {
+        self.borrows.set(0);
+    }
+}
+
+/// The reasons borrowing from [`AnyResources`] can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorrowError {
+    /// There is no resource of the requested type
+    Missing(TypeId),
+
+    /// The resource is already borrowed in a way that conflicts with the request
+    AlreadyBorrowed(TypeId),
+
+    /// The same resource was requested twice in an incompatible way
+    Conflict(TypeId),
+}
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(type_id) => write!(f, "no resource of type {type_id:?}"),
+            Self::AlreadyBorrowed(type_id) => {
+                write!(f, "resource of type {type_id:?} is already borrowed")
+            }
+            Self::Conflict(type_id) => {
+                write!(
+                    f,
+                    "resource of type {type_id:?} requested in conflicting ways"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for BorrowError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrow_counting() {
+        let mut a = 1i32;
+        let mut resources = AnyResources::new();
+        resources.insert(AnyMut::erase(&mut a));
+
+        let shared_a = resources.borrow::<i32>().unwrap();
+        let shared_b = resources.borrow_erased(&TypeId::of::<i32>()).unwrap();
+        assert_eq!(
+            resources.borrow_mut::<i32>().unwrap_err(),
+            BorrowError::AlreadyBorrowed(TypeId::of::<i32>())
+        );
+
+        drop((shared_a, shared_b));
+        let mut exclusive = resources.borrow_mut_erased(&TypeId::of::<i32>()).unwrap();
+        *exclusive.get_mut().unerase_mut::<i32>().unwrap() = 2;
+        assert!(resources.borrow::<i32>().is_err());
+
+        drop(exclusive);
+        assert_eq!(*resources.borrow::<i32>().unwrap(), 2);
+        assert_eq!(
+            resources.borrow::<u8>().unwrap_err(),
+            BorrowError::Missing(TypeId::of::<u8>())
+        );
+    }
+
+    #[test]
+    fn borrow_many_is_all_or_nothing() {
+        let mut a = 1i32;
+        let mut b = 2u8;
+        let mut resources = AnyResources::new();
+        resources.insert(AnyMut::erase(&mut a));
+        resources.insert(AnyMut::erase(&mut b));
+
+        let shared = resources.borrow::<u8>().unwrap();
+
+        // i32 is free, but u8 isn't, so nothing should get borrowed
+        let error = resources
+            .borrow_many([], [TypeId::of::<i32>(), TypeId::of::<u8>()])
+            .unwrap_err();
+        assert_eq!(error, BorrowError::AlreadyBorrowed(TypeId::of::<u8>()));
+        assert!(resources.borrow_mut::<i32>().is_ok());
+
+        let error = resources
+            .borrow_many([TypeId::of::<i32>()], [TypeId::of::<i32>()])
+            .unwrap_err();
+        assert_eq!(error, BorrowError::Conflict(TypeId::of::<i32>()));
+
+        drop(shared);
+        let (_, mut writes) = resources
+            .borrow_many([], [TypeId::of::<i32>(), TypeId::of::<u8>()])
+            .unwrap();
+        *writes[1].get_mut().unerase_mut::<u8>().unwrap() = 3;
+        drop(writes);
+
+        drop(resources);
+        assert_eq!(b, 3);
+    }
+}
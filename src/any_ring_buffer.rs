@@ -0,0 +1,536 @@
+use crate::{AnyMut, AnyRef, AnySliceMut, AnySliceRef, AnySliceUninit, TypeInfo};
+use core::{any::TypeId, fmt, marker::PhantomData, ptr};
+
+/// A type-erased first-in first-out ring buffer of same-typed elements.
+///
+/// Elements are pushed at the back and popped from the front, wrapping around a fixed-capacity buffer
+/// whose element type is only known at runtime. This is a good fit for event queues and delay lines.
+///
+/// The storage is either borrowed from a caller-supplied [`AnySliceUninit`] (see [`AnyRingBuffer::new()`]),
+/// which never allocates, or owned by the ring buffer itself (see [`AnyRingBuffer::with_capacity()`]).
+/// Either way, elements still in the buffer are dropped when it is dropped.
+///
+/// ```
+/// use std::mem::MaybeUninit;
+///
+/// let mut storage = [MaybeUninit::<i32>::uninit(); 3];
+/// let mut ring = sashay::AnyRingBuffer::new(sashay::AnySliceUninit::erase_copy(storage.as_mut_slice()));
+///
+/// ring.push(1i32);
+/// ring.push(2i32);
+/// assert_eq!(ring.pop::<i32>(), Some(1));
+///
+/// // Wrap around the end of the storage
+/// ring.push(3i32);
+/// ring.push_from(sashay::AnyRef::erase(&4i32)).unwrap();
+/// assert!(ring.is_full());
+///
+/// let (front, back) = ring.as_slices();
+/// assert_eq!(front.unerase::<i32>(), Some([2, 3].as_slice()));
+/// assert_eq!(back.unerase::<i32>(), Some([4].as_slice()));
+/// ```
+pub struct AnyRingBuffer<'a> {
+    /// A raw pointer to the start of the storage
+    ///
+    /// Note: this pointer must be aligned and point to `capacity` possibly uninitialized values of `T`,
+    /// of which the `len` starting at `head` (wrapping around) are initialized
+    ptr: *mut u8,
+
+    /// The number of elements that fit in the storage
+    capacity: usize,
+
+    /// The index in the storage of the front element
+    head: usize,
+
+    /// The number of elements in the buffer
+    len: usize,
+
+    /// The type of the elements
+    info: TypeInfo,
+
+    /// Was the storage allocated by the ring buffer itself, so it needs to be deallocated?
+    owned: bool,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a mut ()>,
+}
+
+impl<'a> AnyRingBuffer<'a> {
+    /// Construct a ring buffer on top of borrowed uninitialized storage.
+    ///
+    /// The capacity is the length of the storage. Elements already in its initialized prefix become
+    /// the initial contents of the buffer, in order.
+    pub fn new(mut storage: AnySliceUninit<'a>) -> Self {
+        Self {
+            ptr: storage.as_mut_ptr().cast::<u8>(),
+            capacity: storage.len(),
+            head: 0,
+            len: storage.initialized_len(),
+            info: *storage.type_info(),
+            owned: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Append an element at the back of the buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is full, or if `T` is not the element type.
+    pub fn push<T: 'static>(&mut self, value: T) {
+        assert!(self.contains::<T>(), "pushed a value of the wrong type");
+        if self.try_push(value).is_err() {
+            panic!("pushed beyond the capacity of {}", self.capacity);
+        }
+    }
+
+    /// Append an element at the back of the buffer, if there is room for it.
+    ///
+    /// If the buffer is full, or if `T` is not the element type, you get the value back.
+    pub fn try_push<T: 'static>(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() || !self.contains::<T>() {
+            return Err(value);
+        }
+
+        // SAFETY: The slot after the back element is within the storage and uninitialized, and of type T
+        unsafe { self.slot(self.len).cast::<T>().write(value) };
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Append a copy of an erased value at the back of the buffer.
+    ///
+    /// This only works if the element type is known to be `Copy` (see [`TypeInfo::is_copy()`]).
+    /// Otherwise, or if the buffer is full or the value has the wrong type, you get an error.
+    pub fn push_from(&mut self, value: AnyRef<'_>) -> Result<(), RingBufferError> {
+        self.check_type(*value.type_id())?;
+
+        if !self.info.is_copy() {
+            return Err(RingBufferError::NotCopy);
+        } else if self.is_full() {
+            return Err(RingBufferError::Full);
+        }
+
+        // SAFETY:
+        // - The value has the element type, which is Copy
+        // - The slot after the back element is within the storage and uninitialized
+        unsafe {
+            ptr::copy_nonoverlapping(
+                value.as_ptr().cast::<u8>(),
+                self.slot(self.len),
+                self.info.stride(),
+            );
+        }
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Remove the front element from the buffer and return it.
+    ///
+    /// If the buffer is empty, or if `T` is not the element type, you get `None`.
+    pub fn pop<T: 'static>(&mut self) -> Option<T> {
+        if self.is_empty() || !self.contains::<T>() {
+            return None;
+        }
+
+        // SAFETY: The front element is initialized, and is removed from the buffer right after
+        let value = unsafe { self.slot(0).cast::<T>().read() };
+        self.advance();
+
+        Some(value)
+    }
+
+    /// Remove the front element from the buffer and move it into an erased destination.
+    ///
+    /// The old value of the destination is dropped. This works for any element type, `Copy` or not.
+    /// If the buffer is empty or the destination has the wrong type, you get an error.
+    pub fn pop_into(&mut self, mut destination: AnyMut<'_>) -> Result<(), RingBufferError> {
+        self.check_type(*destination.type_id())?;
+
+        if self.is_empty() {
+            return Err(RingBufferError::Empty);
+        }
+
+        // Swap the values first, so the buffer is consistent if dropping the old value panics
+        let front = self.slot(0);
+
+        // SAFETY:
+        // - The destination has the element type, and the front element is initialized
+        // - We hold a mutable reference to the buffer, so the destination can't overlap with it
+        unsafe {
+            ptr::swap_nonoverlapping(
+                front,
+                destination.as_mut_ptr().cast::<u8>(),
+                self.info.stride(),
+            )
+        };
+        self.advance();
+
+        // SAFETY: The slot holds the old value of the destination, and is no longer part of the buffer
+        unsafe { self.info.drop_in_place(front.cast::<()>()) };
+
+        Ok(())
+    }
+
+    /// Remove the front element from the buffer and drop it.
+    ///
+    /// Returns whether there was an element to remove.
+    pub fn discard(&mut self) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        let front = self.slot(0);
+        self.advance();
+
+        // SAFETY: The element was initialized, and is no longer part of the buffer
+        unsafe { self.info.drop_in_place(front.cast::<()>()) };
+
+        true
+    }
+
+    /// Remove and drop all elements.
+    pub fn clear(&mut self) {
+        while self.discard() {}
+    }
+
+    /// Retrieve an immutable reference to the front element.
+    pub fn front(&self) -> Option<AnyRef<'_>> {
+        self.get(0)
+    }
+
+    /// Retrieve an immutable reference to an element, counting from the front.
+    pub fn get(&self, index: usize) -> Option<AnyRef<'_>> {
+        // SAFETY: The element is within the buffer, so it's initialized
+        (index < self.len).then(|| unsafe {
+            AnyRef::from_raw_parts(
+                self.slot(index).cast_const().cast::<()>(),
+                *self.info.type_id(),
+            )
+        })
+    }
+
+    /// Retrieve a mutable reference to an element, counting from the front.
+    pub fn get_mut(&mut self, index: usize) -> Option<AnyMut<'_>> {
+        // SAFETY: The element is within the buffer, so it's initialized
+        (index < self.len).then(|| unsafe {
+            AnyMut::from_raw_parts(self.slot(index).cast::<()>(), *self.info.type_id())
+        })
+    }
+
+    /// Access the elements as two erased slices, which together contain all elements in order.
+    ///
+    /// The second slice is only non-empty if the elements wrap around the end of the storage.
+    pub fn as_slices(&self) -> (AnySliceRef<'_>, AnySliceRef<'_>) {
+        let (front, back) = self.halves();
+        let (start, stride, type_id) = (self.start(), self.info.stride(), *self.info.type_id());
+
+        // SAFETY: The halves only cover initialized elements, and don't overlap
+        unsafe {
+            (
+                AnySliceRef::from_raw_parts(
+                    start.cast_const().cast::<()>(),
+                    front,
+                    stride,
+                    type_id,
+                ),
+                AnySliceRef::from_raw_parts(
+                    self.ptr.cast_const().cast::<()>(),
+                    back,
+                    stride,
+                    type_id,
+                ),
+            )
+        }
+    }
+
+    /// Mutably access the elements as two erased slices, which together contain all elements in order.
+    ///
+    /// The second slice is only non-empty if the elements wrap around the end of the storage.
+    pub fn as_mut_slices(&mut self) -> (AnySliceMut<'_>, AnySliceMut<'_>) {
+        let (front, back) = self.halves();
+        let (start, stride, type_id) = (self.start(), self.info.stride(), *self.info.type_id());
+
+        // SAFETY: The halves only cover initialized elements, and don't overlap
+        unsafe {
+            (
+                AnySliceMut::from_raw_parts(start.cast::<()>(), front, stride, type_id),
+                AnySliceMut::from_raw_parts(self.ptr.cast::<()>(), back, stride, type_id),
+            )
+        }
+    }
+
+    /// How many elements does the buffer contain?
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Does the buffer contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many elements fit in the buffer?
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Is the buffer at its capacity?
+    pub const fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    /// Are the elements of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.info.is::<T>()
+    }
+
+    /// The `size_of()` of the elements.
+    pub const fn stride(&self) -> usize {
+        self.info.stride()
+    }
+
+    /// A unique type id representing the element type.
+    pub const fn type_id(&self) -> &TypeId {
+        self.info.type_id()
+    }
+
+    /// The runtime information about the element type.
+    pub const fn type_info(&self) -> &TypeInfo {
+        &self.info
+    }
+
+    /// Check that values of type `type_id` can be stored in the buffer
+    fn check_type(&self, type_id: TypeId) -> Result<(), RingBufferError> {
+        if type_id == *self.info.type_id() {
+            Ok(())
+        } else {
+            Err(RingBufferError::TypeMismatch {
+                expected: *self.info.type_id(),
+                found: type_id,
+            })
+        }
+    }
+
+    /// Drop the front element from the buffer, without dropping its value
+    fn advance(&mut self) {
+        self.head = (self.head + 1) % self.capacity;
+        self.len -= 1;
+    }
+
+    /// The number of elements before and after wrapping around the end of the storage
+    fn halves(&self) -> (usize, usize) {
+        let front = self.len.min(self.capacity - self.head);
+        (front, self.len - front)
+    }
+
+    /// A pointer to the storage slot of the front element, or to the storage if the buffer is empty
+    ///
+    /// Unlike [`slot()`](Self::slot), this is also valid for a buffer without capacity.
+    fn start(&self) -> *mut u8 {
+        if self.is_empty() {
+            self.ptr
+        } else {
+            self.slot(0)
+        }
+    }
+
+    /// A pointer to the storage slot of the element at `index` counted from the front
+    ///
+    /// The index must be below the capacity, which can't be zero.
+    fn slot(&self, index: usize) -> *mut u8 {
+        let wrapped = (self.head + index) % self.capacity;
+        self.ptr.wrapping_add(wrapped * self.info.stride())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AnyRingBuffer<'static> {
+    /// Construct an empty ring buffer of elements of type `T`, that owns its storage.
+    ///
+    /// This allocates once, up front. `Copy` types can't be detected automatically, so use
+    /// [`AnyRingBuffer::with_type_info()`] with [`TypeInfo::of_copy()`] to allow [`AnyRingBuffer::push_from()`].
+    pub fn with_capacity<T: 'static>(capacity: usize) -> Self {
+        Self::with_type_info(TypeInfo::of::<T>(), capacity)
+    }
+
+    /// Construct an empty ring buffer of elements of a described type, that owns its storage.
+    ///
+    /// ```
+    /// let mut ring = sashay::AnyRingBuffer::with_type_info(sashay::TypeInfo::of_copy::<u8>(), 2);
+    ///
+    /// ring.push_from(sashay::AnyRef::erase(&7u8)).unwrap();
+    /// assert_eq!(ring.pop::<u8>(), Some(7));
+    /// ```
+    pub fn with_type_info(info: TypeInfo, capacity: usize) -> Self {
+        Self {
+            ptr: info.allocate_array(capacity),
+            capacity,
+            head: 0,
+            len: 0,
+            info,
+            owned: true,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl Drop for AnyRingBuffer<'_> {
+    fn drop(&mut self) {
+        self.clear();
+
+        #[cfg(feature = "alloc")]
+        if self.owned {
+            // SAFETY: The storage was allocated with the same type information and capacity
+            unsafe { self.info.deallocate_array(self.ptr, self.capacity) };
+        }
+    }
+}
+
+impl fmt::Debug for AnyRingBuffer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyRingBuffer")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity)
+            .field("info", &self.info)
+            .field("owned", &self.owned)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> From<AnySliceUninit<'a>> for AnyRingBuffer<'a> {
+    fn from(storage: AnySliceUninit<'a>) -> Self {
+        Self::new(storage)
+    }
+}
+
+/// The reasons pushing to or popping from a ring buffer can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RingBufferError {
+    /// The value has a different type than the elements
+    TypeMismatch { expected: TypeId, found: TypeId },
+
+    /// The buffer is at its capacity
+    Full,
+
+    /// The buffer contains no elements
+    Empty,
+
+    /// The element type is not known to be `Copy`, so it can't be copied from an erased reference
+    NotCopy,
+}
+
+impl fmt::Display for RingBufferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected a value of {expected:?}, but got {found:?}")
+            }
+            Self::Full => write!(f, "the ring buffer is full"),
+            Self::Empty => write!(f, "the ring buffer is empty"),
+            Self::NotCopy => write!(f, "the element type is not known to be `Copy`"),
+        }
+    }
+}
+
+impl core::error::Error for RingBufferError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{DropCounter, Tracked};
+    use core::mem::MaybeUninit;
+
+    // These tests use an (u8, u16) because it has padding, and `Tracked` values to count drops
+
+    #[test]
+    fn wrap_around() {
+        let mut storage = [MaybeUninit::<(u8, u16)>::uninit(); 3];
+        let mut ring = AnyRingBuffer::new(AnySliceUninit::erase_copy(storage.as_mut_slice()));
+
+        for value in 0..3u8 {
+            ring.push((value, 0u16));
+        }
+        assert_eq!(ring.try_push((3u8, 0u16)), Err((3u8, 0u16)));
+        assert_eq!(
+            ring.push_from(AnyRef::erase(&(3u8, 0u16))),
+            Err(RingBufferError::Full)
+        );
+        assert_eq!(ring.pop::<(u8, u16)>(), Some((0u8, 0u16)));
+        assert_eq!(ring.pop::<u8>(), None);
+
+        ring.push_from(AnyRef::erase(&(3u8, 0u16))).unwrap();
+        assert_eq!(
+            ring.get(2).unwrap().unerase::<(u8, u16)>(),
+            Some(&(3u8, 0u16))
+        );
+
+        let (mut front, mut back) = ring.as_mut_slices();
+        front.unerase_mut::<(u8, u16)>().unwrap()[0].1 = 10;
+        back.unerase_mut::<(u8, u16)>().unwrap()[0].1 = 30;
+        let (front, back) = ring.as_slices();
+        assert_eq!(
+            front.unerase::<(u8, u16)>(),
+            Some([(1u8, 10u16), (2u8, 0u16)].as_slice())
+        );
+        assert_eq!(back.unerase::<(u8, u16)>(), Some([(3u8, 30u16)].as_slice()));
+
+        let mut out = (0u8, 0u16);
+        ring.pop_into(AnyMut::erase(&mut out)).unwrap();
+        assert_eq!(out, (1u8, 10u16));
+        assert!(matches!(
+            ring.pop_into(AnyMut::erase(&mut 0u8)),
+            Err(RingBufferError::TypeMismatch { .. })
+        ));
+
+        ring.clear();
+        assert!(ring.is_empty());
+        assert_eq!(
+            ring.pop_into(AnyMut::erase(&mut out)),
+            Err(RingBufferError::Empty)
+        );
+    }
+
+    #[test]
+    fn drops() {
+        static DROPS: DropCounter = DropCounter::new();
+
+        let mut storage: [MaybeUninit<Tracked<u8>>; 2] = [const { MaybeUninit::uninit() }; 2];
+        let mut ring = AnyRingBuffer::new(AnySliceUninit::erase(storage.as_mut_slice()));
+
+        ring.push(DROPS.track(1u8));
+        ring.push(DROPS.track(2u8));
+        assert_eq!(
+            ring.push_from(AnyRef::erase(&DROPS.track(3u8))),
+            Err(RingBufferError::NotCopy)
+        );
+        assert_eq!(DROPS.drops(), 1);
+
+        // Moving out drops the old destination value, but not the moved one
+        let mut out = DROPS.track(0u8);
+        ring.pop_into(AnyMut::erase(&mut out)).unwrap();
+        assert_eq!((out.value, DROPS.drops()), (1, 2));
+
+        ring.push(DROPS.track(4u8));
+        drop(ring);
+        assert_eq!(DROPS.drops(), 4);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut storage: [MaybeUninit<(u8, u16)>; 0] = [];
+        let mut ring = AnyRingBuffer::new(AnySliceUninit::erase_copy(storage.as_mut_slice()));
+
+        assert!(ring.is_full());
+        assert_eq!(ring.try_push((0u8, 0u16)), Err((0u8, 0u16)));
+        assert_eq!(ring.pop::<(u8, u16)>(), None);
+        assert!(!ring.discard());
+
+        let (front, back) = ring.as_slices();
+        assert!(front.is_empty() && back.is_empty());
+        let (front, back) = ring.as_mut_slices();
+        assert!(front.is_empty() && back.is_empty());
+    }
+}
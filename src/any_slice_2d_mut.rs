@@ -0,0 +1,494 @@
+use crate::{
+    slice_2d::fits, AnyMut, AnyRef, AnySlice2dRef, AnySliceMut, AnySliceRef, Slice2dMut, Slice2dRef,
+};
+use core::{any::TypeId, marker::PhantomData, mem::size_of};
+
+/// A type-erased mutable two-dimensional view into rows of elements.
+///
+/// This describes `height` rows of `width` elements each, where the start of every row lies `pitch`
+/// bytes after the start of the previous one. See [`AnySlice2dRef`] for more information.
+///
+/// Unerasing gives you a [`Slice2dMut`].
+///
+/// ```
+/// let mut data : [u8; 8] = [1, 2, 0, 0, 3, 4, 0, 0];
+/// let slice = sashay::AnySliceMut::erase(data.as_mut_slice());
+/// let mut any = sashay::AnySlice2dMut::new(slice, 2, 2, 4).unwrap();
+///
+/// any.row_mut(1).unwrap().unerase_mut::<u8>().unwrap().fill(9);
+///
+/// assert_eq!(data, [1, 2, 0, 0, 9, 9, 0, 0]);
+/// ```
+#[derive(Debug)]
+pub struct AnySlice2dMut<'a> {
+    /// A raw pointer to the first element of the first row
+    ///
+    /// Note: this pointer must be aligned and point to `width` valid values of `T` at
+    /// subsequent positions along the pitch
+    ptr: *mut u8,
+
+    /// The number of elements in each row
+    width: usize,
+
+    /// The number of rows
+    height: usize,
+
+    /// The `size_of()` of the individual elements
+    stride: usize,
+
+    /// The distance in bytes from the start of one row to the next
+    pitch: usize,
+
+    /// A unique id representing the type of the referenced elements
+    ///
+    /// This is used to ensure we can safely unerase back without accidentally transmuting
+    type_id: TypeId,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a mut ()>,
+}
+
+impl<'a> AnySlice2dMut<'a> {
+    /// View a mutable erased slice as rows of `width` elements, that start `pitch` bytes apart.
+    ///
+    /// Because the alignment of the erased elements is unknown, `pitch` must be a multiple of their stride.
+    /// If it isn't, or if the rows don't fit within the slice or overlap, you get `None`. To use any pitch
+    /// that keeps the elements aligned, erase a [`Slice2dMut`] instead.
+    pub fn new(
+        mut slice: AnySliceMut<'a>,
+        width: usize,
+        height: usize,
+        pitch: usize,
+    ) -> Option<Self> {
+        let stride = slice.stride();
+        let fits = fits(slice.len() * stride, width, height, stride, pitch)
+            && pitch.is_multiple_of(stride);
+
+        // SAFETY:
+        // - We've checked that all rows fall within the slice, and don't overlap
+        // - Rows start a multiple of the stride apart, and strides are a multiple of the alignment
+        fits.then(|| unsafe {
+            Self::from_raw_parts(
+                slice.as_mut_ptr(),
+                width,
+                height,
+                stride,
+                pitch,
+                *slice.type_id(),
+            )
+        })
+    }
+
+    /// Erase the type of a mutable two-dimensional view's elements.
+    pub fn erase<T: 'static>(mut view: Slice2dMut<'a, T>) -> AnySlice2dMut<'a> {
+        // SAFETY: The raw parts come from a valid view, and the TypeId and stride are provided by the compiler
+        unsafe {
+            Self::from_raw_parts(
+                view.as_mut_ptr().cast::<()>(),
+                view.width(),
+                view.height(),
+                size_of::<T>(),
+                view.pitch(),
+                TypeId::of::<T>(),
+            )
+        }
+    }
+
+    /// Construct an erased mutable two-dimensional view from its raw parts.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if:
+    ///  - For every row below `height`, `ptr` offset by `row * pitch` bytes points to `width` valid and aligned consecutive values of `T`
+    ///  - None of the rows overlap, and they aren't accessed through any other pointer for the duration of `'a`
+    ///  - `stride` is the correct [`size_of()`](https://doc.rust-lang.org/std/mem/fn.size_of.html) for the element type `T`
+    ///  - `type_id` is the correct [`TypeId`](https://doc.rust-lang.org/stable/std/any/struct.TypeId.html) for the element type `T`
+    pub unsafe fn from_raw_parts(
+        ptr: *mut (),
+        width: usize,
+        height: usize,
+        stride: usize,
+        pitch: usize,
+        type_id: TypeId,
+    ) -> Self {
+        Self {
+            ptr: ptr.cast::<u8>(),
+            width,
+            height,
+            stride,
+            pitch,
+            type_id,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Unerase back to an immutable two-dimensional view.
+    pub fn unerase<T: 'static>(&self) -> Option<Slice2dRef<'_, T>> {
+        self.borrow().unerase_into()
+    }
+
+    /// Unerase back to a mutable two-dimensional view.
+    ///
+    /// If the original element type was `T`, a valid view is returned. Otherwise, you get `None`.
+    pub fn unerase_mut<T: 'static>(&mut self) -> Option<Slice2dMut<'_, T>> {
+        self.borrow_mut().unerase_into()
+    }
+
+    /// Unerase back into a mutable two-dimensional view, transferring ownership into it.
+    pub fn unerase_into<T: 'static>(self) -> Option<Slice2dMut<'a, T>> {
+        self.contains::<T>().then(|| {
+            // SAFETY:
+            // - We've checked the TypeId of T against the one created at construction, so we're not
+            //   accidentally transmuting to a different type
+            // - The rows are valid and don't overlap as per construction
+            unsafe {
+                Slice2dMut::from_raw_parts(
+                    self.ptr.cast::<T>(),
+                    self.width,
+                    self.height,
+                    self.pitch,
+                )
+            }
+        })
+    }
+
+    /// Borrow this mutable view as an immutable one.
+    pub fn borrow(&self) -> AnySlice2dRef<'_> {
+        // SAFETY: All parts are valid, and we have an immutable reference to self
+        unsafe {
+            AnySlice2dRef::from_raw_parts(
+                self.ptr.cast_const().cast::<()>(),
+                self.width,
+                self.height,
+                self.stride,
+                self.pitch,
+                self.type_id,
+            )
+        }
+    }
+
+    /// Borrow this mutable view as a mutable view with lifetime 'self.
+    pub fn borrow_mut(&mut self) -> AnySlice2dMut<'_> {
+        // SAFETY: All parts are valid, and we have a mutable reference to self
+        unsafe {
+            AnySlice2dMut::from_raw_parts(
+                self.ptr.cast::<()>(),
+                self.width,
+                self.height,
+                self.stride,
+                self.pitch,
+                self.type_id,
+            )
+        }
+    }
+
+    /// Retrieve one of the rows as an immutable erased slice.
+    pub fn row(&self, y: usize) -> Option<AnySliceRef<'_>> {
+        // SAFETY: The row is within bounds, so it's a valid slice as per construction
+        (y < self.height).then(|| unsafe {
+            AnySliceRef::from_raw_parts(
+                self.row_ptr(y).cast_const(),
+                self.width,
+                self.stride,
+                self.type_id,
+            )
+        })
+    }
+
+    /// Retrieve one of the rows as a mutable erased slice.
+    pub fn row_mut(&mut self, y: usize) -> Option<AnySliceMut<'_>> {
+        // SAFETY: The row is within bounds, so it's a valid slice as per construction
+        (y < self.height).then(|| unsafe {
+            AnySliceMut::from_raw_parts(self.row_ptr(y), self.width, self.stride, self.type_id)
+        })
+    }
+
+    /// Retrieve an immutable reference to the element in column `x` and row `y`.
+    pub fn get(&self, x: usize, y: usize) -> Option<AnyRef<'_>> {
+        // SAFETY: The element is within bounds, so it's valid as per construction
+        (x < self.width && y < self.height).then(|| unsafe {
+            AnyRef::from_raw_parts(self.element(x, y).cast_const(), self.type_id)
+        })
+    }
+
+    /// Retrieve a mutable reference to the element in column `x` and row `y`.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<AnyMut<'_>> {
+        // SAFETY: The element is within bounds, so it's valid as per construction
+        (x < self.width && y < self.height)
+            .then(|| unsafe { AnyMut::from_raw_parts(self.element(x, y), self.type_id) })
+    }
+
+    /// Iterate over all rows as immutable erased slices, from top to bottom.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = AnySliceRef<'_>> + ExactSizeIterator {
+        // SAFETY: The row is within bounds, so it's a valid slice as per construction
+        (0..self.height).map(|y| unsafe {
+            AnySliceRef::from_raw_parts(
+                self.row_ptr(y).cast_const(),
+                self.width,
+                self.stride,
+                self.type_id,
+            )
+        })
+    }
+
+    /// Iterate over all rows as mutable erased slices, from top to bottom.
+    pub fn rows_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = AnySliceMut<'_>> + ExactSizeIterator {
+        let this = &*self;
+
+        // SAFETY:
+        // - The row is within bounds, so it's a valid slice as per construction
+        // - Every row is only visited once, and rows don't overlap, so the slices are unique
+        (0..self.height).map(move |y| unsafe {
+            AnySliceMut::from_raw_parts(this.row_ptr(y), this.width, this.stride, this.type_id)
+        })
+    }
+
+    /// Access an immutable rectangular part of the view.
+    ///
+    /// See [`AnySlice2dRef::sub_rect()`] for more information.
+    pub fn sub_rect(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<AnySlice2dRef<'_>> {
+        self.borrow().sub_rect_into(x, y, width, height)
+    }
+
+    /// Access a mutable rectangular part of the view.
+    ///
+    /// The rectangle starts at column `x` and row `y`, and is `width` by `height` elements large. If it
+    /// doesn't fall entirely within the view, you get `None`.
+    pub fn sub_rect_mut(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<AnySlice2dMut<'_>> {
+        self.borrow_mut().sub_rect_into(x, y, width, height)
+    }
+
+    /// Access a mutable rectangular part of the view, transferring ownership into it.
+    pub fn sub_rect_into(
+        self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<AnySlice2dMut<'a>> {
+        let fits = x.checked_add(width).is_some_and(|end| end <= self.width)
+            && y.checked_add(height).is_some_and(|end| end <= self.height);
+
+        // SAFETY: The rectangle falls within the view, and keeps its pitch
+        fits.then(|| unsafe {
+            Self::from_raw_parts(
+                self.element(x, y),
+                width,
+                height,
+                self.stride,
+                self.pitch,
+                self.type_id,
+            )
+        })
+    }
+
+    /// Split the view into two disjoint mutable views, one with the rows above `y` and one with the rest.
+    ///
+    /// If `y` is larger than the height, you get `None`.
+    ///
+    /// ```
+    /// let mut data : [u8; 6] = [0; 6];
+    /// let slice = sashay::AnySliceMut::erase(data.as_mut_slice());
+    /// let mut any = sashay::AnySlice2dMut::new(slice, 2, 3, 2).unwrap();
+    ///
+    /// let (mut top, mut bottom) = any.split_rows_mut(1).unwrap();
+    /// assert_eq!((top.height(), bottom.height()), (1, 2));
+    ///
+    /// // Both halves can be used at the same time
+    /// top.unerase_mut::<u8>().unwrap().rows_mut().for_each(|row| row.fill(1));
+    /// bottom.unerase_mut::<u8>().unwrap().rows_mut().for_each(|row| row.fill(2));
+    ///
+    /// assert_eq!(data, [1, 1, 2, 2, 2, 2]);
+    /// ```
+    pub fn split_rows_mut(&mut self, y: usize) -> Option<(AnySlice2dMut<'_>, AnySlice2dMut<'_>)> {
+        if y > self.height {
+            return None;
+        }
+
+        // SAFETY: Both halves fall within the view and have no rows in common
+        let halves = unsafe {
+            (
+                AnySlice2dMut::from_raw_parts(
+                    self.row_ptr(0),
+                    self.width,
+                    y,
+                    self.stride,
+                    self.pitch,
+                    self.type_id,
+                ),
+                AnySlice2dMut::from_raw_parts(
+                    self.row_ptr(y),
+                    self.width,
+                    self.height - y,
+                    self.stride,
+                    self.pitch,
+                    self.type_id,
+                ),
+            )
+        };
+
+        Some(halves)
+    }
+
+    /// View all elements as a single immutable erased slice, if there is no padding between the rows.
+    pub fn as_slice(&self) -> Option<AnySliceRef<'_>> {
+        // SAFETY: There is no padding, so the rows form one valid slice
+        self.is_contiguous().then(|| unsafe {
+            AnySliceRef::from_raw_parts(
+                self.as_ptr(),
+                self.width * self.height,
+                self.stride,
+                self.type_id,
+            )
+        })
+    }
+
+    /// View all elements as a single mutable erased slice, if there is no padding between the rows.
+    pub fn as_slice_mut(&mut self) -> Option<AnySliceMut<'_>> {
+        // SAFETY: There is no padding, so the rows form one valid slice
+        self.is_contiguous().then(|| unsafe {
+            AnySliceMut::from_raw_parts(
+                self.as_mut_ptr(),
+                self.width * self.height,
+                self.stride,
+                self.type_id,
+            )
+        })
+    }
+
+    /// Do the rows directly follow each other in memory, without padding?
+    pub const fn is_contiguous(&self) -> bool {
+        self.height <= 1 || self.pitch == self.width * self.stride
+    }
+
+    /// Retrieve an unsafe pointer to the first element of the first row.
+    pub const fn as_ptr(&self) -> *const () {
+        self.ptr.cast_const().cast::<()>()
+    }
+
+    /// Retrieve an unsafe mutable pointer to the first element of the first row.
+    pub fn as_mut_ptr(&mut self) -> *mut () {
+        self.ptr.cast::<()>()
+    }
+
+    /// The number of elements in each row.
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows.
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Does the view contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Was the original element of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        TypeId::of::<T>() == self.type_id
+    }
+
+    /// The `size_of()` of the original elements of type `T`.
+    pub const fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// The distance in bytes from the start of one row to the next.
+    pub const fn pitch(&self) -> usize {
+        self.pitch
+    }
+
+    /// A unique type id representing the original element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        &self.type_id
+    }
+
+    /// A pointer to the first element of row `y`, which doesn't need to be in bounds
+    fn row_ptr(&self, y: usize) -> *mut () {
+        self.element(0, y)
+    }
+
+    /// A pointer to the element in column `x` and row `y`, which doesn't need to be in bounds
+    fn element(&self, x: usize, y: usize) -> *mut () {
+        self.ptr
+            .wrapping_add(y * self.pitch + x * self.stride)
+            .cast::<()>()
+    }
+}
+
+impl<'a, T: 'static> From<Slice2dMut<'a, T>> for AnySlice2dMut<'a> {
+    fn from(view: Slice2dMut<'a, T>) -> Self {
+        Self::erase(view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // All these tests use an (u8, u16) because it has padding
+
+    #[test]
+    fn access() {
+        let mut data = [(0u8, 0u16); 6];
+        let slice = AnySliceMut::erase(data.as_mut_slice());
+        let mut any = AnySlice2dMut::new(slice, 2, 2, 3 * size_of::<(u8, u16)>()).unwrap();
+
+        *any.get_mut(1, 0)
+            .unwrap()
+            .unerase_mut::<(u8, u16)>()
+            .unwrap() = (1, 1);
+        any.row_mut(1)
+            .unwrap()
+            .unerase_mut::<(u8, u16)>()
+            .unwrap()
+            .fill((2, 2));
+        assert!(any.get_mut(0, 2).is_none());
+        assert!(any.as_slice_mut().is_none());
+
+        let mut sub = any.sub_rect_mut(1, 0, 1, 2).unwrap();
+        for mut row in sub.rows_mut() {
+            row.unerase_mut::<(u8, u16)>().unwrap()[0].0 += 10;
+        }
+
+        assert_eq!(data, [(0, 0), (11, 1), (0, 0), (2, 2), (12, 2), (0, 0)]);
+    }
+
+    #[test]
+    fn split() {
+        let mut data = [(0u8, 0u16); 6];
+        let slice = AnySliceMut::erase(data.as_mut_slice());
+        let mut any = AnySlice2dMut::new(slice, 3, 2, 3 * size_of::<(u8, u16)>()).unwrap();
+        assert!(any.split_rows_mut(3).is_none());
+
+        let (mut top, mut bottom) = any.split_rows_mut(1).unwrap();
+        top.as_slice_mut()
+            .unwrap()
+            .unerase_mut::<(u8, u16)>()
+            .unwrap()
+            .fill((1, 1));
+        bottom.unerase_mut::<(u8, u16)>().unwrap()[(2, 0)] = (2, 2);
+
+        let (empty, _) = any.split_rows_mut(0).unwrap();
+        assert!(empty.is_empty());
+
+        assert_eq!(data, [(1, 1), (1, 1), (1, 1), (0, 0), (0, 0), (2, 2)]);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{slice_2d::fits, AnyRef, AnySliceRef, Slice2dRef};
+use core::{any::TypeId, marker::PhantomData, mem::size_of};
+
+/// A type-erased immutable two-dimensional view into rows of elements.
+///
+/// This describes `height` rows of `width` elements each, where the start of every row lies `pitch`
+/// bytes after the start of the previous one. Any padding between the end of a row and the start of the
+/// next is skipped over. This is a common layout for image tiles or spectrogram frames.
+///
+/// Unerasing gives you a [`Slice2dRef`].
+///
+/// ```
+/// // Three rows of two elements, each padded to four
+/// let data : [u8; 12] = [1, 2, 0, 0, 3, 4, 0, 0, 5, 6, 0, 0];
+/// let slice = sashay::AnySliceRef::erase(data.as_slice());
+/// let any = sashay::AnySlice2dRef::new(slice, 2, 3, 4).unwrap();
+///
+/// assert_eq!(any.row(1).unwrap().unerase::<u8>(), Some([3, 4].as_slice()));
+/// assert_eq!(any.get(1, 2).unwrap().unerase::<u8>(), Some(&6));
+///
+/// let tile = any.sub_rect(1, 1, 1, 2).unwrap();
+/// assert_eq!(tile.unerase::<u8>().unwrap().rows().collect::<Vec<_>>(), [[4], [6]]);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AnySlice2dRef<'a> {
+    /// A raw pointer to the first element of the first row
+    ///
+    /// Note: this pointer must be aligned and point to `width` valid values of `T` at
+    /// subsequent positions along the pitch
+    ptr: *const u8,
+
+    /// The number of elements in each row
+    width: usize,
+
+    /// The number of rows
+    height: usize,
+
+    /// The `size_of()` of the individual elements
+    stride: usize,
+
+    /// The distance in bytes from the start of one row to the next
+    pitch: usize,
+
+    /// A unique id representing the type of the referenced elements
+    ///
+    /// This is used to ensure we can safely unerase back without accidentally transmuting
+    type_id: TypeId,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> AnySlice2dRef<'a> {
+    /// View an erased slice as rows of `width` elements, that start `pitch` bytes apart.
+    ///
+    /// Because the alignment of the erased elements is unknown, `pitch` must be a multiple of their stride.
+    /// If it isn't, or if the rows don't fit within the slice, you get `None`. To use any pitch that keeps
+    /// the elements aligned, erase a [`Slice2dRef`] instead.
+    pub fn new(slice: AnySliceRef<'a>, width: usize, height: usize, pitch: usize) -> Option<Self> {
+        let stride = slice.stride();
+        let fits = fits(slice.len() * stride, width, height, stride, pitch)
+            && pitch.is_multiple_of(stride);
+
+        // SAFETY:
+        // - We've checked that all rows fall within the slice
+        // - Rows start a multiple of the stride apart, and strides are a multiple of the alignment
+        fits.then(|| unsafe {
+            Self::from_raw_parts(
+                slice.as_ptr(),
+                width,
+                height,
+                stride,
+                pitch,
+                *slice.type_id(),
+            )
+        })
+    }
+
+    /// Erase the type of a two-dimensional view's elements.
+    pub fn erase<T: 'static>(view: Slice2dRef<'a, T>) -> AnySlice2dRef<'a> {
+        // SAFETY: The raw parts come from a valid view, and the TypeId and stride are provided by the compiler
+        unsafe {
+            Self::from_raw_parts(
+                view.as_ptr().cast::<()>(),
+                view.width(),
+                view.height(),
+                size_of::<T>(),
+                view.pitch(),
+                TypeId::of::<T>(),
+            )
+        }
+    }
+
+    /// Construct an erased two-dimensional view from its raw parts.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if:
+    ///  - For every row below `height`, `ptr` offset by `row * pitch` bytes points to `width` valid and aligned consecutive values of `T`
+    ///  - These elements aren't mutated for the duration of `'a`
+    ///  - `stride` is the correct [`size_of()`](https://doc.rust-lang.org/std/mem/fn.size_of.html) for the element type `T`
+    ///  - `type_id` is the correct [`TypeId`](https://doc.rust-lang.org/stable/std/any/struct.TypeId.html) for the element type `T`
+    pub const unsafe fn from_raw_parts(
+        ptr: *const (),
+        width: usize,
+        height: usize,
+        stride: usize,
+        pitch: usize,
+        type_id: TypeId,
+    ) -> Self {
+        Self {
+            ptr: ptr.cast::<u8>(),
+            width,
+            height,
+            stride,
+            pitch,
+            type_id,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Unerase back to an immutable two-dimensional view.
+    ///
+    /// If the original element type was `T`, a valid view is returned. Otherwise, you get `None`.
+    pub fn unerase<T: 'static>(&self) -> Option<Slice2dRef<'_, T>> {
+        self.unerase_into()
+    }
+
+    /// Unerase back into an immutable two-dimensional view, transferring ownership into it.
+    pub fn unerase_into<T: 'static>(self) -> Option<Slice2dRef<'a, T>> {
+        self.contains::<T>().then(|| {
+            // SAFETY:
+            // - We've checked the TypeId of T against the one created at construction, so we're not
+            //   accidentally transmuting to a different type
+            // - The rows are valid as per construction
+            unsafe {
+                Slice2dRef::from_raw_parts(
+                    self.ptr.cast::<T>(),
+                    self.width,
+                    self.height,
+                    self.pitch,
+                )
+            }
+        })
+    }
+
+    /// Retrieve one of the rows as an erased slice.
+    pub fn row(&self, y: usize) -> Option<AnySliceRef<'_>> {
+        // SAFETY: The row is within bounds, so it's a valid slice as per construction
+        (y < self.height).then(|| unsafe {
+            AnySliceRef::from_raw_parts(self.row_ptr(y), self.width, self.stride, self.type_id)
+        })
+    }
+
+    /// Retrieve an immutable reference to the element in column `x` and row `y`.
+    pub fn get(&self, x: usize, y: usize) -> Option<AnyRef<'_>> {
+        // SAFETY: The element is within bounds, so it's valid as per construction
+        (x < self.width && y < self.height)
+            .then(|| unsafe { AnyRef::from_raw_parts(self.element(x, y), self.type_id) })
+    }
+
+    /// Iterate over all rows as erased slices, from top to bottom.
+    ///
+    /// ```
+    /// let data : [u8; 6] = [1, 2, 0, 3, 4, 0];
+    /// let any = sashay::AnySlice2dRef::new(sashay::AnySliceRef::erase(data.as_slice()), 2, 2, 3).unwrap();
+    ///
+    /// let sums : Vec<u8> = any.rows().map(|row| row.unerase::<u8>().unwrap().iter().sum()).collect();
+    /// assert_eq!(sums, [3, 7]);
+    /// ```
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = AnySliceRef<'_>> + ExactSizeIterator {
+        // SAFETY: The row is within bounds, so it's a valid slice as per construction
+        (0..self.height).map(|y| unsafe {
+            AnySliceRef::from_raw_parts(self.row_ptr(y), self.width, self.stride, self.type_id)
+        })
+    }
+
+    /// Access a rectangular part of the view.
+    ///
+    /// The rectangle starts at column `x` and row `y`, and is `width` by `height` elements large. If it
+    /// doesn't fall entirely within the view, you get `None`.
+    pub fn sub_rect(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<AnySlice2dRef<'_>> {
+        self.sub_rect_into(x, y, width, height)
+    }
+
+    /// Access a rectangular part of the view, transferring ownership into it.
+    pub fn sub_rect_into(
+        self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<AnySlice2dRef<'a>> {
+        let fits = x.checked_add(width).is_some_and(|end| end <= self.width)
+            && y.checked_add(height).is_some_and(|end| end <= self.height);
+
+        // SAFETY: The rectangle falls within the view, and keeps its pitch
+        fits.then(|| unsafe {
+            Self::from_raw_parts(
+                self.element(x, y),
+                width,
+                height,
+                self.stride,
+                self.pitch,
+                self.type_id,
+            )
+        })
+    }
+
+    /// View all elements as a single erased slice, if there is no padding between the rows.
+    pub fn as_slice(&self) -> Option<AnySliceRef<'_>> {
+        // SAFETY: There is no padding, so the rows form one valid slice
+        self.is_contiguous().then(|| unsafe {
+            AnySliceRef::from_raw_parts(
+                self.as_ptr(),
+                self.width * self.height,
+                self.stride,
+                self.type_id,
+            )
+        })
+    }
+
+    /// Do the rows directly follow each other in memory, without padding?
+    pub const fn is_contiguous(&self) -> bool {
+        self.height <= 1 || self.pitch == self.width * self.stride
+    }
+
+    /// Retrieve an unsafe pointer to the first element of the first row.
+    pub const fn as_ptr(&self) -> *const () {
+        self.ptr.cast::<()>()
+    }
+
+    /// The number of elements in each row.
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows.
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Does the view contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Was the original element of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        TypeId::of::<T>() == self.type_id
+    }
+
+    /// The `size_of()` of the original elements of type `T`.
+    pub const fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// The distance in bytes from the start of one row to the next.
+    pub const fn pitch(&self) -> usize {
+        self.pitch
+    }
+
+    /// A unique type id representing the original element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        &self.type_id
+    }
+
+    /// A pointer to the first element of row `y`, which doesn't need to be in bounds
+    fn row_ptr(&self, y: usize) -> *const () {
+        self.element(0, y)
+    }
+
+    /// A pointer to the element in column `x` and row `y`, which doesn't need to be in bounds
+    fn element(&self, x: usize, y: usize) -> *const () {
+        self.ptr
+            .wrapping_add(y * self.pitch + x * self.stride)
+            .cast::<()>()
+    }
+}
+
+impl<'a, T: 'static> From<Slice2dRef<'a, T>> for AnySlice2dRef<'a> {
+    fn from(view: Slice2dRef<'a, T>) -> Self {
+        Self::erase(view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // All these tests use an (u8, u16) because it has padding
+
+    #[test]
+    fn construct() {
+        let data = [(0u8, 0u16); 8];
+        let slice = AnySliceRef::erase(data.as_slice());
+        let stride = size_of::<(u8, u16)>();
+
+        assert!(AnySlice2dRef::new(slice, 3, 2, 4 * stride).is_some());
+        assert!(AnySlice2dRef::new(slice, 4, 2, 4 * stride).is_some());
+        assert!(AnySlice2dRef::new(slice, 3, 3, 4 * stride).is_none()); // doesn't fit
+        assert!(AnySlice2dRef::new(slice, 3, 2, 2 * stride).is_none()); // rows overlap
+        assert!(AnySlice2dRef::new(slice, 3, 2, 4 * stride + 1).is_none()); // misaligned
+        assert!(AnySlice2dRef::new(slice, 0, 0, 0).is_some());
+
+        // Typed views can use any aligned pitch
+        let view = Slice2dRef::new(data.as_slice(), 1, 3, 6).unwrap();
+        assert_eq!(AnySlice2dRef::erase(view).pitch(), 6);
+        assert!(Slice2dRef::new(data.as_slice(), 1, 3, 5).is_none());
+
+        // The size of a row overflows
+        assert!(Slice2dRef::new(data.as_slice(), usize::MAX / 2 + 1, 1, 0).is_none());
+    }
+
+    #[test]
+    fn access() {
+        let data: [(u8, u16); 6] = core::array::from_fn(|i| (i as u8, i as u16 * 10));
+        let slice = AnySliceRef::erase(data.as_slice());
+        let any = AnySlice2dRef::new(slice, 2, 2, 3 * size_of::<(u8, u16)>()).unwrap();
+
+        assert_eq!(
+            any.row(1).unwrap().unerase::<(u8, u16)>(),
+            Some(&data[3..5])
+        );
+        assert!(any.row(2).is_none());
+        assert_eq!(
+            any.get(1, 1).unwrap().unerase::<(u8, u16)>(),
+            Some(&data[4])
+        );
+        assert!(any.get(2, 0).is_none());
+        assert_eq!(any.rows().len(), 2);
+        assert!(any.as_slice().is_none());
+
+        let sub = any.sub_rect(1, 0, 1, 2).unwrap();
+        let typed = sub.unerase::<(u8, u16)>().unwrap();
+        assert_eq!(typed[(0, 0)], data[1]);
+        assert_eq!(typed[(0, 1)], data[4]);
+        assert!(any.sub_rect(1, 1, 2, 1).is_none());
+
+        let whole = AnySlice2dRef::new(slice, 3, 2, 3 * size_of::<(u8, u16)>()).unwrap();
+        assert_eq!(
+            whole.as_slice().unwrap().unerase::<(u8, u16)>(),
+            Some(data.as_slice())
+        );
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{range::constrain_range, AnyMut, AnyRef, AnySliceRef};
+#[cfg(feature = "alloc")]
+use crate::TransparentRegistry;
+use crate::{
+    gather::Selection, range::constrain_range, AnyGatherMut, AnyGatherRef, AnyMut, AnyRef,
+    AnySliceRef, AnyStridedMut, AnyStridedRef, PrimitiveSlice, PrimitiveSliceMut, TransparentAs,
+};
 use core::{
     any::TypeId,
     marker::PhantomData,
@@ -209,6 +214,56 @@ impl<'a> AnySliceMut<'a> {
         })
     }
 
+    /// Reinterpret the slice as one of a layout-compatible type `U`, without copying.
+    ///
+    /// This is allowed if the original type `T` declares compatibility by implementing [`TransparentAs<U>`].
+    /// If the original type wasn't `T`, you get `None`.
+    ///
+    /// This consumes the erased slice, so [reborrow](AnySliceMut::borrow_mut) it first to keep using the original.
+    ///
+    /// ```
+    /// #[repr(transparent)]
+    /// struct Sample(f32);
+    ///
+    /// // SAFETY: Sample is a transparent wrapper around f32
+    /// unsafe impl sashay::TransparentAs<f32> for Sample {}
+    ///
+    /// let mut samples = [Sample(0.5), Sample(1.0)];
+    /// let mut any = sashay::AnySliceMut::erase(samples.as_mut_slice());
+    ///
+    /// let mut floats = any.borrow_mut().reinterpret::<Sample, f32>().unwrap();
+    /// floats.unerase_mut::<f32>().unwrap().fill(2.0);
+    ///
+    /// assert_eq!(samples[1].0, 2.0);
+    /// ```
+    pub fn reinterpret<T, U>(self) -> Option<AnySliceMut<'a>>
+    where
+        T: TransparentAs<U>,
+        U: 'static,
+    {
+        self.contains::<T>().then(|| Self {
+            type_id: TypeId::of::<U>(),
+            ..self
+        })
+    }
+
+    /// Reinterpret the slice as one of a layout-compatible type `U`, if the registry allows it.
+    ///
+    /// Unlike [`AnySliceMut::reinterpret()`], this doesn't require naming the original type. If the registry
+    /// doesn't declare the original type compatible with `U`, you get `None`.
+    #[cfg(feature = "alloc")]
+    pub fn reinterpret_with<U: 'static>(
+        self,
+        registry: &TransparentRegistry,
+    ) -> Option<AnySliceMut<'a>> {
+        registry
+            .is_compatible(self.type_id, TypeId::of::<U>())
+            .then(|| Self {
+                type_id: TypeId::of::<U>(),
+                ..self
+            })
+    }
+
     /// Borrow this mutable slice as an immutable one.
     ///
     /// Even though you have mutable and unique access to a slice, this fuction lets you
@@ -454,6 +509,314 @@ impl<'a> AnySliceMut<'a> {
         }
     }
 
+    /// View one channel of an interleaved buffer.
+    ///
+    /// See [`AnySliceRef::interleaved_channel()`] for more information.
+    pub fn interleaved_channel(
+        &self,
+        channel: usize,
+        channel_count: usize,
+    ) -> Option<AnyStridedRef<'_>> {
+        self.borrow()
+            .interleaved_channel(channel, channel_count)
+            .map(|view| {
+                // SAFETY: The view is valid for as long as self is borrowed
+                unsafe {
+                    AnyStridedRef::from_raw_parts(
+                        view.as_ptr(),
+                        view.len(),
+                        view.step(),
+                        self.stride,
+                        self.type_id,
+                    )
+                }
+            })
+    }
+
+    /// Mutably view one channel of an interleaved buffer.
+    ///
+    /// Treats the slice as frames of `channel_count` elements each (like `LRLRLR` for stereo audio),
+    /// and returns a strided view over the element at position `channel` in every frame. If `channel`
+    /// is not below `channel_count`, or if the distance between frames overflows, you get `None`.
+    ///
+    /// ```
+    /// let mut data : [i32; 6] = [0, 10, 1, 11, 2, 12];
+    /// let mut any = sashay::AnySliceMut::erase(data.as_mut_slice());
+    ///
+    /// let mut left = any.interleaved_channel_mut(0, 2).unwrap();
+    /// left.unerase_mut::<i32>().unwrap().iter_mut().for_each(|value| *value *= 2);
+    ///
+    /// assert_eq!(data, [0, 10, 2, 11, 4, 12]);
+    /// ```
+    pub fn interleaved_channel_mut(
+        &mut self,
+        channel: usize,
+        channel_count: usize,
+    ) -> Option<AnyStridedMut<'_>> {
+        if channel >= channel_count {
+            return None;
+        }
+
+        // SAFETY: The channel is in bounds, and we hold a mutable reference to self for as long as the view lives
+        unsafe { self.channel_unchecked(channel, channel_count) }
+    }
+
+    /// Mutably view all channels of an interleaved buffer at once.
+    ///
+    /// This is the same as calling [`AnySliceMut::interleaved_channel_mut()`] for every channel,
+    /// except that the resulting views can be used at the same time, because they never overlap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the distance between frames of `N` elements overflows.
+    ///
+    /// ```
+    /// let mut data : [i32; 6] = [0, 10, 1, 11, 2, 12];
+    /// let mut any = sashay::AnySliceMut::erase(data.as_mut_slice());
+    ///
+    /// let [mut left, right] = any.interleaved_channels_mut::<2>();
+    /// let mut left = left.unerase_mut::<i32>().unwrap();
+    ///
+    /// for (l, r) in left.iter_mut().zip(right.unerase::<i32>().unwrap().iter()) {
+    ///     *l += r;
+    /// }
+    ///
+    /// assert_eq!(data, [10, 10, 12, 11, 14, 12]);
+    /// ```
+    pub fn interleaved_channels_mut<const N: usize>(&mut self) -> [AnyStridedMut<'_>; N] {
+        let this = &*self;
+
+        // SAFETY: Every channel is below N and handed out once, and we hold a mutable reference to self
+        // for as long as the views live
+        core::array::from_fn(|channel| unsafe {
+            this.channel_unchecked(channel, N)
+                .expect("the distance between frames overflows")
+        })
+    }
+
+    /// A mutable view of one channel, or `None` if the distance between frames overflows
+    ///
+    /// This takes `&self`, so that disjoint channels can be handed out at the same time.
+    ///
+    /// # Safety
+    ///
+    /// `channel` must be below `channel_count`, and the caller must hold a mutable reference to self
+    /// for as long as the view lives, during which no other view of the same channel may exist.
+    unsafe fn channel_unchecked(
+        &self,
+        channel: usize,
+        channel_count: usize,
+    ) -> Option<AnyStridedMut<'_>> {
+        let step = isize::try_from(self.stride.checked_mul(channel_count)?).ok()?;
+
+        // SAFETY:
+        // - Every frame lies within the slice, and the caller guarantees the channel is within every frame
+        // - The channel is below the channel count, so its offset doesn't overflow either
+        // - Different channels never share elements, and the caller guarantees exclusive access to this one
+        // - `type_id` and `stride` were already valid, and they haven't changed
+        Some(unsafe {
+            AnyStridedMut::from_raw_parts(
+                self.ptr.wrapping_add(self.stride * channel).cast::<()>(),
+                self.len / channel_count,
+                step,
+                self.stride,
+                self.type_id,
+            )
+        })
+    }
+
+    /// View a selection of the elements by their index, without copying.
+    ///
+    /// See [`AnySliceRef::gather()`] for more information.
+    pub fn gather<'b>(&'b self, indices: &'b [usize]) -> Option<AnyGatherRef<'b>> {
+        let selection = Selection::indices(indices, self.len, false)?;
+        Some(AnyGatherRef::new(self.borrow(), selection))
+    }
+
+    /// Mutably view a selection of the elements by their index, without copying.
+    ///
+    /// Indices can be in any order, but may not repeat, so that every element is only referenced once.
+    /// If any index is out of bounds or occurs more than once, you get `None`.
+    ///
+    /// ```
+    /// let mut data : [i32; 4] = [0, 10, 20, 30];
+    /// let mut any = sashay::AnySliceMut::erase(data.as_mut_slice());
+    ///
+    /// let mut view = any.gather_mut(&[3, 1]).unwrap();
+    /// view.unerase_mut::<i32>().unwrap().iter_mut().for_each(|value| *value += 1);
+    ///
+    /// assert_eq!(data, [0, 11, 20, 31]);
+    /// ```
+    pub fn gather_mut<'b>(&'b mut self, indices: &'b [usize]) -> Option<AnyGatherMut<'b>> {
+        let selection = Selection::indices(indices, self.len, true)?;
+        Some(AnyGatherMut::new(self.borrow_mut(), selection))
+    }
+
+    /// View the elements for which the mask is `true`, without copying.
+    ///
+    /// See [`AnySliceRef::select()`] for more information.
+    pub fn select<'b>(&'b self, mask: &'b [bool]) -> Option<AnyGatherRef<'b>> {
+        let selection = Selection::mask(mask, self.len)?;
+        Some(AnyGatherRef::new(self.borrow(), selection))
+    }
+
+    /// Mutably view the elements for which the mask is `true`, without copying.
+    ///
+    /// The mask must have exactly one entry for every element, otherwise you get `None`.
+    pub fn select_mut<'b>(&'b mut self, mask: &'b [bool]) -> Option<AnyGatherMut<'b>> {
+        let selection = Selection::mask(mask, self.len)?;
+        Some(AnyGatherMut::new(self.borrow_mut(), selection))
+    }
+
+    /// View the elements back to front, without copying.
+    ///
+    /// See [`AnySliceRef::rev_view()`] for more information.
+    pub fn rev_view(&self) -> AnyStridedRef<'_> {
+        // SAFETY:
+        // - Walking back from the last element visits every element of the slice exactly once
+        // - `type_id` and `stride` were already valid, and they haven't changed
+        unsafe {
+            AnyStridedRef::from_raw_parts(
+                self.ptr
+                    .wrapping_add(self.stride * self.len.saturating_sub(1))
+                    .cast_const()
+                    .cast::<()>(),
+                self.len,
+                -(self.stride as isize),
+                self.stride,
+                self.type_id,
+            )
+        }
+    }
+
+    /// Mutably view the elements back to front, without copying.
+    ///
+    /// A regular slice can't represent this, so you get a strided view with a negative step instead.
+    /// Unerasing it gives a [`StridedMut`](crate::StridedMut) that iterates from the last element to the first.
+    ///
+    /// ```
+    /// let mut data : [i32; 4] = [0, 1, 2, 3];
+    /// let mut any = sashay::AnySliceMut::erase(data.as_mut_slice());
+    /// let mut reversed = any.rev_view_mut();
+    ///
+    /// let mut typed = reversed.unerase_mut::<i32>().unwrap();
+    /// typed[0] = 30;
+    /// typed.iter_mut().skip(1).for_each(|value| *value *= 10);
+    ///
+    /// assert_eq!(data, [0, 10, 20, 30]);
+    /// ```
+    pub fn rev_view_mut(&mut self) -> AnyStridedMut<'_> {
+        // SAFETY:
+        // - Walking back from the last element visits every element of the slice exactly once
+        // - We hold a mutable reference to self, so the view is unique
+        // - `type_id` and `stride` were already valid, and they haven't changed
+        unsafe {
+            AnyStridedMut::from_raw_parts(
+                self.ptr
+                    .wrapping_add(self.stride * self.len.saturating_sub(1))
+                    .cast::<()>(),
+                self.len,
+                -(self.stride as isize),
+                self.stride,
+                self.type_id,
+            )
+        }
+    }
+
+    /// View a slice of arrays `[T; N]` as a flat slice of `T`.
+    ///
+    /// See [`AnySliceRef::flatten()`] for more information.
+    pub fn flatten<T: 'static, const N: usize>(&self) -> Option<AnySliceRef<'_>> {
+        self.borrow().flatten_into::<T, N>()
+    }
+
+    /// Mutably view a slice of arrays `[T; N]` as a flat slice of `T`, with `N` times as many elements.
+    ///
+    /// If the original elements aren't of type `[T; N]`, you get `None`.
+    ///
+    /// ```
+    /// let mut vertices : [[f32; 2]; 2] = [[0.0, 1.0], [2.0, 3.0]];
+    /// let mut any = sashay::AnySliceMut::erase(vertices.as_mut_slice());
+    ///
+    /// let mut flat = any.flatten_mut::<f32, 2>().unwrap();
+    /// flat.unerase_mut::<f32>().unwrap()[1] = 5.0;
+    ///
+    /// assert_eq!(vertices, [[0.0, 5.0], [2.0, 3.0]]);
+    /// ```
+    pub fn flatten_mut<T: 'static, const N: usize>(&mut self) -> Option<AnySliceMut<'_>> {
+        self.borrow_mut().flatten_into::<T, N>()
+    }
+
+    /// Mutably view a slice of arrays `[T; N]` as a flat slice of `T`, transferring ownership into it.
+    pub fn flatten_into<T: 'static, const N: usize>(mut self) -> Option<AnySliceMut<'a>> {
+        let len = self.len.checked_mul(N)?;
+
+        // SAFETY:
+        // - We've checked the TypeId of [T; N], and arrays are laid out as N adjacent elements of T
+        // - The TypeId and stride of T are provided by the compiler
+        self.contains::<[T; N]>().then(|| unsafe {
+            Self::from_raw_parts(self.as_mut_ptr(), len, size_of::<T>(), TypeId::of::<T>())
+        })
+    }
+
+    /// View a flat slice of `T` as a slice of arrays `[T; N]`.
+    ///
+    /// See [`AnySliceRef::unflatten()`] for more information.
+    pub fn unflatten<T: 'static, const N: usize>(&self) -> Option<AnySliceRef<'_>> {
+        self.borrow().unflatten_into::<T, N>()
+    }
+
+    /// Mutably view a flat slice of `T` as a slice of arrays `[T; N]`, with `N` times fewer elements.
+    ///
+    /// If the original elements aren't of type `T`, or if the length isn't a multiple of `N`, you get `None`.
+    pub fn unflatten_mut<T: 'static, const N: usize>(&mut self) -> Option<AnySliceMut<'_>> {
+        self.borrow_mut().unflatten_into::<T, N>()
+    }
+
+    /// Mutably view a flat slice of `T` as a slice of arrays `[T; N]`, transferring ownership into it.
+    pub fn unflatten_into<T: 'static, const N: usize>(mut self) -> Option<AnySliceMut<'a>> {
+        const { assert!(N > 0, "cannot unflatten into arrays of zero elements") };
+
+        // SAFETY:
+        // - We've checked the TypeId of T, and every N adjacent elements of T form a valid [T; N]
+        // - The TypeId and stride of [T; N] are provided by the compiler
+        (self.contains::<T>() && self.len.is_multiple_of(N)).then(|| unsafe {
+            Self::from_raw_parts(
+                self.as_mut_ptr(),
+                self.len / N,
+                size_of::<[T; N]>(),
+                TypeId::of::<[T; N]>(),
+            )
+        })
+    }
+
+    /// Unerase to an _immutable_ slice of whichever primitive type it contains.
+    ///
+    /// If the original slice's elements are Rust primitives (any integer or float, `bool` or `char`), a
+    /// [`PrimitiveSlice`] holding the typed slice is returned. Otherwise, you get `None`.
+    pub fn primitive(&self) -> Option<PrimitiveSlice<'_>> {
+        PrimitiveSlice::from_any(self.borrow())
+    }
+
+    /// Unerase to a mutable slice of whichever primitive type it contains.
+    ///
+    /// If the original slice's elements are Rust primitives (any integer or float, `bool` or `char`), a
+    /// [`PrimitiveSliceMut`] holding the typed slice is returned. Otherwise, you get `None`.
+    ///
+    /// ```
+    /// let mut data : [i8; 3] = [0, 1, 2];
+    /// let mut any = sashay::AnySliceMut::erase(data.as_mut_slice());
+    ///
+    /// if let Some(sashay::PrimitiveSliceMut::I8(slice)) = any.primitive_mut() {
+    ///     slice.fill(-1);
+    /// }
+    ///
+    /// assert_eq!(data, [-1, -1, -1]);
+    /// ```
+    pub fn primitive_mut(&mut self) -> Option<PrimitiveSliceMut<'_>> {
+        PrimitiveSliceMut::from_any(self.borrow_mut())
+    }
+
     /// Retrieve an unsafe immutable pointer to the raw slice data.
     pub const fn as_ptr(&self) -> *const () {
         self.ptr.cast::<()>().cast_const()
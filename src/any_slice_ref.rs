@@ -1,4 +1,9 @@
-use crate::{range::constrain_range, AnyRef};
+#[cfg(feature = "alloc")]
+use crate::TransparentRegistry;
+use crate::{
+    gather::Selection, range::constrain_range, AnyGatherRef, AnyRef, AnyStridedRef, PrimitiveSlice,
+    TransparentAs,
+};
 use core::{
     any::TypeId, marker::PhantomData, mem::size_of, ops::RangeBounds, slice::from_raw_parts,
 };
@@ -175,6 +180,52 @@ impl<'a> AnySliceRef<'a> {
         })
     }
 
+    /// Reinterpret the slice as one of a layout-compatible type `U`, without copying.
+    ///
+    /// This is allowed if the original type `T` declares compatibility by implementing [`TransparentAs<U>`].
+    /// If the original type wasn't `T`, you get `None`.
+    ///
+    /// ```
+    /// #[repr(transparent)]
+    /// struct Sample(f32);
+    ///
+    /// // SAFETY: Sample is a transparent wrapper around f32
+    /// unsafe impl sashay::TransparentAs<f32> for Sample {}
+    ///
+    /// let samples = [Sample(0.5), Sample(1.0)];
+    /// let any = sashay::AnySliceRef::erase(samples.as_slice());
+    ///
+    /// let floats = any.reinterpret::<Sample, f32>().unwrap();
+    /// assert_eq!(floats.unerase::<f32>(), Some([0.5, 1.0].as_slice()));
+    /// ```
+    pub fn reinterpret<T, U>(self) -> Option<AnySliceRef<'a>>
+    where
+        T: TransparentAs<U>,
+        U: 'static,
+    {
+        self.contains::<T>().then(|| Self {
+            type_id: TypeId::of::<U>(),
+            ..self
+        })
+    }
+
+    /// Reinterpret the slice as one of a layout-compatible type `U`, if the registry allows it.
+    ///
+    /// Unlike [`AnySliceRef::reinterpret()`], this doesn't require naming the original type. If the registry
+    /// doesn't declare the original type compatible with `U`, you get `None`.
+    #[cfg(feature = "alloc")]
+    pub fn reinterpret_with<U: 'static>(
+        self,
+        registry: &TransparentRegistry,
+    ) -> Option<AnySliceRef<'a>> {
+        registry
+            .is_compatible(self.type_id, TypeId::of::<U>())
+            .then(|| Self {
+                type_id: TypeId::of::<U>(),
+                ..self
+            })
+    }
+
     /// Retrieve an immutable reference to one of the elements in the slice.
     ///
     /// ```
@@ -285,6 +336,194 @@ impl<'a> AnySliceRef<'a> {
         }
     }
 
+    /// View one channel of an interleaved buffer.
+    ///
+    /// Treats the slice as frames of `channel_count` elements each (like `LRLRLR` for stereo audio),
+    /// and returns a strided view over the element at position `channel` in every frame. Elements in an
+    /// incomplete trailing frame are not part of any channel. If `channel` is not below `channel_count`,
+    /// or if the distance between frames overflows, you get `None`.
+    ///
+    /// ```
+    /// let data : [i32; 6] = [0, 10, 1, 11, 2, 12];
+    /// let any = sashay::AnySliceRef::erase(data.as_slice());
+    ///
+    /// let right = any.interleaved_channel(1, 2).unwrap();
+    /// assert_eq!(right.unerase::<i32>().unwrap(), [10, 11, 12]);
+    /// ```
+    pub fn interleaved_channel(
+        &self,
+        channel: usize,
+        channel_count: usize,
+    ) -> Option<AnyStridedRef<'_>> {
+        if channel >= channel_count {
+            return None;
+        }
+
+        let step = isize::try_from(self.stride.checked_mul(channel_count)?).ok()?;
+
+        // SAFETY:
+        // - Every frame lies within the slice, and the channel is within every frame
+        // - The channel is below the channel count, so its offset doesn't overflow either
+        // - `type_id` and `stride` were already valid, and they haven't changed
+        let view = unsafe {
+            AnyStridedRef::from_raw_parts(
+                self.ptr.wrapping_add(self.stride * channel).cast::<()>(),
+                self.len / channel_count,
+                step,
+                self.stride,
+                self.type_id,
+            )
+        };
+
+        Some(view)
+    }
+
+    /// View a selection of the elements by their index, without copying.
+    ///
+    /// Indices may repeat and can be in any order. If any of them is out of bounds, you get `None`.
+    ///
+    /// ```
+    /// let data : [i32; 4] = [0, 10, 20, 30];
+    /// let any = sashay::AnySliceRef::erase(data.as_slice());
+    ///
+    /// let view = any.gather(&[3, 1, 3]).unwrap();
+    /// assert!(view.unerase::<i32>().unwrap().iter().eq(&[30, 10, 30]));
+    ///
+    /// assert!(any.gather(&[4]).is_none());
+    /// ```
+    pub fn gather<'b>(&'b self, indices: &'b [usize]) -> Option<AnyGatherRef<'b>> {
+        let selection = Selection::indices(indices, self.len, false)?;
+        Some(AnyGatherRef::new(*self, selection))
+    }
+
+    /// View the elements for which the mask is `true`, without copying.
+    ///
+    /// The mask must have exactly one entry for every element, otherwise you get `None`.
+    ///
+    /// ```
+    /// let data : [i32; 4] = [0, 10, 20, 30];
+    /// let any = sashay::AnySliceRef::erase(data.as_slice());
+    ///
+    /// let view = any.select(&[true, false, false, true]).unwrap();
+    /// assert!(view.unerase::<i32>().unwrap().iter().eq(&[0, 30]));
+    /// ```
+    pub fn select<'b>(&'b self, mask: &'b [bool]) -> Option<AnyGatherRef<'b>> {
+        let selection = Selection::mask(mask, self.len)?;
+        Some(AnyGatherRef::new(*self, selection))
+    }
+
+    /// View the elements back to front, without copying.
+    ///
+    /// A regular slice can't represent this, so you get a strided view with a negative step instead.
+    /// Unerasing it gives a [`StridedRef`](crate::StridedRef) that iterates from the last element to the first.
+    ///
+    /// ```
+    /// let data : [i32; 4] = [0, 1, 2, 3];
+    /// let any = sashay::AnySliceRef::erase(data.as_slice());
+    /// let reversed = any.rev_view();
+    ///
+    /// assert_eq!(reversed.step(), -4);
+    /// assert_eq!(reversed.get(0).unwrap().unerase_into::<i32>(), Some(&3));
+    /// assert_eq!(reversed.subslice(1..3).unerase::<i32>().unwrap(), [2, 1]);
+    /// ```
+    pub fn rev_view(&self) -> AnyStridedRef<'_> {
+        // SAFETY:
+        // - Walking back from the last element visits every element of the slice exactly once
+        // - `type_id` and `stride` were already valid, and they haven't changed
+        unsafe {
+            AnyStridedRef::from_raw_parts(
+                self.ptr
+                    .wrapping_add(self.stride * self.len.saturating_sub(1))
+                    .cast::<()>(),
+                self.len,
+                -(self.stride as isize),
+                self.stride,
+                self.type_id,
+            )
+        }
+    }
+
+    /// View a slice of arrays `[T; N]` as a flat slice of `T`, with `N` times as many elements.
+    ///
+    /// If the original elements aren't of type `[T; N]`, you get `None`.
+    ///
+    /// ```
+    /// let vertices : [[f32; 2]; 2] = [[0.0, 1.0], [2.0, 3.0]];
+    /// let any = sashay::AnySliceRef::erase(vertices.as_slice());
+    ///
+    /// let flat = any.flatten::<f32, 2>().unwrap();
+    /// assert_eq!(flat.unerase::<f32>(), Some([0.0, 1.0, 2.0, 3.0].as_slice()));
+    /// ```
+    pub fn flatten<T: 'static, const N: usize>(&self) -> Option<AnySliceRef<'_>> {
+        self.flatten_into::<T, N>()
+    }
+
+    /// View a slice of arrays `[T; N]` as a flat slice of `T`, transferring ownership into it.
+    pub fn flatten_into<T: 'static, const N: usize>(self) -> Option<AnySliceRef<'a>> {
+        let len = self.len.checked_mul(N)?;
+
+        // SAFETY:
+        // - We've checked the TypeId of [T; N], and arrays are laid out as N adjacent elements of T
+        // - The TypeId and stride of T are provided by the compiler
+        self.contains::<[T; N]>().then(|| unsafe {
+            Self::from_raw_parts(self.as_ptr(), len, size_of::<T>(), TypeId::of::<T>())
+        })
+    }
+
+    /// View a flat slice of `T` as a slice of arrays `[T; N]`, with `N` times fewer elements.
+    ///
+    /// The element type must be named, because the type of the arrays can't be derived from an erased
+    /// element type. If the original elements aren't of type `T`, or if the length isn't a multiple of `N`,
+    /// you get `None`.
+    ///
+    /// ```
+    /// let samples : [f32; 4] = [0.0, 1.0, 2.0, 3.0];
+    /// let any = sashay::AnySliceRef::erase(samples.as_slice());
+    ///
+    /// let pairs = any.unflatten::<f32, 2>().unwrap();
+    /// assert_eq!(pairs.unerase::<[f32; 2]>(), Some([[0.0, 1.0], [2.0, 3.0]].as_slice()));
+    ///
+    /// assert!(any.unflatten::<f32, 3>().is_none());
+    /// ```
+    pub fn unflatten<T: 'static, const N: usize>(&self) -> Option<AnySliceRef<'_>> {
+        self.unflatten_into::<T, N>()
+    }
+
+    /// View a flat slice of `T` as a slice of arrays `[T; N]`, transferring ownership into it.
+    pub fn unflatten_into<T: 'static, const N: usize>(self) -> Option<AnySliceRef<'a>> {
+        const { assert!(N > 0, "cannot unflatten into arrays of zero elements") };
+
+        // SAFETY:
+        // - We've checked the TypeId of T, and every N adjacent elements of T form a valid [T; N]
+        // - The TypeId and stride of [T; N] are provided by the compiler
+        (self.contains::<T>() && self.len.is_multiple_of(N)).then(|| unsafe {
+            Self::from_raw_parts(
+                self.as_ptr(),
+                self.len / N,
+                size_of::<[T; N]>(),
+                TypeId::of::<[T; N]>(),
+            )
+        })
+    }
+
+    /// Unerase to a slice of whichever primitive type it contains.
+    ///
+    /// If the original slice's elements are Rust primitives (any integer or float, `bool` or `char`), a
+    /// [`PrimitiveSlice`] holding the typed slice is returned. Otherwise, you get `None`.
+    ///
+    /// ```
+    /// let data : [u16; 3] = [0, 1, 2];
+    /// let any = sashay::AnySliceRef::erase(data.as_slice());
+    ///
+    /// match any.primitive() {
+    ///     Some(sashay::PrimitiveSlice::U16(slice)) => assert_eq!(slice, [0, 1, 2]),
+    ///     _ => panic!("not a u16 slice"),
+    /// }
+    /// ```
+    pub fn primitive(&self) -> Option<PrimitiveSlice<'a>> {
+        PrimitiveSlice::from_any(*self)
+    }
+
     /// Retrieve an unsafe pointer to the raw slice data.
     pub const fn as_ptr(&self) -> *const () {
         self.ptr.cast::<()>()
@@ -369,4 +608,29 @@ mod tests {
             Some(&data[0..2])
         );
     }
+
+    #[test]
+    fn flatten() {
+        let data = [[(0u8, 1u16), (2u8, 3u16)], [(4u8, 5u16), (6u8, 7u16)]];
+        let any = AnySliceRef::erase(data.as_slice());
+
+        assert!(any.flatten::<(u8, u16), 1>().is_none());
+        let flat = any.flatten::<(u8, u16), 2>().unwrap();
+        assert_eq!(flat.len(), 4);
+        assert_eq!(flat.stride(), size_of::<(u8, u16)>());
+        assert_eq!(flat.type_id(), &TypeId::of::<(u8, u16)>());
+        assert_eq!(
+            flat.get(3).unwrap().unerase::<(u8, u16)>(),
+            Some(&(6u8, 7u16))
+        );
+
+        let triples = flat.unflatten::<(u8, u16), 4>().unwrap();
+        assert_eq!(triples.len(), 1);
+        assert_eq!(triples.stride(), size_of::<[(u8, u16); 4]>());
+        assert!(flat.unflatten::<(u8, u16), 3>().is_none());
+        assert!(flat.unflatten::<u8, 2>().is_none());
+
+        let pairs = flat.unflatten_into::<(u8, u16), 2>().unwrap();
+        assert_eq!(pairs.unerase::<[(u8, u16); 2]>(), Some(data.as_slice()));
+    }
 }
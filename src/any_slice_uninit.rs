@@ -0,0 +1,447 @@
+use crate::{AnyRef, AnySliceMut, AnySliceRef, TypeInfo};
+use core::{
+    any::TypeId, fmt, marker::PhantomData, mem::MaybeUninit, ptr, slice::from_raw_parts_mut,
+};
+
+/// A type-erased slice of possibly uninitialized elements.
+///
+/// This erases a `&mut [MaybeUninit<T>]`, such as a pre-allocated output buffer, so that it can be
+/// written to without knowing its element type statically. The slice keeps track of how many elements
+/// at the front have been initialized, and writes must extend this initialized prefix in order (or
+/// overwrite elements within it). Once every element is written, [`AnySliceUninit::finish()`] turns it
+/// into a regular [`AnySliceMut`].
+///
+/// Like `MaybeUninit`, initialized elements are not dropped when the slice is dropped without finishing it.
+///
+/// ```
+/// use std::mem::MaybeUninit;
+///
+/// let mut buffer = [MaybeUninit::<i32>::uninit(); 3];
+/// let mut any = sashay::AnySliceUninit::erase_copy(buffer.as_mut_slice());
+///
+/// any.write(0, 7i32).unwrap();
+/// any.write_from(1, sashay::AnyRef::erase(&8i32)).unwrap();
+/// assert_eq!(any.initialized_len(), 2);
+///
+/// // Not every element is written yet
+/// let mut any = any.finish().unwrap_err();
+///
+/// any.write(2, 9i32).unwrap();
+/// let slice = any.finish().unwrap();
+/// assert_eq!(slice.unerase::<i32>(), Some([7, 8, 9].as_slice()));
+/// ```
+#[derive(Debug)]
+pub struct AnySliceUninit<'a> {
+    /// A raw pointer to the referenced slice
+    ///
+    /// Note: this pointer must be aligned and point to possibly uninitialized values of `T` at
+    /// subsequent positions along the stride
+    ptr: *mut u8,
+
+    /// The number of elements in referenced slice
+    len: usize,
+
+    /// The number of elements at the front of the slice that are initialized
+    initialized: usize,
+
+    /// The type of the referenced slice elements (`T`, not `MaybeUninit<T>`)
+    ///
+    /// This is used to ensure we can safely unerase back without accidentally transmuting, and
+    /// to know whether elements can be written by copying their bytes
+    info: TypeInfo,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a mut ()>,
+}
+
+impl<'a> AnySliceUninit<'a> {
+    /// Erase the type of a slice of uninitialized elements.
+    ///
+    /// Nothing is assumed to be initialized yet. Use [`AnySliceUninit::erase_copy()`] for `Copy` types,
+    /// to also allow writing elements from erased references.
+    pub fn erase<T: 'static>(slice: &'a mut [MaybeUninit<T>]) -> AnySliceUninit<'a> {
+        // SAFETY:
+        //  - The raw parts come from a valid slice
+        //  - The type information is provided by the compiler
+        unsafe {
+            Self::from_raw_parts(
+                slice.as_mut_ptr().cast::<()>(),
+                slice.len(),
+                TypeInfo::of::<T>(),
+            )
+        }
+    }
+
+    /// Erase the type of a slice of uninitialized `Copy` elements.
+    ///
+    /// This allows elements to be written from erased references, with [`AnySliceUninit::write_from()`].
+    pub fn erase_copy<T: Copy + 'static>(slice: &'a mut [MaybeUninit<T>]) -> AnySliceUninit<'a> {
+        // SAFETY:
+        //  - The raw parts come from a valid slice
+        //  - The type information is provided by the compiler, and T is Copy
+        unsafe {
+            Self::from_raw_parts(
+                slice.as_mut_ptr().cast::<()>(),
+                slice.len(),
+                TypeInfo::of_copy::<T>(),
+            )
+        }
+    }
+
+    /// Construct an erased uninitialized slice from its raw parts.
+    ///
+    /// Nothing is assumed to be initialized yet.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if:
+    ///  - All safety rules for [`from_raw_parts_mut()`](https://doc.rust-lang.org/std/slice/fn.from_raw_parts_mut.html) hold for a `[MaybeUninit<T>]`
+    ///  - `info` describes the element type `T`
+    pub unsafe fn from_raw_parts(ptr: *mut (), len: usize, info: TypeInfo) -> Self {
+        Self {
+            ptr: ptr.cast::<u8>(),
+            len,
+            initialized: 0,
+            info,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Unerase back into the original slice of uninitialized elements, transferring ownership into it.
+    ///
+    /// Which elements were initialized is forgotten. If the original element type was `T`, a valid
+    /// slice is returned. Otherwise, you get `None`.
+    pub fn unerase_into<T: 'static>(self) -> Option<&'a mut [MaybeUninit<T>]> {
+        self.contains::<T>().then(|| {
+            // SAFETY:
+            // - We've checked the TypeId of T against the one created at construction, so we're not
+            //   accidentally transmuting to a different type
+            // - The pointer came directly out of a valid slice, so it's not null and aligned
+            unsafe { from_raw_parts_mut(self.ptr.cast::<MaybeUninit<T>>(), self.len) }
+        })
+    }
+
+    /// Write a value to one of the elements.
+    ///
+    /// The index must be within the initialized prefix, in which case the old value is dropped and
+    /// replaced, or directly after it, in which case the prefix grows by one. If the value is of the
+    /// wrong type or the index is invalid, nothing is written and you get an error.
+    pub fn write<T: 'static>(&mut self, index: usize, value: T) -> Result<(), UninitError> {
+        self.check_write(index, TypeId::of::<T>())?;
+
+        let element = self.element(index).cast::<T>();
+        if index < self.initialized {
+            // SAFETY: The element is initialized, and of type T
+            unsafe { *element = value };
+        } else {
+            // SAFETY: The element is uninitialized, and of type T
+            unsafe { element.write(value) };
+            self.initialized += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Write a copy of an erased value to one of the elements.
+    ///
+    /// This only works for slices created with [`AnySliceUninit::erase_copy()`], because the element
+    /// type must be `Copy`. Otherwise, this behaves the same as [`AnySliceUninit::write()`].
+    pub fn write_from(&mut self, index: usize, value: AnyRef<'_>) -> Result<(), UninitError> {
+        if !self.info.is_copy() {
+            return Err(UninitError::NotCopy);
+        }
+
+        self.check_write(index, *value.type_id())?;
+
+        // SAFETY:
+        // - Both the value and the element are of the same type, which is Copy
+        // - We hold a mutable reference to the slice, so the value can't overlap with it
+        unsafe {
+            ptr::copy_nonoverlapping(
+                value.as_ptr().cast::<u8>(),
+                self.element(index),
+                self.info.stride(),
+            );
+        }
+
+        if index == self.initialized {
+            self.initialized += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Write clones of a value to all elements.
+    ///
+    /// Elements that were already initialized are dropped and replaced. If the value is of the wrong
+    /// type, nothing is written and you get an error.
+    pub fn fill<T: Clone + 'static>(&mut self, value: T) -> Result<(), UninitError> {
+        if !self.contains::<T>() {
+            return Err(UninitError::TypeMismatch {
+                expected: *self.info.type_id(),
+                found: TypeId::of::<T>(),
+            });
+        }
+
+        for index in 0..self.len {
+            self.write(index, value.clone())?;
+        }
+
+        Ok(())
+    }
+
+    /// Turn this into an initialized slice, if every element has been written.
+    ///
+    /// If some elements are still uninitialized, you get the uninitialized slice back as an error, so
+    /// that you can continue writing to it.
+    pub fn finish(self) -> Result<AnySliceMut<'a>, AnySliceUninit<'a>> {
+        if self.is_initialized() {
+            // SAFETY: All elements have been initialized
+            Ok(unsafe { self.assume_init() })
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Turn this into an initialized slice, without checking whether every element has been written.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if all elements are initialized, for example because they
+    /// were written through a pointer retrieved with [`AnySliceUninit::as_mut_ptr()`].
+    pub unsafe fn assume_init(self) -> AnySliceMut<'a> {
+        // SAFETY: The caller guarantees the elements are initialized, and the other parts are still valid
+        unsafe {
+            AnySliceMut::from_raw_parts(
+                self.ptr.cast::<()>(),
+                self.len,
+                self.info.stride(),
+                *self.info.type_id(),
+            )
+        }
+    }
+
+    /// Access the initialized prefix of the slice.
+    pub fn initialized(&self) -> AnySliceRef<'_> {
+        // SAFETY: The prefix has been initialized, and the other parts are still valid
+        unsafe {
+            AnySliceRef::from_raw_parts(
+                self.ptr.cast_const().cast::<()>(),
+                self.initialized,
+                self.info.stride(),
+                *self.info.type_id(),
+            )
+        }
+    }
+
+    /// Mutably access the initialized prefix of the slice.
+    pub fn initialized_mut(&mut self) -> AnySliceMut<'_> {
+        // SAFETY: The prefix has been initialized, and the other parts are still valid
+        unsafe {
+            AnySliceMut::from_raw_parts(
+                self.ptr.cast::<()>(),
+                self.initialized,
+                self.info.stride(),
+                *self.info.type_id(),
+            )
+        }
+    }
+
+    /// How many elements at the front of the slice have been initialized?
+    pub const fn initialized_len(&self) -> usize {
+        self.initialized
+    }
+
+    /// Have all elements been initialized?
+    pub const fn is_initialized(&self) -> bool {
+        self.initialized == self.len
+    }
+
+    /// Retrieve an unsafe pointer to the first element.
+    pub const fn as_ptr(&self) -> *const () {
+        self.ptr.cast_const().cast::<()>()
+    }
+
+    /// Retrieve an unsafe mutable pointer to the first element.
+    pub fn as_mut_ptr(&mut self) -> *mut () {
+        self.ptr.cast::<()>()
+    }
+
+    /// How many elements does the slice contain, initialized or not?
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Does the slice contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Were the original elements of type `MaybeUninit<T>`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.info.is::<T>()
+    }
+
+    /// Can elements be written from erased references?
+    pub const fn is_copy(&self) -> bool {
+        self.info.is_copy()
+    }
+
+    /// The `size_of()` of the original elements of type `T`.
+    pub const fn stride(&self) -> usize {
+        self.info.stride()
+    }
+
+    /// A unique type id representing the original element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        self.info.type_id()
+    }
+
+    /// The runtime information about the original element `T`.
+    pub const fn type_info(&self) -> &TypeInfo {
+        &self.info
+    }
+
+    /// Check that a value of type `type_id` may be written at `index`
+    fn check_write(&self, index: usize, type_id: TypeId) -> Result<(), UninitError> {
+        if type_id != *self.info.type_id() {
+            Err(UninitError::TypeMismatch {
+                expected: *self.info.type_id(),
+                found: type_id,
+            })
+        } else if index >= self.len {
+            Err(UninitError::OutOfBounds {
+                index,
+                len: self.len,
+            })
+        } else if index > self.initialized {
+            Err(UninitError::Gap {
+                index,
+                initialized: self.initialized,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// A pointer to the element at `index`, which doesn't need to be in bounds
+    fn element(&self, index: usize) -> *mut u8 {
+        self.ptr.wrapping_add(index * self.info.stride())
+    }
+}
+
+impl<'a, T: 'static> From<&'a mut [MaybeUninit<T>]> for AnySliceUninit<'a> {
+    fn from(slice: &'a mut [MaybeUninit<T>]) -> Self {
+        Self::erase(slice)
+    }
+}
+
+/// The reasons writing to an uninitialized slice can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UninitError {
+    /// The value has a different type than the elements
+    TypeMismatch { expected: TypeId, found: TypeId },
+
+    /// The index is beyond the end of the slice
+    OutOfBounds { index: usize, len: usize },
+
+    /// Writing at the index would leave uninitialized elements before it
+    Gap { index: usize, initialized: usize },
+
+    /// The element type is not known to be `Copy`, so it can't be copied from an erased reference
+    NotCopy,
+}
+
+impl fmt::Display for UninitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected a value of {expected:?}, but got {found:?}")
+            }
+            Self::OutOfBounds { index, len } => {
+                write!(f, "index {index} is out of bounds for a slice of {len}")
+            }
+            Self::Gap { index, initialized } => write!(
+                f,
+                "cannot write index {index}, only the first {initialized} elements are initialized"
+            ),
+            Self::NotCopy => write!(f, "the element type is not known to be `Copy`"),
+        }
+    }
+}
+
+impl core::error::Error for UninitError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{DropCounter, Tracked};
+
+    // These tests use an (u8, u16) because it has padding, and `Tracked` values to count drops
+
+    #[test]
+    fn write() {
+        let mut buffer = [MaybeUninit::<(u8, u16)>::uninit(); 3];
+        let mut any = AnySliceUninit::erase_copy(buffer.as_mut_slice());
+
+        assert_eq!(
+            any.write(2, (0u8, 0u16)),
+            Err(UninitError::Gap {
+                index: 2,
+                initialized: 0
+            })
+        );
+        assert!(matches!(
+            any.write(0, 0u8),
+            Err(UninitError::TypeMismatch { .. })
+        ));
+        assert_eq!(any.write(0, (1u8, 2u16)), Ok(()));
+        assert_eq!(any.write_from(1, AnyRef::erase(&(3u8, 4u16))), Ok(()));
+        assert_eq!(any.write(0, (5u8, 6u16)), Ok(()));
+        assert_eq!(
+            any.write(3, (0u8, 0u16)),
+            Err(UninitError::OutOfBounds { index: 3, len: 3 })
+        );
+
+        assert_eq!(any.initialized_len(), 2);
+        assert_eq!(
+            any.initialized().unerase::<(u8, u16)>(),
+            Some([(5u8, 6u16), (3u8, 4u16)].as_slice())
+        );
+
+        let mut any = any.finish().unwrap_err();
+        any.write_from(2, AnyRef::erase(&(7u8, 8u16))).unwrap();
+        let slice = any.finish().unwrap();
+        assert_eq!(
+            slice.unerase::<(u8, u16)>(),
+            Some([(5u8, 6u16), (3u8, 4u16), (7u8, 8u16)].as_slice())
+        );
+    }
+
+    #[test]
+    fn fill_drop() {
+        static DROPS: DropCounter = DropCounter::new();
+
+        let mut buffer: [MaybeUninit<Tracked<u8>>; 3] = [const { MaybeUninit::uninit() }; 3];
+        let mut any = AnySliceUninit::erase(buffer.as_mut_slice());
+
+        assert_eq!(
+            any.write_from(0, AnyRef::erase(&0u8)),
+            Err(UninitError::NotCopy)
+        );
+        any.write(0, DROPS.track(1u8)).unwrap();
+        assert_eq!(DROPS.drops(), 0);
+
+        // Filling replaces (and drops) the initialized element, and the original value
+        any.fill(DROPS.track(2u8)).unwrap();
+        assert_eq!(DROPS.drops(), 2);
+
+        let slice = any.finish().unwrap();
+        assert!(slice
+            .unerase::<Tracked<u8>>()
+            .unwrap()
+            .iter()
+            .all(|tracked| tracked.value == 2));
+        assert_eq!(DROPS.drops(), 2);
+    }
+}
@@ -0,0 +1,416 @@
+use crate::{AnyMut, AnyRef, AnySliceMut, AnySliceRef, AnyVec, TypeInfo};
+use alloc::vec::Vec;
+use core::{any::TypeId, fmt};
+
+/// A type-erased slot map, storing values of a single type behind generational keys.
+///
+/// Inserting a value returns a [`Key`], which stays valid until the value is removed. Keys are plain
+/// data without a lifetime, so they can be kept across frames. Every slot tracks a generation that is
+/// bumped when its value is removed, so stale keys are reliably rejected even if the slot is reused.
+///
+/// Values are packed contiguously, so they can be iterated over as a slice.
+///
+/// ```
+/// let mut map = sashay::AnySlotMap::new::<String>();
+///
+/// let foo = map.insert_as(String::from("foo")).unwrap();
+/// let bar = map.insert_as(String::from("bar")).unwrap();
+/// assert_eq!(map.get_as::<String>(foo).unwrap(), "foo");
+///
+/// assert_eq!(map.remove::<String>(foo).as_deref(), Some("foo"));
+/// assert!(map.get(foo).is_none());
+///
+/// // The slot is reused, but the old key stays stale
+/// let baz = map.insert_as(String::from("baz")).unwrap();
+/// assert!(map.get(foo).is_none());
+/// assert_eq!(map.values().unerase::<String>().unwrap(), ["bar", "baz"]);
+/// ```
+#[derive(Debug)]
+pub struct AnySlotMap {
+    /// The packed values
+    values: AnyVec,
+
+    /// The slot of every packed value, in the same order
+    owners: Vec<u32>,
+
+    /// The slots keys refer to
+    slots: Vec<Slot>,
+
+    /// The indices of vacant slots that can be reused
+    free: Vec<u32>,
+}
+
+impl AnySlotMap {
+    /// Construct an empty map of values of type `T`.
+    pub fn new<T: 'static>() -> Self {
+        Self::with_type_info(TypeInfo::of::<T>())
+    }
+
+    /// Construct an empty map of values of a described type.
+    pub fn with_type_info(info: TypeInfo) -> Self {
+        Self {
+            values: AnyVec::with_type_info(info),
+            owners: Vec::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Insert a copy of an erased value, returning its key.
+    ///
+    /// Copying is only allowed if the value type is known to be `Copy` (see [`TypeInfo::is_copy()`]).
+    pub fn insert(&mut self, value: AnyRef<'_>) -> Result<Key, SlotMapError> {
+        self.check_type(*value.type_id())?;
+
+        if !self.values.type_info().is_copy() {
+            return Err(SlotMapError::NotCopy);
+        }
+
+        // SAFETY: The value has the right type, and is Copy
+        unsafe { self.values.push_raw(value.as_ptr().cast::<u8>()) };
+        Ok(self.link())
+    }
+
+    /// Move an erased value into the map, returning its key.
+    ///
+    /// Unlike [`AnySlotMap::insert()`], this works for value types that aren't `Copy`.
+    ///
+    /// # Safety
+    ///
+    /// If this succeeds, the value has been moved into the map, so the caller must make sure the
+    /// original is never used or dropped again (for example by wrapping it in `ManuallyDrop`).
+    pub unsafe fn insert_moved(&mut self, value: AnyRef<'_>) -> Result<Key, SlotMapError> {
+        self.check_type(*value.type_id())?;
+
+        // SAFETY: The value has the right type, and the caller guarantees it may be moved
+        unsafe { self.values.push_raw(value.as_ptr().cast::<u8>()) };
+        Ok(self.link())
+    }
+
+    /// Insert a typed value, returning its key.
+    ///
+    /// If `T` is not the value type, you get the value back.
+    pub fn insert_as<T: 'static>(&mut self, value: T) -> Result<Key, T> {
+        self.values.try_push(value)?;
+        Ok(self.link())
+    }
+
+    /// Remove the value of a key and return it.
+    ///
+    /// If the key is stale, or if `T` is not the value type, you get `None`.
+    pub fn remove<T: 'static>(&mut self, key: Key) -> Option<T> {
+        if !self.contains::<T>() {
+            return None;
+        }
+
+        let position = self.unlink(key)?;
+        self.values.swap_remove(position)
+    }
+
+    /// Remove the value of a key and drop it.
+    ///
+    /// Returns whether the key was valid.
+    pub fn discard(&mut self, key: Key) -> bool {
+        let Some(position) = self.unlink(key) else {
+            return false;
+        };
+
+        self.values.swap_discard(position);
+        true
+    }
+
+    /// Remove all values, dropping them.
+    ///
+    /// All existing keys become stale.
+    pub fn clear(&mut self) {
+        while let Some(&owner) = self.owners.last() {
+            let generation = self.slots[owner as usize].generation;
+            self.discard(Key {
+                index: owner,
+                generation,
+            });
+        }
+    }
+
+    /// Retrieve an immutable reference to the value of a key.
+    pub fn get(&self, key: Key) -> Option<AnyRef<'_>> {
+        self.values.get(self.position(key)?)
+    }
+
+    /// Retrieve a mutable reference to the value of a key.
+    pub fn get_mut(&mut self, key: Key) -> Option<AnyMut<'_>> {
+        let position = self.position(key)?;
+        self.values.get_mut(position)
+    }
+
+    /// Retrieve a typed immutable reference to the value of a key.
+    ///
+    /// If the key is stale, or if `T` is not the value type, you get `None`.
+    pub fn get_as<T: 'static>(&self, key: Key) -> Option<&T> {
+        self.get(key)?.unerase_into()
+    }
+
+    /// Retrieve a typed mutable reference to the value of a key.
+    ///
+    /// If the key is stale, or if `T` is not the value type, you get `None`.
+    pub fn get_as_mut<T: 'static>(&mut self, key: Key) -> Option<&mut T> {
+        self.get_mut(key)?.unerase_into()
+    }
+
+    /// Does the key refer to a value in this map?
+    pub fn contains_key(&self, key: Key) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Access the packed values as an erased slice.
+    ///
+    /// The values are in the same order as [`AnySlotMap::keys()`].
+    pub fn values(&self) -> AnySliceRef<'_> {
+        self.values.as_slice()
+    }
+
+    /// Access the packed values as a mutable erased slice.
+    ///
+    /// The values are in the same order as [`AnySlotMap::keys()`].
+    pub fn values_mut(&mut self) -> AnySliceMut<'_> {
+        self.values.as_mut_slice()
+    }
+
+    /// Iterate over the keys of all values, in packed order.
+    pub fn keys(&self) -> impl ExactSizeIterator<Item = Key> + '_ {
+        self.owners.iter().map(|&index| Key {
+            index,
+            generation: self.slots[index as usize].generation,
+        })
+    }
+
+    /// Iterate over the keys and typed values, in packed order.
+    ///
+    /// If `T` is not the value type, you get `None`.
+    pub fn iter<T: 'static>(&self) -> Option<impl ExactSizeIterator<Item = (Key, &T)>> {
+        let values = self.values.as_slice().unerase_into::<T>()?;
+        Some(self.keys().zip(values))
+    }
+
+    /// How many values does the map contain?
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Does the map contain any values at all?
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Are the values of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.values.contains::<T>()
+    }
+
+    /// The `size_of()` of the values.
+    pub fn stride(&self) -> usize {
+        self.values.stride()
+    }
+
+    /// A unique type id representing the value type.
+    pub fn type_id(&self) -> &TypeId {
+        self.values.type_id()
+    }
+
+    /// The runtime information about the value type.
+    pub fn type_info(&self) -> &TypeInfo {
+        self.values.type_info()
+    }
+
+    /// Check that values of type `type_id` can be stored in the map
+    fn check_type(&self, type_id: TypeId) -> Result<(), SlotMapError> {
+        if type_id == *self.values.type_id() {
+            Ok(())
+        } else {
+            Err(SlotMapError::TypeMismatch {
+                expected: *self.values.type_id(),
+                found: type_id,
+            })
+        }
+    }
+
+    /// The position of the value of a key in the packed values
+    fn position(&self, key: Key) -> Option<usize> {
+        let slot = self.slots.get(key.index as usize)?;
+        (slot.generation == key.generation)
+            .then_some(slot.position)
+            .flatten()
+    }
+
+    /// Link the last packed value to a vacant slot, and return its key
+    fn link(&mut self) -> Key {
+        let position = self.owners.len();
+
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                let index = u32::try_from(self.slots.len()).expect("slot map has too many slots");
+                self.slots.push(Slot {
+                    generation: 0,
+                    position: None,
+                });
+                index
+            }
+        };
+
+        let slot = &mut self.slots[index as usize];
+        slot.position = Some(position);
+        self.owners.push(index);
+
+        Key {
+            index,
+            generation: slot.generation,
+        }
+    }
+
+    /// Unlink a key from its packed value, expecting that value to be swap-removed afterwards
+    fn unlink(&mut self, key: Key) -> Option<usize> {
+        let position = self.position(key)?;
+        let slot = &mut self.slots[key.index as usize];
+        slot.position = None;
+
+        // Slots whose generation would wrap around are retired, so old keys can never become valid again
+        if let Some(generation) = slot.generation.checked_add(1) {
+            slot.generation = generation;
+            self.free.push(key.index);
+        }
+
+        self.owners.swap_remove(position);
+        if let Some(&moved) = self.owners.get(position) {
+            self.slots[moved as usize].position = Some(position);
+        }
+
+        Some(position)
+    }
+}
+
+/// A generational key to a value in an [`AnySlotMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Key {
+    /// The index of the slot
+    index: u32,
+
+    /// The generation of the slot when the value was inserted
+    generation: u32,
+}
+
+impl Key {
+    /// The index of the slot the key refers to.
+    pub const fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The generation of the slot when the value was inserted.
+    pub const fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+/// A slot that keys refer to
+#[derive(Debug)]
+struct Slot {
+    /// The number of times a value was removed from this slot
+    generation: u32,
+
+    /// The position of the value in the packed values, if the slot is occupied
+    position: Option<usize>,
+}
+
+/// The reasons inserting into a slot map can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotMapError {
+    /// The value has a different type than the map
+    TypeMismatch { expected: TypeId, found: TypeId },
+
+    /// The value type is not known to be `Copy`, so it can't be copied from an erased reference
+    NotCopy,
+}
+
+impl fmt::Display for SlotMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected a value of {expected:?}, but got {found:?}")
+            }
+            Self::NotCopy => write!(f, "the value type is not known to be `Copy`"),
+        }
+    }
+}
+
+impl core::error::Error for SlotMapError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{DropCounter, Tracked};
+    use core::mem::ManuallyDrop;
+
+    // These tests use an (u8, u16) because it has padding, and `Tracked` values to count drops
+
+    #[test]
+    fn stale_keys() {
+        let mut map = AnySlotMap::with_type_info(TypeInfo::of_copy::<(u8, u16)>());
+
+        let first = map.insert(AnyRef::erase(&(1u8, 1u16))).unwrap();
+        let second = map.insert(AnyRef::erase(&(2u8, 2u16))).unwrap();
+        assert!(matches!(
+            map.insert(AnyRef::erase(&0u8)),
+            Err(SlotMapError::TypeMismatch { .. })
+        ));
+
+        assert_eq!(map.remove::<u8>(first), None);
+        assert_eq!(map.remove::<(u8, u16)>(first), Some((1u8, 1u16)));
+        assert_eq!(map.remove::<(u8, u16)>(first), None);
+
+        // The slot is reused with a new generation
+        let third = map.insert_as((3u8, 3u16)).unwrap();
+        assert_eq!(third.index(), first.index());
+        assert_ne!(third.generation(), first.generation());
+        assert!(!map.contains_key(first));
+        assert!(map.get(first).is_none());
+
+        *map.get_as_mut::<(u8, u16)>(second).unwrap() = (4u8, 4u16);
+        assert_eq!(
+            map.iter::<(u8, u16)>().unwrap().collect::<Vec<_>>(),
+            [(second, &(4u8, 4u16)), (third, &(3u8, 3u16))]
+        );
+
+        // Exhausted slots are retired instead of wrapping around
+        map.slots[third.index as usize].generation = u32::MAX;
+        let last = map.keys().last().unwrap();
+        assert!(map.discard(last));
+        assert_eq!(map.insert_as((5u8, 5u16)).unwrap().index(), 2);
+
+        map.clear();
+        assert!(map.is_empty());
+        assert!(map.get(second).is_none());
+    }
+
+    #[test]
+    fn drops() {
+        static DROPS: DropCounter = DropCounter::new();
+
+        let mut map = AnySlotMap::new::<Tracked>();
+        assert_eq!(
+            map.insert(AnyRef::erase(&DROPS.track(()))),
+            Err(SlotMapError::NotCopy)
+        );
+        assert_eq!(DROPS.drops(), 1);
+
+        let tracked = ManuallyDrop::new(DROPS.track(()));
+        let key = unsafe { map.insert_moved(AnyRef::erase(&*tracked)).unwrap() };
+        assert!(map.insert_as(DROPS.track(())).is_ok());
+
+        assert!(map.discard(key));
+        assert!(!map.discard(key));
+        assert_eq!(DROPS.drops(), 2);
+
+        drop(map);
+        assert_eq!(DROPS.drops(), 3);
+    }
+}
@@ -0,0 +1,374 @@
+use crate::{AnyMut, AnyRef, AnySliceMut, AnySliceRef, AnyVec, TypeInfo};
+use alloc::vec::Vec;
+use core::{any::TypeId, fmt};
+
+/// A type-erased sparse set, mapping indices to packed values of a single type.
+///
+/// This is the other common storage for entity-component systems, next to dense tables: values are
+/// keyed by an entity index, but stored contiguously in insertion order (give or take removals) so
+/// they can be iterated over quickly. Lookups, insertions and removals are all constant time.
+///
+/// The memory used for the index lookup grows with the largest index, so keep indices small.
+///
+/// ```
+/// let mut set = sashay::AnySparseSet::with_type_info(sashay::TypeInfo::of_copy::<f32>());
+///
+/// set.insert(10, sashay::AnyRef::erase(&1.0f32)).unwrap();
+/// set.insert(3, sashay::AnyRef::erase(&2.0f32)).unwrap();
+/// assert_eq!(set.get(10).unwrap().unerase::<f32>(), Some(&1.0));
+///
+/// assert!(set.remove(10));
+/// assert_eq!(set.dense().unerase::<f32>(), Some([2.0].as_slice()));
+///
+/// let pairs: Vec<_> = set.iter::<f32>().unwrap().collect();
+/// assert_eq!(pairs, [(3, &2.0)]);
+/// ```
+#[derive(Debug)]
+pub struct AnySparseSet {
+    /// The packed values
+    dense: AnyVec,
+
+    /// The index of every packed value, in the same order
+    indices: Vec<usize>,
+
+    /// For every index, the position of its value in `dense`, if any
+    sparse: Vec<Option<usize>>,
+}
+
+impl AnySparseSet {
+    /// Construct an empty set of values of type `T`.
+    pub fn new<T: 'static>() -> Self {
+        Self::with_type_info(TypeInfo::of::<T>())
+    }
+
+    /// Construct an empty set of values of a described type.
+    pub fn with_type_info(info: TypeInfo) -> Self {
+        Self {
+            dense: AnyVec::with_type_info(info),
+            indices: Vec::new(),
+            sparse: Vec::new(),
+        }
+    }
+
+    /// Insert a copy of an erased value at an index, dropping the previous value.
+    ///
+    /// Copying is only allowed if the value type is known to be `Copy` (see [`TypeInfo::is_copy()`]).
+    pub fn insert(&mut self, index: usize, value: AnyRef<'_>) -> Result<(), SparseSetError> {
+        self.check_type(*value.type_id())?;
+
+        if !self.dense.type_info().is_copy() {
+            return Err(SparseSetError::NotCopy);
+        }
+
+        // SAFETY: The value has the right type, and is Copy
+        unsafe { self.insert_unchecked(index, value) };
+        Ok(())
+    }
+
+    /// Move an erased value into an index, dropping the previous value.
+    ///
+    /// Unlike [`AnySparseSet::insert()`], this works for value types that aren't `Copy`.
+    ///
+    /// # Safety
+    ///
+    /// If this succeeds, the value has been moved into the set, so the caller must make sure the
+    /// original is never used or dropped again (for example by wrapping it in `ManuallyDrop`).
+    pub unsafe fn insert_moved(
+        &mut self,
+        index: usize,
+        value: AnyRef<'_>,
+    ) -> Result<(), SparseSetError> {
+        self.check_type(*value.type_id())?;
+
+        // SAFETY: The value has the right type, and the caller guarantees it may be moved
+        unsafe { self.insert_unchecked(index, value) };
+        Ok(())
+    }
+
+    /// Insert a typed value at an index, returning the previous value.
+    ///
+    /// If `T` is not the value type, you get the value back.
+    pub fn insert_as<T: 'static>(&mut self, index: usize, value: T) -> Result<Option<T>, T> {
+        if !self.contains::<T>() {
+            return Err(value);
+        }
+
+        let previous = self.remove_as::<T>(index);
+        self.dense.push(value);
+        self.link(index);
+
+        Ok(previous)
+    }
+
+    /// Remove the value at an index and drop it.
+    ///
+    /// Returns whether there was a value at the index.
+    pub fn remove(&mut self, index: usize) -> bool {
+        let Some(position) = self.unlink(index) else {
+            return false;
+        };
+
+        self.dense.swap_discard(position);
+        true
+    }
+
+    /// Remove the value at an index and return it.
+    ///
+    /// If there is no value at the index, or if `T` is not the value type, you get `None`.
+    pub fn remove_as<T: 'static>(&mut self, index: usize) -> Option<T> {
+        if !self.contains::<T>() {
+            return None;
+        }
+
+        let position = self.unlink(index)?;
+        self.dense.swap_remove(position)
+    }
+
+    /// Remove all values, dropping them.
+    pub fn clear(&mut self) {
+        self.dense.clear();
+        self.indices.clear();
+        self.sparse.clear();
+    }
+
+    /// Retrieve an immutable reference to the value at an index.
+    pub fn get(&self, index: usize) -> Option<AnyRef<'_>> {
+        self.dense.get(self.position(index)?)
+    }
+
+    /// Retrieve a mutable reference to the value at an index.
+    pub fn get_mut(&mut self, index: usize) -> Option<AnyMut<'_>> {
+        let position = self.position(index)?;
+        self.dense.get_mut(position)
+    }
+
+    /// Is there a value at an index?
+    pub fn has_index(&self, index: usize) -> bool {
+        self.position(index).is_some()
+    }
+
+    /// Access the packed values as an erased slice.
+    ///
+    /// The values are in the same order as [`AnySparseSet::indices()`].
+    pub fn dense(&self) -> AnySliceRef<'_> {
+        self.dense.as_slice()
+    }
+
+    /// Access the packed values as a mutable erased slice.
+    ///
+    /// The values are in the same order as [`AnySparseSet::indices()`].
+    pub fn dense_mut(&mut self) -> AnySliceMut<'_> {
+        self.dense.as_mut_slice()
+    }
+
+    /// The index of every packed value.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Iterate over the indices and typed values, in packed order.
+    ///
+    /// If `T` is not the value type, you get `None`.
+    pub fn iter<T: 'static>(&self) -> Option<impl ExactSizeIterator<Item = (usize, &T)>> {
+        let values = self.dense.as_slice().unerase_into::<T>()?;
+        Some(self.indices.iter().copied().zip(values))
+    }
+
+    /// Iterate over the indices and mutable typed values, in packed order.
+    ///
+    /// If `T` is not the value type, you get `None`.
+    pub fn iter_mut<T: 'static>(
+        &mut self,
+    ) -> Option<impl ExactSizeIterator<Item = (usize, &mut T)>> {
+        let values = self.dense.as_mut_slice().unerase_into::<T>()?;
+        Some(self.indices.iter().copied().zip(values))
+    }
+
+    /// How many values does the set contain?
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    /// Does the set contain any values at all?
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    /// Are the values of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.dense.contains::<T>()
+    }
+
+    /// The `size_of()` of the values.
+    pub fn stride(&self) -> usize {
+        self.dense.stride()
+    }
+
+    /// A unique type id representing the value type.
+    pub fn type_id(&self) -> &TypeId {
+        self.dense.type_id()
+    }
+
+    /// The runtime information about the value type.
+    pub fn type_info(&self) -> &TypeInfo {
+        self.dense.type_info()
+    }
+
+    /// Check that values of type `type_id` can be stored in the set
+    fn check_type(&self, type_id: TypeId) -> Result<(), SparseSetError> {
+        if type_id == *self.dense.type_id() {
+            Ok(())
+        } else {
+            Err(SparseSetError::TypeMismatch {
+                expected: *self.dense.type_id(),
+                found: type_id,
+            })
+        }
+    }
+
+    /// Insert a value by copying its bytes, dropping the previous value
+    ///
+    /// # Safety
+    ///
+    /// The value must have the right type, and must either be `Copy` or never be used or dropped again.
+    unsafe fn insert_unchecked(&mut self, index: usize, value: AnyRef<'_>) {
+        // SAFETY: The caller guarantees the value has the right type and may be copied or moved
+        unsafe { self.dense.push_raw(value.as_ptr().cast::<u8>()) };
+
+        match self.position(index) {
+            // The new value ends up in the position of the old one, which is dropped
+            Some(position) => {
+                self.dense.swap_discard(position);
+            }
+            None => self.link(index),
+        }
+    }
+
+    /// The position of the value at an index in the packed values
+    fn position(&self, index: usize) -> Option<usize> {
+        self.sparse.get(index).copied().flatten()
+    }
+
+    /// Link an index to the last packed value
+    fn link(&mut self, index: usize) {
+        if index >= self.sparse.len() {
+            self.sparse.resize(index + 1, None);
+        }
+
+        self.sparse[index] = Some(self.indices.len());
+        self.indices.push(index);
+    }
+
+    /// Unlink an index from its packed value, expecting that value to be swap-removed afterwards
+    fn unlink(&mut self, index: usize) -> Option<usize> {
+        let position = self.sparse.get_mut(index)?.take()?;
+
+        self.indices.swap_remove(position);
+        if let Some(&moved) = self.indices.get(position) {
+            self.sparse[moved] = Some(position);
+        }
+
+        Some(position)
+    }
+}
+
+/// The reasons inserting into a sparse set can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SparseSetError {
+    /// The value has a different type than the set
+    TypeMismatch { expected: TypeId, found: TypeId },
+
+    /// The value type is not known to be `Copy`, so it can't be copied from an erased reference
+    NotCopy,
+}
+
+impl fmt::Display for SparseSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected a value of {expected:?}, but got {found:?}")
+            }
+            Self::NotCopy => write!(f, "the value type is not known to be `Copy`"),
+        }
+    }
+}
+
+impl core::error::Error for SparseSetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{DropCounter, Tracked};
+    use core::mem::ManuallyDrop;
+
+    // These tests use an (u8, u16) because it has padding, and `Tracked` values to count drops
+
+    #[test]
+    fn insert_remove() {
+        let mut set = AnySparseSet::with_type_info(TypeInfo::of_copy::<(u8, u16)>());
+
+        for index in [5usize, 0, 9] {
+            set.insert(index, AnyRef::erase(&(index as u8, 0u16)))
+                .unwrap();
+        }
+        assert!(matches!(
+            set.insert(1, AnyRef::erase(&0u8)),
+            Err(SparseSetError::TypeMismatch { .. })
+        ));
+
+        set.insert(0, AnyRef::erase(&(1u8, 1u16))).unwrap();
+        assert_eq!(set.insert_as(9, (2u8, 2u16)), Ok(Some((9u8, 0u16))));
+        assert_eq!(set.insert_as(9, 0u8), Err(0u8));
+        assert_eq!(set.len(), 3);
+
+        assert!(set.remove(5));
+        assert!(!set.remove(5));
+        assert!(!set.has_index(5));
+        assert_eq!(set.remove_as::<(u8, u16)>(7), None);
+
+        assert_eq!(set.indices(), [9, 0]);
+        assert_eq!(
+            set.dense().unerase::<(u8, u16)>(),
+            Some([(2u8, 2u16), (1u8, 1u16)].as_slice())
+        );
+        for (index, value) in set.iter_mut::<(u8, u16)>().unwrap() {
+            value.1 = index as u16;
+        }
+        assert_eq!(
+            set.get(0).unwrap().unerase::<(u8, u16)>(),
+            Some(&(1u8, 0u16))
+        );
+        assert_eq!(
+            set.iter::<(u8, u16)>().unwrap().collect::<Vec<_>>(),
+            [(9, &(2u8, 9u16)), (0, &(1u8, 0u16))]
+        );
+    }
+
+    #[test]
+    fn drops() {
+        static DROPS: DropCounter = DropCounter::new();
+
+        let mut set = AnySparseSet::new::<Tracked>();
+        assert_eq!(
+            set.insert(0, AnyRef::erase(&DROPS.track(()))),
+            Err(SparseSetError::NotCopy)
+        );
+        assert_eq!(DROPS.drops(), 1);
+
+        for index in 0..3 {
+            let tracked = ManuallyDrop::new(DROPS.track(()));
+            unsafe { set.insert_moved(index, AnyRef::erase(&*tracked)).unwrap() };
+        }
+
+        // Replacing drops the previous value
+        let tracked = ManuallyDrop::new(DROPS.track(()));
+        unsafe { set.insert_moved(1, AnyRef::erase(&*tracked)).unwrap() };
+        assert_eq!(DROPS.drops(), 2);
+
+        set.remove(0);
+        assert_eq!(DROPS.drops(), 3);
+
+        drop(set);
+        assert_eq!(DROPS.drops(), 5);
+    }
+}
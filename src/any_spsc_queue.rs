@@ -0,0 +1,507 @@
+use crate::{AnySliceRef, TypeInfo};
+use core::{
+    any::TypeId,
+    fmt,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A wait-free single-producer single-consumer queue of type-erased elements.
+///
+/// The queue is created for a single element type, and then [split](AnySpscQueue::split()) into an
+/// [`AnyProducer`] and an [`AnyConsumer`] that can each be sent to a different thread. This makes it
+/// possible to pass erased messages from, say, a UI thread to a realtime audio thread. Neither side
+/// ever blocks, allocates or locks; they only synchronize through `core` atomics.
+///
+/// The element type must be `Send`, because values are moved from the producing to the consuming thread.
+///
+/// ```
+/// use std::mem::MaybeUninit;
+///
+/// let mut storage = [MaybeUninit::<u32>::uninit(); 4];
+/// let mut queue = sashay::AnySpscQueue::erase(storage.as_mut_slice());
+/// let (mut producer, mut consumer) = queue.split();
+///
+/// std::thread::scope(|scope| {
+///     scope.spawn(move || {
+///         for value in 0..10u32 {
+///             while producer.push(value).is_err() {}
+///         }
+///     });
+///
+///     let mut received = 0;
+///     while received < 10 {
+///         if let Some(value) = consumer.pop::<u32>() {
+///             assert_eq!(value, received);
+///             received += 1;
+///         }
+///     }
+/// });
+/// ```
+pub struct AnySpscQueue<'a> {
+    /// A raw pointer to the start of the storage
+    ///
+    /// Note: this pointer must be aligned and point to `capacity` possibly uninitialized values of `T`,
+    /// of which the ones from `head` up to `tail` (wrapping around) are initialized
+    ptr: *mut u8,
+
+    /// The number of elements that fit in the storage
+    capacity: usize,
+
+    /// The position of the front element, only advanced by the consumer
+    ///
+    /// Positions run up to twice the capacity, so a full queue can be told apart from an empty one.
+    head: AtomicUsize,
+
+    /// The position after the back element, only advanced by the producer
+    tail: AtomicUsize,
+
+    /// The type of the elements
+    info: TypeInfo,
+
+    /// Was the storage allocated by the queue itself, so it needs to be deallocated?
+    owned: bool,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a mut ()>,
+}
+
+// SAFETY: The element type is required to be Send, and the elements are only reachable by splitting,
+// which requires exclusive access. Shared access only exposes the positions, which are atomic.
+unsafe impl Send for AnySpscQueue<'_> {}
+
+// SAFETY: See above
+unsafe impl Sync for AnySpscQueue<'_> {}
+
+impl<'a> AnySpscQueue<'a> {
+    /// Construct an empty queue on top of a typed uninitialized buffer.
+    ///
+    /// The capacity is the length of the buffer.
+    pub fn erase<T: Send + 'static>(storage: &'a mut [MaybeUninit<T>]) -> Self {
+        Self::from_parts(
+            storage.as_mut_ptr().cast::<u8>(),
+            storage.len(),
+            TypeInfo::of::<T>(),
+            false,
+        )
+    }
+
+    /// Split the queue into its producing and consuming halves.
+    pub fn split(&mut self) -> (AnyProducer<'_>, AnyConsumer<'_>) {
+        (AnyProducer { queue: self }, AnyConsumer { queue: self })
+    }
+
+    /// How many elements does the queue contain?
+    pub fn len(&self) -> usize {
+        self.distance(
+            self.head.load(Ordering::Acquire),
+            self.tail.load(Ordering::Acquire),
+        )
+    }
+
+    /// Does the queue contain any elements at all?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many elements fit in the queue?
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Is the queue at its capacity?
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity
+    }
+
+    /// Are the elements of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.info.is::<T>()
+    }
+
+    /// The `size_of()` of the elements.
+    pub const fn stride(&self) -> usize {
+        self.info.stride()
+    }
+
+    /// A unique type id representing the element type.
+    pub const fn type_id(&self) -> &TypeId {
+        self.info.type_id()
+    }
+
+    /// The runtime information about the element type.
+    pub const fn type_info(&self) -> &TypeInfo {
+        &self.info
+    }
+
+    /// Construct an empty queue from its storage
+    fn from_parts(ptr: *mut u8, capacity: usize, info: TypeInfo, owned: bool) -> Self {
+        assert!(capacity <= usize::MAX / 4, "capacity overflow");
+
+        Self {
+            ptr,
+            capacity,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            info,
+            owned,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The number of elements from position `head` up to position `tail`
+    fn distance(&self, head: usize, tail: usize) -> usize {
+        if tail >= head {
+            tail - head
+        } else {
+            tail + 2 * self.capacity - head
+        }
+    }
+
+    /// The position `count` elements after `position`, where `count` is at most the capacity
+    fn advance(&self, position: usize, count: usize) -> usize {
+        let position = position + count;
+        if position >= 2 * self.capacity {
+            position - 2 * self.capacity
+        } else {
+            position
+        }
+    }
+
+    /// The index in the storage of the element at `position`
+    fn index(&self, position: usize) -> usize {
+        if position >= self.capacity {
+            position - self.capacity
+        } else {
+            position
+        }
+    }
+
+    /// A pointer to the storage slot of the element at `position`
+    fn slot(&self, position: usize) -> *mut u8 {
+        self.ptr
+            .wrapping_add(self.index(position) * self.info.stride())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AnySpscQueue<'static> {
+    /// Construct an empty queue of elements of type `T`, that owns its storage.
+    ///
+    /// This allocates once, up front. To hand the halves to threads that outlive the current scope,
+    /// leak the queue (for example with `Box::leak()`) before splitting it.
+    pub fn with_capacity<T: Send + 'static>(capacity: usize) -> Self {
+        let info = TypeInfo::of::<T>();
+        Self::from_parts(info.allocate_array(capacity), capacity, info, true)
+    }
+}
+
+impl Drop for AnySpscQueue<'_> {
+    fn drop(&mut self) {
+        self.split().1.discard(usize::MAX);
+
+        #[cfg(feature = "alloc")]
+        if self.owned {
+            // SAFETY: The storage was allocated with the same type information and capacity
+            unsafe { self.info.deallocate_array(self.ptr, self.capacity) };
+        }
+    }
+}
+
+impl fmt::Debug for AnySpscQueue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnySpscQueue")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .field("info", &self.info)
+            .field("owned", &self.owned)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The producing half of an [`AnySpscQueue`], which pushes elements at the back.
+#[derive(Debug)]
+pub struct AnyProducer<'a> {
+    queue: &'a AnySpscQueue<'a>,
+}
+
+impl AnyProducer<'_> {
+    /// Append an element at the back of the queue, if there is room for it.
+    ///
+    /// If the queue is full, or if `T` is not the element type, you get the value back.
+    pub fn push<T: 'static>(&mut self, value: T) -> Result<(), T> {
+        let queue = self.queue;
+        if !queue.contains::<T>() {
+            return Err(value);
+        }
+
+        // The producer is the only one advancing the tail, so its own last store is always visible.
+        // Acquiring the head makes sure the consumer is done with the slot we're about to write.
+        let tail = queue.tail.load(Ordering::Relaxed);
+        if queue.distance(queue.head.load(Ordering::Acquire), tail) == queue.capacity {
+            return Err(value);
+        }
+
+        // SAFETY: The slot at the tail is within the storage and not in use by the consumer, and of type T
+        unsafe { queue.slot(tail).cast::<T>().write(value) };
+        queue.tail.store(queue.advance(tail, 1), Ordering::Release);
+
+        Ok(())
+    }
+
+    /// How many elements does the queue contain?
+    ///
+    /// The consumer can pop elements at any time, so this is an upper bound.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Does the queue contain any elements at all?
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Is the queue at its capacity?
+    pub fn is_full(&self) -> bool {
+        self.queue.is_full()
+    }
+
+    /// How many elements fit in the queue?
+    pub const fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// Are the elements of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.queue.contains::<T>()
+    }
+
+    /// A unique type id representing the element type.
+    pub const fn type_id(&self) -> &TypeId {
+        self.queue.type_id()
+    }
+}
+
+/// The consuming half of an [`AnySpscQueue`], which pops elements from the front.
+#[derive(Debug)]
+pub struct AnyConsumer<'a> {
+    queue: &'a AnySpscQueue<'a>,
+}
+
+impl AnyConsumer<'_> {
+    /// Remove the front element from the queue and return it.
+    ///
+    /// If the queue is empty, or if `T` is not the element type, you get `None`.
+    pub fn pop<T: 'static>(&mut self) -> Option<T> {
+        let queue = self.queue;
+        if !queue.contains::<T>() {
+            return None;
+        }
+
+        // The consumer is the only one advancing the head, so its own last store is always visible.
+        // Acquiring the tail makes sure the producer is done writing the slots up to it.
+        let head = queue.head.load(Ordering::Relaxed);
+        if head == queue.tail.load(Ordering::Acquire) {
+            return None;
+        }
+
+        // SAFETY: The slot at the head is initialized, and is handed back to the producer right after
+        let value = unsafe { queue.slot(head).cast::<T>().read() };
+        queue.head.store(queue.advance(head, 1), Ordering::Release);
+
+        Some(value)
+    }
+
+    /// Access the available elements as two erased slices, which together contain them in order.
+    ///
+    /// This lets you process a whole batch without copying. The second slice is only non-empty if the
+    /// elements wrap around the end of the storage. Once you're done, remove the batch from the queue
+    /// with [`AnyConsumer::discard()`].
+    ///
+    /// ```
+    /// use std::mem::MaybeUninit;
+    ///
+    /// let mut storage = [MaybeUninit::<u32>::uninit(); 4];
+    /// let mut queue = sashay::AnySpscQueue::erase(storage.as_mut_slice());
+    /// let (mut producer, mut consumer) = queue.split();
+    ///
+    /// producer.push(1u32).unwrap();
+    /// producer.push(2u32).unwrap();
+    ///
+    /// let (front, back) = consumer.as_slices();
+    /// assert_eq!(front.unerase::<u32>(), Some([1, 2].as_slice()));
+    /// assert!(back.is_empty());
+    ///
+    /// assert_eq!(consumer.discard(2), 2);
+    /// assert!(consumer.is_empty());
+    /// ```
+    pub fn as_slices(&self) -> (AnySliceRef<'_>, AnySliceRef<'_>) {
+        let queue = self.queue;
+        let head = queue.head.load(Ordering::Relaxed);
+        let len = queue.distance(head, queue.tail.load(Ordering::Acquire));
+
+        let front = len.min(queue.capacity - queue.index(head));
+        let (stride, type_id) = (queue.info.stride(), *queue.info.type_id());
+
+        // SAFETY: The halves only cover initialized elements, which the producer won't touch until
+        // the consumer (which we borrow) has advanced the head past them
+        unsafe {
+            (
+                AnySliceRef::from_raw_parts(
+                    queue.slot(head).cast_const().cast::<()>(),
+                    front,
+                    stride,
+                    type_id,
+                ),
+                AnySliceRef::from_raw_parts(
+                    queue.ptr.cast_const().cast::<()>(),
+                    len - front,
+                    stride,
+                    type_id,
+                ),
+            )
+        }
+    }
+
+    /// Remove up to `count` elements from the front of the queue and drop them.
+    ///
+    /// Returns how many elements were removed.
+    pub fn discard(&mut self, count: usize) -> usize {
+        let queue = self.queue;
+        let head = queue.head.load(Ordering::Relaxed);
+        let count = count.min(queue.distance(head, queue.tail.load(Ordering::Acquire)));
+
+        for offset in 0..count {
+            let position = queue.advance(head, offset);
+
+            // Hand the slot back to the producer even if dropping the element panics
+            let _release = Release {
+                head: &queue.head,
+                position: queue.advance(position, 1),
+            };
+
+            // SAFETY: The element is initialized, and the slot is handed back to the producer right after
+            unsafe { queue.info.drop_in_place(queue.slot(position).cast::<()>()) };
+        }
+
+        count
+    }
+
+    /// How many elements does the queue contain?
+    ///
+    /// The producer can push elements at any time, so this is a lower bound.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Does the queue contain any elements at all?
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// How many elements fit in the queue?
+    pub const fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// Are the elements of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.queue.contains::<T>()
+    }
+
+    /// A unique type id representing the element type.
+    pub const fn type_id(&self) -> &TypeId {
+        self.queue.type_id()
+    }
+}
+
+/// Stores a new head position when dropped
+struct Release<'a> {
+    head: &'a AtomicUsize,
+    position: usize,
+}
+
+impl Drop for Release<'_> {
+    fn drop(&mut self) {
+        self.head.store(self.position, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::testing::{DropCounter, Tracked};
+
+    // These tests use an (u8, u16) because it has padding, and `Tracked` values to count drops
+
+    #[test]
+    fn wrap_around() {
+        let mut storage = [MaybeUninit::<(u8, u16)>::uninit(); 3];
+        let mut queue = AnySpscQueue::erase(storage.as_mut_slice());
+        let (mut producer, mut consumer) = queue.split();
+
+        assert_eq!(producer.push(0u8), Err(0u8));
+        for value in 0..3u8 {
+            producer.push((value, 0u16)).unwrap();
+        }
+        assert!(producer.is_full());
+        assert_eq!(producer.push((3u8, 0u16)), Err((3u8, 0u16)));
+
+        assert_eq!(consumer.pop::<u8>(), None);
+        assert_eq!(consumer.pop::<(u8, u16)>(), Some((0u8, 0u16)));
+        producer.push((3u8, 0u16)).unwrap();
+
+        let (front, back) = consumer.as_slices();
+        assert_eq!(
+            front.unerase::<(u8, u16)>(),
+            Some([(1u8, 0u16), (2u8, 0u16)].as_slice())
+        );
+        assert_eq!(back.unerase::<(u8, u16)>(), Some([(3u8, 0u16)].as_slice()));
+
+        assert_eq!(consumer.discard(2), 2);
+        assert_eq!(consumer.pop::<(u8, u16)>(), Some((3u8, 0u16)));
+        assert_eq!(consumer.discard(1), 0);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn threads() {
+        static DROPS: DropCounter = DropCounter::new();
+
+        let mut storage: [MaybeUninit<Tracked<(u8, u16)>>; 4] =
+            [const { MaybeUninit::uninit() }; 4];
+        let mut queue = AnySpscQueue::erase(storage.as_mut_slice());
+        let (mut producer, mut consumer) = queue.split();
+
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                for value in 0..20u8 {
+                    let mut tracked = DROPS.track((value, 0u16));
+                    while let Err(rejected) = producer.push(tracked) {
+                        tracked = rejected;
+                        std::thread::yield_now();
+                    }
+                }
+            });
+
+            let mut expected = 0u8;
+            while expected < 18 {
+                match consumer.pop::<Tracked<(u8, u16)>>() {
+                    Some(tracked) => {
+                        assert_eq!(tracked.value, (expected, 0u16));
+                        expected += 1;
+                    }
+                    None => std::thread::yield_now(),
+                }
+            }
+        });
+
+        // The last two elements are dropped along with the queue
+        assert_eq!(queue.len(), 2);
+        drop(queue);
+        assert_eq!(DROPS.drops(), 20);
+    }
+}
@@ -0,0 +1,416 @@
+use crate::{
+    range::{chunk_ranges, constrain_range},
+    AnyMut, AnyRef, AnySliceMut, AnySliceRef, AnyStridedRef, StridedMut, StridedRef,
+};
+use core::{any::TypeId, marker::PhantomData, mem::size_of, ops::RangeBounds};
+
+/// A type-erased mutable view of elements that are evenly spaced in memory.
+///
+/// This is like an [`AnySliceMut`], except that subsequent elements don't have to be adjacent. Instead,
+/// they are a fixed number of bytes apart, called the `step`. See [`AnyStridedRef`] for more information.
+///
+/// Unerasing gives you a [`StridedMut`], because a regular `&mut [T]` can't represent gaps between elements.
+///
+/// ```
+/// let mut data : [i32; 4] = [0, 1, 2, 3];
+/// let mut any = sashay::AnySliceMut::erase(data.as_mut_slice());
+///
+/// let mut right = any.interleaved_channel_mut(1, 2).unwrap();
+/// right.unerase_mut::<i32>().unwrap()[1] = 9;
+///
+/// assert_eq!(data, [0, 1, 2, 9]);
+/// ```
+#[derive(Debug)]
+pub struct AnyStridedMut<'a> {
+    /// A raw pointer to the first element
+    ///
+    /// Note: this pointer must be aligned and point to valid values of `T` at
+    /// subsequent positions along the step
+    ptr: *mut u8,
+
+    /// The number of elements in the view
+    len: usize,
+
+    /// The distance in bytes from one element to the next
+    ///
+    /// This is a multiple of `stride`, such that ptr + N * step points to subsequent elements
+    step: isize,
+
+    /// The `size_of()` of the individual elements
+    stride: usize,
+
+    /// A unique id representing the type of the referenced elements
+    ///
+    /// This is used to ensure we can safely unerase back without accidentally transmuting
+    type_id: TypeId,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a mut ()>,
+}
+
+impl<'a> AnyStridedMut<'a> {
+    /// Erase the type of a mutable strided view's elements.
+    pub fn erase<T: 'static>(mut view: StridedMut<'a, T>) -> AnyStridedMut<'a> {
+        // SAFETY: The raw parts come from a valid view, and the TypeId and stride are provided by the compiler
+        unsafe {
+            Self::from_raw_parts(
+                view.as_mut_ptr().cast::<()>(),
+                view.len(),
+                view.step(),
+                size_of::<T>(),
+                TypeId::of::<T>(),
+            )
+        }
+    }
+
+    /// Construct an erased mutable strided view from its raw parts.
+    ///
+    /// The parameters are the same as for [`AnySliceMut::from_raw_parts()`], with the addition
+    /// of `step`: the (possibly negative) distance in bytes from one element to the next.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if:
+    ///  - For every index below `len`, `ptr` offset by `index * step` bytes points to a valid and aligned `T`
+    ///  - None of these elements overlap, and they aren't accessed through any other pointer for the duration of `'a`
+    ///  - `stride` is the correct [`size_of()`](https://doc.rust-lang.org/std/mem/fn.size_of.html) for the element type `T`
+    ///  - `type_id` is the correct [`TypeId`](https://doc.rust-lang.org/stable/std/any/struct.TypeId.html) for the element type `T`
+    pub unsafe fn from_raw_parts(
+        ptr: *mut (),
+        len: usize,
+        step: isize,
+        stride: usize,
+        type_id: TypeId,
+    ) -> Self {
+        Self {
+            ptr: ptr.cast::<u8>(),
+            len,
+            step,
+            stride,
+            type_id,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Unerase back to an immutable strided view.
+    pub fn unerase<T: 'static>(&self) -> Option<StridedRef<'_, T>> {
+        self.borrow().unerase_into()
+    }
+
+    /// Unerase back to a mutable strided view.
+    ///
+    /// If the original element type was `T`, a valid view is returned. Otherwise, you get `None`.
+    pub fn unerase_mut<T: 'static>(&mut self) -> Option<StridedMut<'_, T>> {
+        self.borrow_mut().unerase_into()
+    }
+
+    /// Unerase back into a mutable strided view, transferring ownership into it.
+    pub fn unerase_into<T: 'static>(self) -> Option<StridedMut<'a, T>> {
+        self.contains::<T>().then(|| {
+            // SAFETY:
+            // - We've checked the TypeId of T against the one created at construction, so we're not
+            //   accidentally transmuting to a different type
+            // - The pointer and step describe valid, non-overlapping elements as per construction
+            unsafe { StridedMut::from_raw_parts(self.ptr.cast::<T>(), self.len, self.step) }
+        })
+    }
+
+    /// Borrow this mutable view as an immutable one.
+    pub fn borrow(&self) -> AnyStridedRef<'_> {
+        // SAFETY: All parts are valid, and we have an immutable reference to self
+        unsafe {
+            AnyStridedRef::from_raw_parts(
+                self.ptr.cast_const().cast::<()>(),
+                self.len,
+                self.step,
+                self.stride,
+                self.type_id,
+            )
+        }
+    }
+
+    /// Borrow this mutable view as a mutable view with lifetime 'self.
+    pub fn borrow_mut(&mut self) -> AnyStridedMut<'_> {
+        // SAFETY: All parts are valid, and we have a mutable reference to self
+        unsafe {
+            AnyStridedMut::from_raw_parts(
+                self.ptr.cast::<()>(),
+                self.len,
+                self.step,
+                self.stride,
+                self.type_id,
+            )
+        }
+    }
+
+    /// Retrieve an immutable reference to one of the elements in the view.
+    pub fn get(&self, index: usize) -> Option<AnyRef<'_>> {
+        // SAFETY: The index is within bounds, so the element is valid as per construction
+        (index < self.len).then(|| unsafe {
+            AnyRef::from_raw_parts(self.element(index).cast_const(), self.type_id)
+        })
+    }
+
+    /// Retrieve a mutable reference to one of the elements in the view.
+    ///
+    /// ```
+    /// let mut data : [i32; 4] = [0, 1, 2, 3];
+    /// let mut any = sashay::AnySliceMut::erase(data.as_mut_slice());
+    /// let mut left = any.interleaved_channel_mut(0, 2).unwrap();
+    ///
+    /// *left.get_mut(1).unwrap().unerase_into::<i32>().unwrap() = 7;
+    ///
+    /// assert_eq!(data, [0, 1, 7, 3]);
+    /// ```
+    pub fn get_mut(&mut self, index: usize) -> Option<AnyMut<'_>> {
+        // SAFETY: The index is within bounds, so the element is valid as per construction
+        (index < self.len)
+            .then(|| unsafe { AnyMut::from_raw_parts(self.element(index), self.type_id) })
+    }
+
+    /// Iterate over references to all elements in the view, in order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = AnyRef<'_>> + ExactSizeIterator {
+        let type_id = self.type_id;
+
+        // SAFETY: The index is within bounds, so the element is valid as per construction
+        (0..self.len).map(move |index| unsafe {
+            AnyRef::from_raw_parts(self.element(index).cast_const(), type_id)
+        })
+    }
+
+    /// Iterate over mutable references to all elements in the view, in order.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = AnyMut<'_>> + ExactSizeIterator {
+        let (ptr, step, type_id) = (self.ptr, self.step, self.type_id);
+
+        // SAFETY:
+        // - The index is within bounds, so the element is valid as per construction
+        // - Every index is only visited once, and elements don't overlap, so the references are unique
+        (0..self.len).map(move |index| unsafe {
+            AnyMut::from_raw_parts(
+                ptr.wrapping_offset(index as isize * step).cast::<()>(),
+                type_id,
+            )
+        })
+    }
+
+    /// Access an immutable smaller view within a given range.
+    pub fn subslice<R>(&self, range: R) -> AnyStridedRef<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.borrow().subslice_into(range)
+    }
+
+    /// Access a mutable smaller view within a given range.
+    pub fn subslice_mut<R>(&mut self, range: R) -> AnyStridedMut<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.borrow_mut().subslice_into(range)
+    }
+
+    /// Iterate over consecutive immutable smaller views of `chunk_size` elements each.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn chunks(
+        &self,
+        chunk_size: usize,
+    ) -> impl DoubleEndedIterator<Item = AnyStridedRef<'_>> + ExactSizeIterator {
+        chunk_ranges(self.len, chunk_size).map(|range| self.subslice(range))
+    }
+
+    /// Iterate over consecutive mutable smaller views of `chunk_size` elements each.
+    ///
+    /// The last chunk is shorter if the length isn't a multiple of `chunk_size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    ///
+    /// ```
+    /// let mut data : [i32; 5] = [0, 1, 2, 3, 4];
+    /// let mut any = sashay::AnySliceMut::erase(data.as_mut_slice());
+    /// let mut reversed = any.rev_view_mut();
+    ///
+    /// for (index, mut chunk) in reversed.chunks_mut(2).enumerate() {
+    ///     chunk.unerase_mut::<i32>().unwrap().iter_mut().for_each(|value| *value = index as i32);
+    /// }
+    ///
+    /// assert_eq!(data, [2, 1, 1, 0, 0]);
+    /// ```
+    pub fn chunks_mut(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl DoubleEndedIterator<Item = AnyStridedMut<'_>> + ExactSizeIterator {
+        let (ptr, step, stride, type_id) = (self.ptr, self.step, self.stride, self.type_id);
+
+        // SAFETY:
+        // - The ranges are within bounds, so the elements are valid as per construction
+        // - The ranges never overlap, so the views are unique
+        chunk_ranges(self.len, chunk_size).map(move |range| unsafe {
+            AnyStridedMut::from_raw_parts(
+                ptr.wrapping_offset(range.start as isize * step)
+                    .cast::<()>(),
+                range.len(),
+                step,
+                stride,
+                type_id,
+            )
+        })
+    }
+
+    /// Access a mutable smaller view within a given range, transferring ownership into it.
+    pub fn subslice_into<R>(self, range: R) -> AnyStridedMut<'a>
+    where
+        R: RangeBounds<usize>,
+    {
+        let range = constrain_range(self.len, range);
+
+        // SAFETY: `constrain_range()` ensures that the new elements are a subset of the current ones
+        unsafe {
+            Self::from_raw_parts(
+                self.element(range.start),
+                range.len(),
+                self.step,
+                self.stride,
+                self.type_id,
+            )
+        }
+    }
+
+    /// View the elements as a regular immutable erased slice, if they are adjacent in memory.
+    pub fn as_slice(&self) -> Option<AnySliceRef<'_>> {
+        // SAFETY: The elements are adjacent, so they form a valid slice
+        self.is_contiguous().then(|| unsafe {
+            AnySliceRef::from_raw_parts(self.as_ptr(), self.len, self.stride, self.type_id)
+        })
+    }
+
+    /// View the elements as a regular mutable erased slice, if they are adjacent in memory.
+    pub fn as_slice_mut(&mut self) -> Option<AnySliceMut<'_>> {
+        // SAFETY: The elements are adjacent, so they form a valid slice
+        self.is_contiguous().then(|| unsafe {
+            AnySliceMut::from_raw_parts(self.as_mut_ptr(), self.len, self.stride, self.type_id)
+        })
+    }
+
+    /// Are the elements adjacent in memory and in increasing order?
+    pub const fn is_contiguous(&self) -> bool {
+        self.len <= 1 || self.step == self.stride as isize
+    }
+
+    /// Retrieve an unsafe pointer to the first element.
+    pub const fn as_ptr(&self) -> *const () {
+        self.ptr.cast_const().cast::<()>()
+    }
+
+    /// Retrieve an unsafe mutable pointer to the first element.
+    pub fn as_mut_ptr(&mut self) -> *mut () {
+        self.ptr.cast::<()>()
+    }
+
+    /// How many elements does the view contain?
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Does the view contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Was the original element of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        TypeId::of::<T>() == self.type_id
+    }
+
+    /// The distance in bytes from one element to the next.
+    pub const fn step(&self) -> isize {
+        self.step
+    }
+
+    /// The `size_of()` of the original elements of type `T`.
+    pub const fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// A unique type id representing the original element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        &self.type_id
+    }
+
+    /// A pointer to the element at `index`, which doesn't need to be in bounds
+    fn element(&self, index: usize) -> *mut () {
+        self.ptr
+            .wrapping_offset(index as isize * self.step)
+            .cast::<()>()
+    }
+}
+
+impl<'a> From<AnySliceMut<'a>> for AnyStridedMut<'a> {
+    fn from(mut slice: AnySliceMut<'a>) -> Self {
+        // SAFETY: Slice elements are exactly `stride` bytes apart, and don't overlap
+        unsafe {
+            Self::from_raw_parts(
+                slice.as_mut_ptr(),
+                slice.len(),
+                slice.stride() as isize,
+                slice.stride(),
+                *slice.type_id(),
+            )
+        }
+    }
+}
+
+impl<'a, T: 'static> From<StridedMut<'a, T>> for AnyStridedMut<'a> {
+    fn from(view: StridedMut<'a, T>) -> Self {
+        Self::erase(view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // All these tests use an (u8, u16) because it has padding
+
+    #[test]
+    fn erase_unerase() {
+        let mut data = [(0u8, 1u16), (2u8, 3u16), (4u8, 5u16), (6u8, 7u16)];
+        let mut any = AnySliceMut::erase(data.as_mut_slice());
+
+        let [mut left, mut right] = any.interleaved_channels_mut::<2>();
+        assert!(left.unerase_mut::<u8>().is_none());
+
+        let mut left = left.unerase_mut::<(u8, u16)>().unwrap();
+        let right = right.unerase_mut::<(u8, u16)>().unwrap();
+        for (l, r) in left.iter_mut().zip(right.iter()) {
+            l.1 += r.1;
+        }
+
+        assert_eq!(data, [(0, 4), (2, 3), (4, 12), (6, 7)]);
+
+        let mut any = AnySliceMut::erase(data.as_mut_slice());
+        assert!(any.interleaved_channel_mut(0, usize::MAX / 2).is_none()); // frames overflow
+    }
+
+    #[test]
+    fn sub() {
+        let mut data = [(0u8, 1u16), (2u8, 3u16), (4u8, 5u16)];
+        let mut any = AnyStridedMut::from(AnySliceMut::erase(data.as_mut_slice()));
+
+        for mut element in any.subslice_mut(1..).iter_mut() {
+            element.unerase_mut::<(u8, u16)>().unwrap().0 = 9;
+        }
+
+        any.as_slice_mut()
+            .unwrap()
+            .unerase_mut::<(u8, u16)>()
+            .unwrap()[0]
+            .0 = 8;
+
+        assert_eq!(data, [(8, 1), (9, 3), (9, 5)]);
+    }
+}
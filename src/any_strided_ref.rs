@@ -0,0 +1,368 @@
+use crate::{
+    range::{chunk_ranges, constrain_range},
+    AnyRef, AnySliceRef, StridedRef,
+};
+use core::{any::TypeId, marker::PhantomData, mem::size_of, ops::RangeBounds};
+
+/// A type-erased immutable view of elements that are evenly spaced in memory.
+///
+/// This is like an [`AnySliceRef`], except that subsequent elements don't have to be adjacent. Instead,
+/// they are a fixed number of bytes apart, called the `step`. This allows views over a single channel in
+/// an interleaved buffer, or over a slice back to front.
+///
+/// Unerasing gives you a [`StridedRef`], because a regular `&[T]` can't represent gaps between elements.
+///
+/// ```
+/// let data : [i32; 6] = [0, 1, 2, 3, 4, 5];
+/// let any = sashay::AnySliceRef::erase(data.as_slice());
+///
+/// // Take a view of every third element, starting at the second
+/// let strided = any.interleaved_channel(1, 3).unwrap();
+///
+/// assert_eq!(strided.len(), 2);
+/// assert_eq!(strided.step(), 3 * std::mem::size_of::<i32>() as isize);
+/// assert_eq!(strided.unerase::<i32>().unwrap(), [1, 4]);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AnyStridedRef<'a> {
+    /// A raw pointer to the first element
+    ///
+    /// Note: this pointer must be aligned and point to valid values of `T` at
+    /// subsequent positions along the step
+    ptr: *const u8,
+
+    /// The number of elements in the view
+    len: usize,
+
+    /// The distance in bytes from one element to the next
+    ///
+    /// This is a multiple of `stride`, such that ptr + N * step points to subsequent elements
+    step: isize,
+
+    /// The `size_of()` of the individual elements
+    stride: usize,
+
+    /// A unique id representing the type of the referenced elements
+    ///
+    /// This is used to ensure we can safely unerase back without accidentally transmuting
+    type_id: TypeId,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> AnyStridedRef<'a> {
+    /// Erase the type of a strided view's elements.
+    pub fn erase<T: 'static>(view: StridedRef<'a, T>) -> AnyStridedRef<'a> {
+        // SAFETY: The raw parts come from a valid view, and the TypeId and stride are provided by the compiler
+        unsafe {
+            Self::from_raw_parts(
+                view.as_ptr().cast::<()>(),
+                view.len(),
+                view.step(),
+                size_of::<T>(),
+                TypeId::of::<T>(),
+            )
+        }
+    }
+
+    /// Construct an erased strided view from its raw parts.
+    ///
+    /// The parameters are the same as for [`AnySliceRef::from_raw_parts()`], with the addition
+    /// of `step`: the (possibly negative) distance in bytes from one element to the next.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if:
+    ///  - For every index below `len`, `ptr` offset by `index * step` bytes points to a valid and aligned `T`
+    ///  - These elements aren't mutated for the duration of `'a`
+    ///  - `stride` is the correct [`size_of()`](https://doc.rust-lang.org/std/mem/fn.size_of.html) for the element type `T`
+    ///  - `type_id` is the correct [`TypeId`](https://doc.rust-lang.org/stable/std/any/struct.TypeId.html) for the element type `T`
+    pub const unsafe fn from_raw_parts(
+        ptr: *const (),
+        len: usize,
+        step: isize,
+        stride: usize,
+        type_id: TypeId,
+    ) -> Self {
+        Self {
+            ptr: ptr.cast::<u8>(),
+            len,
+            step,
+            stride,
+            type_id,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Unerase back to an immutable strided view.
+    ///
+    /// If the original element type was `T`, a valid view is returned. Otherwise, you get `None`.
+    pub fn unerase<T: 'static>(&self) -> Option<StridedRef<'_, T>> {
+        self.unerase_into()
+    }
+
+    /// Unerase back into an immutable strided view, transferring ownership into it.
+    pub fn unerase_into<T: 'static>(self) -> Option<StridedRef<'a, T>> {
+        self.contains::<T>().then(|| {
+            // SAFETY:
+            // - We've checked the TypeId of T against the one created at construction, so we're not
+            //   accidentally transmuting to a different type
+            // - The pointer and step describe valid elements as per construction
+            unsafe { StridedRef::from_raw_parts(self.ptr.cast::<T>(), self.len, self.step) }
+        })
+    }
+
+    /// Retrieve an immutable reference to one of the elements in the view.
+    ///
+    /// ```
+    /// let data : [i32; 4] = [0, 1, 2, 3];
+    /// let any = sashay::AnySliceRef::erase(data.as_slice());
+    /// let strided = any.interleaved_channel(1, 2).unwrap();
+    ///
+    /// assert_eq!(strided.get(1).unwrap().unerase_into::<i32>(), Some(&3));
+    /// assert!(strided.get(2).is_none());
+    /// ```
+    pub fn get(&self, index: usize) -> Option<AnyRef<'_>> {
+        // SAFETY: The index is within bounds, so the element is valid as per construction
+        (index < self.len)
+            .then(|| unsafe { AnyRef::from_raw_parts(self.element(index), self.type_id) })
+    }
+
+    /// Iterate over references to all elements in the view, in order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = AnyRef<'_>> + ExactSizeIterator {
+        // SAFETY: The index is within bounds, so the element is valid as per construction
+        (0..self.len)
+            .map(|index| unsafe { AnyRef::from_raw_parts(self.element(index), self.type_id) })
+    }
+
+    /// Access a smaller view within a given range.
+    ///
+    /// This works the same as [`AnySliceRef::subslice()`], and the resulting view has the same step.
+    pub fn subslice<R>(&self, range: R) -> AnyStridedRef<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.subslice_into(range)
+    }
+
+    /// Access a smaller view within a given range, transferring ownership into it.
+    pub fn subslice_into<R>(self, range: R) -> AnyStridedRef<'a>
+    where
+        R: RangeBounds<usize>,
+    {
+        let range = constrain_range(self.len, range);
+
+        // SAFETY: `constrain_range()` ensures that the new elements are a subset of the current ones
+        unsafe {
+            Self::from_raw_parts(
+                self.element(range.start),
+                range.len(),
+                self.step,
+                self.stride,
+                self.type_id,
+            )
+        }
+    }
+
+    /// Iterate over consecutive smaller views of `chunk_size` elements each.
+    ///
+    /// The last chunk is shorter if the length isn't a multiple of `chunk_size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    ///
+    /// ```
+    /// let data : [i32; 5] = [0, 1, 2, 3, 4];
+    /// let any = sashay::AnySliceRef::erase(data.as_slice());
+    /// let reversed = any.rev_view();
+    ///
+    /// let mut chunks = reversed.chunks(2);
+    /// assert_eq!(chunks.next().unwrap().unerase::<i32>().unwrap(), [4, 3]);
+    /// assert_eq!(chunks.next().unwrap().unerase::<i32>().unwrap(), [2, 1]);
+    /// assert_eq!(chunks.next().unwrap().unerase::<i32>().unwrap(), [0]);
+    /// assert!(chunks.next().is_none());
+    /// ```
+    pub fn chunks(
+        &self,
+        chunk_size: usize,
+    ) -> impl DoubleEndedIterator<Item = AnyStridedRef<'_>> + ExactSizeIterator {
+        chunk_ranges(self.len, chunk_size).map(|range| self.subslice(range))
+    }
+
+    /// View the elements as a regular erased slice, if they are adjacent in memory.
+    ///
+    /// ```
+    /// let data : [i32; 4] = [0, 1, 2, 3];
+    /// let any = sashay::AnySliceRef::erase(data.as_slice());
+    ///
+    /// assert!(any.interleaved_channel(0, 1).unwrap().as_slice().is_some());
+    /// assert!(any.interleaved_channel(0, 2).unwrap().as_slice().is_none());
+    /// ```
+    pub fn as_slice(&self) -> Option<AnySliceRef<'_>> {
+        // SAFETY: The elements are adjacent, so they form a valid slice
+        self.is_contiguous().then(|| unsafe {
+            AnySliceRef::from_raw_parts(self.as_ptr(), self.len, self.stride, self.type_id)
+        })
+    }
+
+    /// Are the elements adjacent in memory and in increasing order?
+    pub const fn is_contiguous(&self) -> bool {
+        self.len <= 1 || self.step == self.stride as isize
+    }
+
+    /// Retrieve an unsafe pointer to the first element.
+    pub const fn as_ptr(&self) -> *const () {
+        self.ptr.cast::<()>()
+    }
+
+    /// How many elements does the view contain?
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Does the view contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Was the original element of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        TypeId::of::<T>() == self.type_id
+    }
+
+    /// The distance in bytes from one element to the next.
+    pub const fn step(&self) -> isize {
+        self.step
+    }
+
+    /// The `size_of()` of the original elements of type `T`.
+    pub const fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// A unique type id representing the original element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        &self.type_id
+    }
+
+    /// A pointer to the element at `index`, which doesn't need to be in bounds
+    fn element(&self, index: usize) -> *const () {
+        self.ptr
+            .wrapping_offset(index as isize * self.step)
+            .cast::<()>()
+    }
+}
+
+impl<'a> From<AnySliceRef<'a>> for AnyStridedRef<'a> {
+    fn from(slice: AnySliceRef<'a>) -> Self {
+        // SAFETY: Slice elements are exactly `stride` bytes apart
+        unsafe {
+            Self::from_raw_parts(
+                slice.as_ptr(),
+                slice.len(),
+                slice.stride() as isize,
+                slice.stride(),
+                *slice.type_id(),
+            )
+        }
+    }
+}
+
+impl<'a, T: 'static> From<StridedRef<'a, T>> for AnyStridedRef<'a> {
+    fn from(view: StridedRef<'a, T>) -> Self {
+        Self::erase(view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // All these tests use an (u8, u16) because it has padding
+
+    #[test]
+    fn erase_unerase() {
+        let data = [(0u8, 1u16), (2u8, 3u16), (4u8, 5u16), (6u8, 7u16)];
+        let any = AnySliceRef::erase(data.as_slice());
+
+        let strided = any.interleaved_channel(1, 2).unwrap();
+        assert_eq!(strided.len(), 2);
+        assert_eq!(strided.stride(), size_of::<(u8, u16)>());
+        assert_eq!(strided.step(), 2 * size_of::<(u8, u16)>() as isize);
+        assert!(!strided.is_contiguous());
+        assert!(any.interleaved_channel(2, 2).is_none());
+        assert!(any.interleaved_channel(0, usize::MAX / 2).is_none()); // frames overflow
+
+        assert_eq!(strided.unerase::<u8>(), None);
+        assert_eq!(
+            strided.unerase::<(u8, u16)>().unwrap(),
+            [(2u8, 3u16), (6u8, 7u16)]
+        );
+
+        let erased = AnyStridedRef::erase(strided.unerase_into::<(u8, u16)>().unwrap());
+        assert_eq!(
+            erased.get(1).unwrap().unerase::<(u8, u16)>(),
+            Some(&(6u8, 7u16))
+        );
+    }
+
+    #[test]
+    fn sub() {
+        let data = [
+            (0u8, 1u16),
+            (2u8, 3u16),
+            (4u8, 5u16),
+            (6u8, 7u16),
+            (8u8, 9u16),
+        ];
+        let any = AnyStridedRef::from(AnySliceRef::erase(data.as_slice()));
+        assert_eq!(any.as_slice().unwrap().unerase(), Some(data.as_slice()));
+
+        let sub = any.subslice(1..4);
+        assert_eq!(
+            sub.as_slice().unwrap().unerase::<(u8, u16)>(),
+            Some(&data[1..4])
+        );
+
+        let slice = AnySliceRef::erase(data.as_slice());
+        let every_other = slice.interleaved_channel(0, 2).unwrap().subslice_into(1..);
+        // The last element is in an incomplete frame, so it's not part of the channel
+        assert_eq!(every_other.len(), 1);
+        assert_eq!(
+            every_other.iter().next().unwrap().unerase::<(u8, u16)>(),
+            Some(&(4u8, 5u16))
+        );
+    }
+
+    #[test]
+    fn reversed() {
+        let data = [(0u8, 1u16), (2u8, 3u16), (4u8, 5u16)];
+        let any = AnySliceRef::erase(data.as_slice());
+
+        let reversed = any.rev_view();
+        assert_eq!(reversed.len(), 3);
+        assert!(!reversed.is_contiguous());
+        assert!(reversed
+            .iter()
+            .map(|element| element.unerase::<(u8, u16)>().unwrap().0)
+            .eq([4, 2, 0]));
+        assert_eq!(
+            reversed
+                .subslice(2..)
+                .as_slice()
+                .unwrap()
+                .unerase::<(u8, u16)>(),
+            Some(&data[..1])
+        );
+
+        let typed = reversed.unerase::<(u8, u16)>().unwrap();
+        assert_eq!(typed, [(4u8, 5u16), (2u8, 3u16), (0u8, 1u16)]);
+        assert!(typed.chunks(2).map(|chunk| chunk.len()).eq([2, 1]));
+
+        let empty = AnySliceRef::erase(&data[..0]);
+        assert!(empty.rev_view().is_empty());
+        assert_eq!(empty.rev_view().chunks(2).len(), 0);
+    }
+}
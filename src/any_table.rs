@@ -0,0 +1,598 @@
+use crate::{AnyMut, AnyRef, AnySliceMut, AnySliceRef, AnyVec, TypeInfo};
+use alloc::vec::Vec;
+use core::{any::TypeId, fmt};
+
+/// A table of same-length, type-erased columns, with one column per type.
+///
+/// This is the storage at the heart of an archetype-based entity-component system: every row is an
+/// entity, and every column holds one of its components in an [`AnyVec`]. Rows are added and removed
+/// with erased values, and can be moved to tables with different column sets when components are
+/// added or removed.
+///
+/// ```
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Position(f32);
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Velocity(f32);
+///
+/// let mut table = sashay::AnyTable::new(&[
+///     sashay::TypeInfo::of_copy::<Position>(),
+///     sashay::TypeInfo::of_copy::<Velocity>(),
+/// ]).unwrap();
+///
+/// // Values are matched to columns by type, so their order doesn't matter
+/// table.push_row(&[sashay::AnyRef::erase(&Velocity(1.0)), sashay::AnyRef::erase(&Position(0.0))]).unwrap();
+/// table.push_row(&[sashay::AnyRef::erase(&Position(5.0)), sashay::AnyRef::erase(&Velocity(-1.0))]).unwrap();
+///
+/// for (position, velocity) in table.column_mut::<Position>().unwrap().iter_mut().zip([1.0, -1.0]) {
+///     position.0 += velocity;
+/// }
+///
+/// assert_eq!(table.column::<Position>(), Some([Position(1.0), Position(4.0)].as_slice()));
+/// ```
+#[derive(Debug)]
+pub struct AnyTable {
+    /// The columns, sorted by type id
+    columns: Vec<AnyVec>,
+
+    /// The number of rows
+    len: usize,
+}
+
+impl AnyTable {
+    /// Construct an empty table with a column for each described type.
+    ///
+    /// Every type can only have a single column.
+    pub fn new(columns: &[TypeInfo]) -> Result<Self, TableError> {
+        let mut table = Self {
+            columns: Vec::with_capacity(columns.len()),
+            len: 0,
+        };
+
+        for info in columns {
+            match table.position(info.type_id()) {
+                Ok(_) => return Err(TableError::DuplicateColumn(*info.type_id())),
+                Err(index) => table.columns.insert(index, AnyVec::with_type_info(*info)),
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Append a row by copying a value for every column.
+    ///
+    /// Values are matched to columns by their type, so they can be passed in any order. Copying is
+    /// only allowed for columns whose element type is known to be `Copy` (see [`TypeInfo::is_copy()`]).
+    ///
+    /// Returns the index of the new row.
+    pub fn push_row(&mut self, values: &[AnyRef<'_>]) -> Result<usize, TableError> {
+        validate(self.columns.iter(), values, true)?;
+
+        // SAFETY: Every column has a matching value, which is Copy
+        Ok(unsafe { self.push_row_unchecked(values) })
+    }
+
+    /// Append a row by moving a value for every column.
+    ///
+    /// Values are matched to columns by their type, so they can be passed in any order. Unlike
+    /// [`AnyTable::push_row()`], this works for element types that aren't `Copy`.
+    ///
+    /// Returns the index of the new row.
+    ///
+    /// # Safety
+    ///
+    /// If this succeeds, the values have been moved into the table, so the caller must make sure the
+    /// originals are never used or dropped again (for example by wrapping them in `ManuallyDrop`).
+    pub unsafe fn push_row_moved(&mut self, values: &[AnyRef<'_>]) -> Result<usize, TableError> {
+        validate(self.columns.iter(), values, false)?;
+
+        // SAFETY: Every column has a matching value, and the caller guarantees it may be moved
+        Ok(unsafe { self.push_row_unchecked(values) })
+    }
+
+    /// Remove a row and drop its values, replacing it with the last row.
+    ///
+    /// Returns whether the row was in bounds.
+    pub fn swap_remove_row(&mut self, row: usize) -> bool {
+        if row >= self.len {
+            return false;
+        }
+
+        // Remove the row from every column before dropping anything, so the columns stay the same
+        // length if dropping a value panics
+        self.len -= 1;
+        for column in &mut self.columns {
+            column.swap_remove_staged(row);
+        }
+
+        // SAFETY: Every column just staged its removed value right past its end
+        unsafe { drop_removed(self.columns.iter().map(|column| (column, self.len))) };
+
+        true
+    }
+
+    /// Remove a row and drop its values, shifting all rows after it to the front.
+    ///
+    /// Returns whether the row was in bounds.
+    pub fn remove_row(&mut self, row: usize) -> bool {
+        if row >= self.len {
+            return false;
+        }
+
+        // Remove the row from every column before dropping anything, so the columns stay the same
+        // length if dropping a value panics
+        self.len -= 1;
+        for column in &mut self.columns {
+            column.remove_staged(row);
+        }
+
+        // SAFETY: Every column just staged its removed value right past its end
+        unsafe { drop_removed(self.columns.iter().map(|column| (column, self.len))) };
+
+        true
+    }
+
+    /// Move a row to another table, replacing it with the last row.
+    ///
+    /// Values in columns that both tables share are moved. Values in columns the destination lacks are
+    /// dropped. Columns that only the destination has need to be provided with values in `extra`, which
+    /// are copied and thus need to be known to be `Copy`.
+    ///
+    /// Returns the index of the row in the destination table.
+    ///
+    /// ```
+    /// let mut small = sashay::AnyTable::new(&[sashay::TypeInfo::of::<String>()]).unwrap();
+    /// let mut large = sashay::AnyTable::new(&[
+    ///     sashay::TypeInfo::of::<String>(),
+    ///     sashay::TypeInfo::of_copy::<u32>(),
+    /// ]).unwrap();
+    ///
+    /// let name = std::mem::ManuallyDrop::new(String::from("player"));
+    /// unsafe { small.push_row_moved(&[sashay::AnyRef::erase(&*name)]).unwrap() };
+    ///
+    /// let row = small.move_row(0, &mut large, &[sashay::AnyRef::erase(&100u32)]).unwrap();
+    /// assert!(small.is_empty());
+    /// assert_eq!(large.column::<String>().unwrap()[row], "player");
+    /// assert_eq!(large.column::<u32>().unwrap()[row], 100);
+    /// ```
+    pub fn move_row(
+        &mut self,
+        row: usize,
+        destination: &mut AnyTable,
+        extra: &[AnyRef<'_>],
+    ) -> Result<usize, TableError> {
+        if row >= self.len || self.columns.iter().any(|column| row >= column.len()) {
+            return Err(TableError::OutOfBounds {
+                index: row,
+                len: self.len,
+            });
+        }
+
+        let missing = destination
+            .columns
+            .iter()
+            .filter(|column| !self.has_column(column.type_id()));
+        validate(missing, extra, true)?;
+
+        // Make room up front, so no value is moved unless all of them are
+        destination.reserve_row();
+        for column in &mut destination.columns {
+            let value = match self.position(column.type_id()) {
+                Ok(index) => self.columns[index].element(row),
+                Err(_) => find(extra, column.type_id())
+                    .expect("extra values were validated")
+                    .as_ptr()
+                    .cast::<u8>(),
+            };
+
+            // SAFETY: The value has the column type, and is either moved out of this table or Copy
+            unsafe { column.push_raw(value) };
+        }
+
+        // Remove the row from every column before dropping anything, so the columns stay the same
+        // length if dropping a value panics
+        for column in &mut self.columns {
+            if destination.has_column(column.type_id()) {
+                // SAFETY: The row is in bounds, and its value was moved to the destination
+                unsafe { column.swap_remove_forget(row) };
+            } else {
+                column.swap_remove_staged(row);
+            }
+        }
+
+        self.len -= 1;
+        destination.len += 1;
+
+        let dropped = self
+            .columns
+            .iter()
+            .filter(|column| !destination.has_column(column.type_id()))
+            .map(|column| (column, self.len));
+
+        // SAFETY: Every column the destination lacks just staged its removed value right past its end
+        unsafe { drop_removed(dropped) };
+
+        Ok(destination.len - 1)
+    }
+
+    /// Remove all rows, dropping their values.
+    pub fn clear(&mut self) {
+        // Empty every column before dropping anything, so the columns stay the same length if dropping
+        // a value panics
+        let len = self.len;
+        self.len = 0;
+        for column in &mut self.columns {
+            column.truncate_staged(0);
+        }
+
+        let dropped = self
+            .columns
+            .iter()
+            .flat_map(|column| (0..len).map(move |row| (column, row)));
+
+        // SAFETY: Every column just staged all of its values
+        unsafe { drop_removed(dropped) };
+    }
+
+    /// Access the column of type `T` as a typed slice.
+    ///
+    /// If there is no such column, you get `None`.
+    pub fn column<T: 'static>(&self) -> Option<&[T]> {
+        self.column_erased(&TypeId::of::<T>())?.unerase_into()
+    }
+
+    /// Access the column of type `T` as a mutable typed slice.
+    ///
+    /// If there is no such column, you get `None`.
+    pub fn column_mut<T: 'static>(&mut self) -> Option<&mut [T]> {
+        self.column_erased_mut(&TypeId::of::<T>())?.unerase_into()
+    }
+
+    /// Access the column of a type as an erased slice.
+    ///
+    /// If there is no such column, you get `None`.
+    pub fn column_erased(&self, type_id: &TypeId) -> Option<AnySliceRef<'_>> {
+        let index = self.position(type_id).ok()?;
+        Some(self.columns[index].as_slice())
+    }
+
+    /// Access the column of a type as a mutable erased slice.
+    ///
+    /// If there is no such column, you get `None`.
+    pub fn column_erased_mut(&mut self, type_id: &TypeId) -> Option<AnySliceMut<'_>> {
+        let index = self.position(type_id).ok()?;
+        Some(self.columns[index].as_mut_slice())
+    }
+
+    /// Retrieve an immutable reference to the value of a type in a row.
+    pub fn get(&self, row: usize, type_id: &TypeId) -> Option<AnyRef<'_>> {
+        let index = self.position(type_id).ok()?;
+        self.columns[index].get(row)
+    }
+
+    /// Retrieve a mutable reference to the value of a type in a row.
+    pub fn get_mut(&mut self, row: usize, type_id: &TypeId) -> Option<AnyMut<'_>> {
+        let index = self.position(type_id).ok()?;
+        self.columns[index].get_mut(row)
+    }
+
+    /// Does the table have a column of a type?
+    pub fn has_column(&self, type_id: &TypeId) -> bool {
+        self.position(type_id).is_ok()
+    }
+
+    /// Iterate over the type ids of all columns, in sorted order.
+    pub fn type_ids(&self) -> impl ExactSizeIterator<Item = &TypeId> {
+        self.columns.iter().map(AnyVec::type_id)
+    }
+
+    /// How many columns does the table have?
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// How many rows does the table contain?
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Does the table contain any rows at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Find the index of the column of a type, or where it would be inserted
+    fn position(&self, type_id: &TypeId) -> Result<usize, usize> {
+        self.columns
+            .binary_search_by(|column| column.type_id().cmp(type_id))
+    }
+
+    /// Make sure every column fits one more row without reallocating
+    fn reserve_row(&mut self) {
+        for column in &mut self.columns {
+            column.reserve(1);
+        }
+    }
+
+    /// Append a row by copying the bytes of a value for every column
+    ///
+    /// # Safety
+    ///
+    /// The values must have been validated against the columns, and must either be `Copy` or never be
+    /// used or dropped again.
+    unsafe fn push_row_unchecked(&mut self, values: &[AnyRef<'_>]) -> usize {
+        // Make room up front, so no value is moved unless all of them are
+        self.reserve_row();
+        for column in &mut self.columns {
+            let value = find(values, column.type_id()).expect("values were validated");
+
+            // SAFETY: The value has the column type, and the caller guarantees it may be copied or moved
+            unsafe { column.push_raw(value.as_ptr().cast::<u8>()) };
+        }
+
+        self.len += 1;
+        self.len - 1
+    }
+}
+
+/// Drop values that were removed from their columns, but are still in their storage
+///
+/// If dropping a value panics, the rest are still dropped while unwinding.
+///
+/// # Safety
+///
+/// Every value must be initialized and no longer be part of its column, and must only be yielded once.
+unsafe fn drop_removed<'a>(mut values: impl Iterator<Item = (&'a AnyVec, usize)>) {
+    /// Drops the remaining values, and drops the rest when unwinding if one of them panics
+    struct Guard<'a, 'b>(&'b mut dyn Iterator<Item = (&'a AnyVec, usize)>);
+
+    impl Guard<'_, '_> {
+        fn drop_rest(&mut self) {
+            // The iterator advances before every drop, so a value that panicked isn't dropped again
+            for (column, index) in &mut *self.0 {
+                // SAFETY: The caller guarantees the value is initialized and removed
+                unsafe {
+                    column
+                        .type_info()
+                        .drop_in_place(column.element(index).cast::<()>())
+                };
+            }
+        }
+    }
+
+    impl Drop for Guard<'_, '_> {
+        fn drop(&mut self) {
+            self.drop_rest();
+        }
+    }
+
+    Guard(&mut values).drop_rest();
+}
+
+/// Check that there is exactly one value for each of the columns, and that they can be copied if needed
+fn validate<'a>(
+    columns: impl Iterator<Item = &'a AnyVec>,
+    values: &[AnyRef<'_>],
+    copy: bool,
+) -> Result<(), TableError> {
+    let mut count = 0;
+
+    for column in columns {
+        count += 1;
+
+        let type_id = *column.type_id();
+        if find(values, &type_id).is_none() {
+            return Err(TableError::MissingValue(type_id));
+        } else if copy && !column.type_info().is_copy() {
+            return Err(TableError::NotCopy(type_id));
+        }
+    }
+
+    // Every column type is unique, so equal counts means every value was matched exactly once
+    if values.len() != count {
+        return Err(TableError::LengthMismatch {
+            expected: count,
+            found: values.len(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Find the value of a type
+fn find<'a, 'b>(values: &'a [AnyRef<'b>], type_id: &TypeId) -> Option<&'a AnyRef<'b>> {
+    values.iter().find(|value| value.type_id() == type_id)
+}
+
+/// The reasons table operations can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableError {
+    /// A table can have only a single column per type
+    DuplicateColumn(TypeId),
+
+    /// No value was provided for the column of this type
+    MissingValue(TypeId),
+
+    /// The number of values doesn't match the number of columns
+    LengthMismatch { expected: usize, found: usize },
+
+    /// The column type is not known to be `Copy`, so its value can't be copied from an erased reference
+    NotCopy(TypeId),
+
+    /// The row is out of bounds
+    OutOfBounds { index: usize, len: usize },
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateColumn(type_id) => write!(f, "duplicate column of {type_id:?}"),
+            Self::MissingValue(type_id) => write!(f, "missing a value of {type_id:?}"),
+            Self::LengthMismatch { expected, found } => {
+                write!(f, "expected {expected} values, but got {found}")
+            }
+            Self::NotCopy(type_id) => write!(f, "{type_id:?} is not known to be `Copy`"),
+            Self::OutOfBounds { index, len } => {
+                write!(f, "row {index} is out of bounds for a table of {len} rows")
+            }
+        }
+    }
+}
+
+impl core::error::Error for TableError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{DropCounter, Tracked};
+    use core::mem::ManuallyDrop;
+
+    #[test]
+    fn rows() {
+        let mut table =
+            AnyTable::new(&[TypeInfo::of_copy::<(u8, u16)>(), TypeInfo::of_copy::<u32>()]).unwrap();
+
+        for value in 0..4u8 {
+            let row = table
+                .push_row(&[
+                    AnyRef::erase(&u32::from(value)),
+                    AnyRef::erase(&(value, 0u16)),
+                ])
+                .unwrap();
+            assert_eq!(row, usize::from(value));
+        }
+
+        assert_eq!(
+            table.push_row(&[AnyRef::erase(&0u32)]),
+            Err(TableError::MissingValue(TypeId::of::<(u8, u16)>()))
+        );
+        assert_eq!(
+            table.push_row(&[
+                AnyRef::erase(&0u32),
+                AnyRef::erase(&(0u8, 0u16)),
+                AnyRef::erase(&0u32)
+            ]),
+            Err(TableError::LengthMismatch {
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            AnyTable::new(&[TypeInfo::of::<u32>(), TypeInfo::of::<u32>()]).unwrap_err(),
+            TableError::DuplicateColumn(TypeId::of::<u32>())
+        );
+
+        assert!(table.swap_remove_row(0));
+        assert!(table.remove_row(1));
+        assert!(!table.remove_row(2));
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.column::<u32>(), Some([3u32, 2].as_slice()));
+        assert_eq!(
+            table
+                .column_erased(&TypeId::of::<(u8, u16)>())
+                .unwrap()
+                .unerase::<(u8, u16)>(),
+            Some([(3u8, 0u16), (2u8, 0u16)].as_slice())
+        );
+        assert_eq!(table.column::<u8>(), None);
+    }
+
+    #[test]
+    fn move_row() {
+        static DROPS: DropCounter = DropCounter::new();
+
+        let mut source =
+            AnyTable::new(&[TypeInfo::of::<Tracked>(), TypeInfo::of_copy::<(u8, u16)>()]).unwrap();
+        let mut destination =
+            AnyTable::new(&[TypeInfo::of_copy::<u32>(), TypeInfo::of::<(u8, u16)>()]).unwrap();
+
+        for value in 0..2u8 {
+            let tracked = ManuallyDrop::new(DROPS.track(()));
+            unsafe {
+                source
+                    .push_row_moved(&[AnyRef::erase(&*tracked), AnyRef::erase(&(value, 0u16))])
+                    .unwrap()
+            };
+        }
+        assert_eq!(
+            source.push_row(&[AnyRef::erase(&DROPS.track(())), AnyRef::erase(&(0u8, 0u16))]),
+            Err(TableError::NotCopy(TypeId::of::<Tracked>()))
+        );
+        assert_eq!(DROPS.drops(), 1);
+
+        assert_eq!(
+            source.move_row(0, &mut destination, &[]),
+            Err(TableError::MissingValue(TypeId::of::<u32>()))
+        );
+        assert_eq!(
+            source.move_row(0, &mut destination, &[AnyRef::erase(&7u32)]),
+            Ok(0)
+        );
+
+        // The tracked value was dropped, because the destination has no column for it
+        assert_eq!(DROPS.drops(), 2);
+        assert_eq!(source.len(), 1);
+        assert_eq!(source.column::<(u8, u16)>(), Some([(1u8, 0u16)].as_slice()));
+        assert_eq!(
+            destination.column::<(u8, u16)>(),
+            Some([(0u8, 0u16)].as_slice())
+        );
+        assert_eq!(destination.column::<u32>(), Some([7u32].as_slice()));
+
+        drop(source);
+        assert_eq!(DROPS.drops(), 3);
+    }
+
+    #[test]
+    fn panicking_drop() {
+        extern crate std;
+
+        struct Bomb(bool);
+
+        impl Drop for Bomb {
+            fn drop(&mut self) {
+                assert!(!self.0, "boom");
+            }
+        }
+
+        static DROPS: DropCounter = DropCounter::new();
+
+        let mut table =
+            AnyTable::new(&[TypeInfo::of::<Bomb>(), TypeInfo::of::<Tracked>()]).unwrap();
+        let push = |table: &mut AnyTable, armed| {
+            let bomb = ManuallyDrop::new(Bomb(armed));
+            let tracked = ManuallyDrop::new(DROPS.track(()));
+            unsafe {
+                table
+                    .push_row_moved(&[AnyRef::erase(&*bomb), AnyRef::erase(&*tracked)])
+                    .unwrap()
+            };
+        };
+        push(&mut table, true);
+        push(&mut table, false);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            table.swap_remove_row(0);
+        }));
+        assert!(result.is_err());
+
+        // The row is gone from every column, and its other value was still dropped
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.column::<Bomb>().unwrap().len(), 1);
+        assert_eq!(table.column::<Tracked>().unwrap().len(), 1);
+        assert!(!table.column::<Bomb>().unwrap()[0].0);
+        assert_eq!(DROPS.drops(), 1);
+
+        push(&mut table, true);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| table.clear()));
+        assert!(result.is_err());
+
+        assert!(table.is_empty());
+        assert!(table
+            .type_ids()
+            .all(|type_id| table.get(0, type_id).is_none()));
+        assert_eq!(table.column::<Tracked>().unwrap().len(), 0);
+        assert_eq!(DROPS.drops(), 3);
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{tensor::Layout, AnyMut, AnyRef, AnySliceMut, AnySliceRef, AnyTensorRef};
+use core::{any::TypeId, marker::PhantomData, ops::RangeBounds};
+
+/// A type-erased mutable n-dimensional view.
+///
+/// A tensor of rank `D` has a shape and a byte stride per axis. See [`AnyTensorRef`] for more information.
+///
+/// Because distinct indices must refer to distinct elements, mutable tensors can't be broadcast.
+/// To broadcast the elements immutably, [`borrow()`](AnyTensorMut::borrow) first.
+///
+/// ```
+/// let mut data : [i32; 6] = [0, 1, 2, 3, 4, 5];
+/// let slice = sashay::AnySliceMut::erase(data.as_mut_slice());
+/// let matrix = sashay::AnyTensorMut::new(slice, [2, 3]).unwrap();
+///
+/// // Write through the transpose
+/// let mut transposed = matrix.transpose();
+/// *transposed.get_mut([2, 0]).unwrap().unerase_mut::<i32>().unwrap() = 9;
+///
+/// assert_eq!(data, [0, 1, 9, 3, 4, 5]);
+/// ```
+#[derive(Debug)]
+pub struct AnyTensorMut<'a, const D: usize> {
+    /// A raw pointer to the element at index `[0; D]`
+    ///
+    /// Note: this pointer must be aligned, and offsetting it along the strides must point to distinct
+    /// valid values of `T` for every index within the shape
+    ptr: *mut u8,
+
+    /// The shape and per-axis strides
+    layout: Layout<D>,
+
+    /// The `size_of()` of the individual elements
+    stride: usize,
+
+    /// A unique id representing the type of the referenced elements
+    ///
+    /// This is used to ensure we can safely unerase back without accidentally transmuting
+    type_id: TypeId,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a mut ()>,
+}
+
+impl<'a, const D: usize> AnyTensorMut<'a, D> {
+    /// View a mutable erased slice as a row-major tensor of a given shape.
+    ///
+    /// The number of elements in the shape must be equal to the length of the slice, otherwise you get `None`.
+    pub fn new(mut slice: AnySliceMut<'a>, shape: [usize; D]) -> Option<Self> {
+        let layout = Layout::contiguous(shape, slice.stride())?;
+
+        // SAFETY: A contiguous layout with as many elements as the slice covers exactly the slice
+        (layout.len() == slice.len()).then(|| unsafe {
+            Self::from_raw_parts(
+                slice.as_mut_ptr(),
+                layout.shape,
+                layout.strides,
+                slice.stride(),
+                *slice.type_id(),
+            )
+        })
+    }
+
+    /// Construct a mutable erased tensor from its raw parts.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if:
+    ///  - For every index within `shape`, `ptr` offset by the sum of `index[axis] * strides[axis]` bytes points to a valid and aligned `T`
+    ///  - Distinct indices point to distinct, non-overlapping elements
+    ///  - These elements aren't accessed through any other pointer for the duration of `'a`
+    ///  - `stride` is the correct [`size_of()`](https://doc.rust-lang.org/std/mem/fn.size_of.html) for the element type `T`
+    ///  - `type_id` is the correct [`TypeId`](https://doc.rust-lang.org/stable/std/any/struct.TypeId.html) for the element type `T`
+    pub unsafe fn from_raw_parts(
+        ptr: *mut (),
+        shape: [usize; D],
+        strides: [isize; D],
+        stride: usize,
+        type_id: TypeId,
+    ) -> Self {
+        Self {
+            ptr: ptr.cast::<u8>(),
+            layout: Layout { shape, strides },
+            stride,
+            type_id,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Borrow this mutable tensor as an immutable one.
+    pub fn borrow(&self) -> AnyTensorRef<'_, D> {
+        // SAFETY: All parts are valid, and we have an immutable reference to self
+        unsafe {
+            AnyTensorRef::from_raw_parts(
+                self.as_ptr(),
+                self.layout.shape,
+                self.layout.strides,
+                self.stride,
+                self.type_id,
+            )
+        }
+    }
+
+    /// Reborrow this mutable tensor with a shorter lifetime.
+    pub fn borrow_mut(&mut self) -> AnyTensorMut<'_, D> {
+        // SAFETY: All parts are valid, and we have a mutable reference to self
+        unsafe {
+            AnyTensorMut::from_raw_parts(
+                self.as_mut_ptr(),
+                self.layout.shape,
+                self.layout.strides,
+                self.stride,
+                self.type_id,
+            )
+        }
+    }
+
+    /// Retrieve an immutable reference to the element at an index.
+    pub fn get(&self, index: [usize; D]) -> Option<AnyRef<'_>> {
+        let offset = self.layout.offset(index)?;
+
+        // SAFETY: The index is within bounds, so the element is valid as per construction
+        let reference = unsafe {
+            AnyRef::from_raw_parts(
+                self.ptr.wrapping_offset(offset).cast_const().cast::<()>(),
+                self.type_id,
+            )
+        };
+
+        Some(reference)
+    }
+
+    /// Retrieve a mutable reference to the element at an index.
+    ///
+    /// If the index is out of bounds along any axis, you get `None`.
+    pub fn get_mut(&mut self, index: [usize; D]) -> Option<AnyMut<'_>> {
+        let offset = self.layout.offset(index)?;
+
+        // SAFETY: The index is within bounds, so the element is valid as per construction
+        let reference = unsafe {
+            AnyMut::from_raw_parts(self.ptr.wrapping_offset(offset).cast::<()>(), self.type_id)
+        };
+
+        Some(reference)
+    }
+
+    /// Restrict one of the axes to a range of indices.
+    ///
+    /// The range is clamped to the length of the axis. If the axis doesn't exist, you get `None`.
+    pub fn slice_axis<R>(self, axis: usize, range: R) -> Option<Self>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (layout, offset) = self.layout.slice_axis(axis, range)?;
+
+        Some(Self {
+            ptr: self.ptr.wrapping_offset(offset),
+            layout,
+            ..self
+        })
+    }
+
+    /// Reverse the order of the axes.
+    pub fn transpose(self) -> Self {
+        Self {
+            layout: self.layout.transpose(),
+            ..self
+        }
+    }
+
+    /// Reorder the axes, so that axis `i` of the result is axis `axes[i]` of this tensor.
+    ///
+    /// If `axes` doesn't contain every axis exactly once, you get `None`.
+    pub fn permute_axes(self, axes: [usize; D]) -> Option<Self> {
+        Some(Self {
+            layout: self.layout.permute(axes)?,
+            ..self
+        })
+    }
+
+    /// View the same elements with a different shape.
+    ///
+    /// This is only possible if the tensor is contiguous, and the new shape has the same number of
+    /// elements. Otherwise, you get `None`.
+    pub fn reshape<const E: usize>(mut self, shape: [usize; E]) -> Option<AnyTensorMut<'a, E>> {
+        let layout = Layout::contiguous(shape, self.stride)?;
+
+        // SAFETY: Both layouts are contiguous and have the same number of elements, so they cover the same memory
+        (self.is_contiguous() && layout.len() == self.len()).then(|| unsafe {
+            AnyTensorMut::from_raw_parts(
+                self.as_mut_ptr(),
+                layout.shape,
+                layout.strides,
+                self.stride,
+                self.type_id,
+            )
+        })
+    }
+
+    /// View the elements as a regular erased slice, in row-major order.
+    ///
+    /// This is only possible if the tensor [is contiguous](AnyTensorMut::is_contiguous). Otherwise, you get `None`.
+    pub fn as_slice(&self) -> Option<AnySliceRef<'_>> {
+        // SAFETY: The elements are contiguous and in row-major order, so they form a valid slice
+        self.is_contiguous().then(|| unsafe {
+            AnySliceRef::from_raw_parts(self.as_ptr(), self.len(), self.stride, self.type_id)
+        })
+    }
+
+    /// View the elements as a regular mutable erased slice, in row-major order.
+    ///
+    /// This is only possible if the tensor [is contiguous](AnyTensorMut::is_contiguous). Otherwise, you get `None`.
+    pub fn as_slice_mut(&mut self) -> Option<AnySliceMut<'_>> {
+        // SAFETY: The elements are contiguous and in row-major order, so they form a valid slice
+        self.is_contiguous().then(|| unsafe {
+            AnySliceMut::from_raw_parts(self.as_mut_ptr(), self.len(), self.stride, self.type_id)
+        })
+    }
+
+    /// Are the elements laid out in row-major order, without any gaps?
+    pub fn is_contiguous(&self) -> bool {
+        self.layout.is_contiguous(self.stride)
+    }
+
+    /// Retrieve an unsafe pointer to the element at index `[0; D]`.
+    pub const fn as_ptr(&self) -> *const () {
+        self.ptr.cast_const().cast::<()>()
+    }
+
+    /// Retrieve an unsafe mutable pointer to the element at index `[0; D]`.
+    pub fn as_mut_ptr(&mut self) -> *mut () {
+        self.ptr.cast::<()>()
+    }
+
+    /// The number of elements along each axis.
+    pub const fn shape(&self) -> &[usize; D] {
+        &self.layout.shape
+    }
+
+    /// The distance in bytes between subsequent elements along each axis.
+    pub const fn strides(&self) -> &[isize; D] {
+        &self.layout.strides
+    }
+
+    /// The number of axes.
+    pub const fn rank(&self) -> usize {
+        D
+    }
+
+    /// The total number of elements.
+    pub fn len(&self) -> usize {
+        self.layout.len()
+    }
+
+    /// Does the tensor contain any elements at all?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Was the original element of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        TypeId::of::<T>() == self.type_id
+    }
+
+    /// The `size_of()` of the original elements of type `T`.
+    pub const fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// A unique type id representing the original element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        &self.type_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // All these tests use an (u8, u16) because it has padding
+
+    #[test]
+    fn write_views() {
+        let mut data: [(u8, u16); 12] = core::array::from_fn(|i| (i as u8, 0));
+        let slice = AnySliceMut::erase(data.as_mut_slice());
+        let mut tensor = AnyTensorMut::new(slice, [3, 4]).unwrap();
+
+        assert_eq!(
+            tensor.get([2, 1]).unwrap().unerase::<(u8, u16)>(),
+            Some(&(9, 0))
+        );
+        assert!(tensor.get_mut([3, 0]).is_none());
+
+        // Zero out the middle two columns through a transposed view
+        let mut columns = tensor.borrow_mut().slice_axis(1, 1..3).unwrap().transpose();
+        assert_eq!(columns.shape(), &[2, 3]);
+        assert!(columns.as_slice_mut().is_none());
+        for x in 0..2 {
+            for y in 0..3 {
+                let mut element = columns.get_mut([x, y]).unwrap();
+                element.unerase_mut::<(u8, u16)>().unwrap().1 = 1;
+            }
+        }
+
+        let flat = tensor.reshape([12]).unwrap();
+        assert!(flat.as_slice().is_some());
+
+        let marked = data.iter().filter(|(_, mark)| *mark == 1);
+        assert!(marked.map(|(i, _)| i % 4).all(|x| x == 1 || x == 2));
+        assert_eq!(data.iter().filter(|(_, mark)| *mark == 1).count(), 6);
+    }
+
+    #[test]
+    fn borrow_broadcast() {
+        let mut data: [(u8, u16); 3] = [(1, 0), (2, 0), (3, 0)];
+        let slice = AnySliceMut::erase(data.as_mut_slice());
+        let tensor = AnyTensorMut::new(slice, [3]).unwrap();
+
+        let broadcast = tensor.borrow().broadcast([2, 3]).unwrap();
+        assert_eq!(
+            broadcast.get([1, 2]).unwrap().unerase::<(u8, u16)>(),
+            Some(&(3, 0))
+        );
+
+        let mut slice = tensor.permute_axes([0]).unwrap();
+        let mut slice = slice.as_slice_mut().unwrap();
+        slice.unerase_mut::<(u8, u16)>().unwrap().reverse();
+        assert_eq!(data, [(3, 0), (2, 0), (1, 0)]);
+    }
+}
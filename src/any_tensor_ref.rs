@@ -0,0 +1,372 @@
+use crate::{tensor::Layout, AnyRef, AnySliceRef};
+use core::{any::TypeId, marker::PhantomData, ops::RangeBounds};
+
+/// A type-erased immutable n-dimensional view.
+///
+/// A tensor of rank `D` has a shape (the number of elements along each of its axes), and a byte stride
+/// per axis (the distance in memory between subsequent elements along that axis). Strides can be anything,
+/// including zero or negative, which allows views to be sliced, transposed and broadcast without copying.
+///
+/// Note that [`stride()`](AnyTensorRef::stride) is the size of the individual elements, just like for
+/// [`AnySliceRef`], while [`strides()`](AnyTensorRef::strides) are the per-axis distances.
+///
+/// View transformations like [`transpose()`](AnyTensorRef::transpose) take the tensor by value, so they
+/// can be chained. Because `AnyTensorRef` is `Copy`, the original remains usable.
+///
+/// ```
+/// let data : [i32; 6] = [0, 1, 2, 3, 4, 5];
+/// let slice = sashay::AnySliceRef::erase(data.as_slice());
+///
+/// // A 2x3 matrix
+/// let matrix = sashay::AnyTensorRef::new(slice, [2, 3]).unwrap();
+/// assert_eq!(matrix.get([1, 0]).unwrap().unerase::<i32>(), Some(&3));
+///
+/// // Its 3x2 transpose
+/// let transposed = matrix.transpose();
+/// assert_eq!(transposed.shape(), &[3, 2]);
+/// assert_eq!(transposed.get([0, 1]).unwrap().unerase::<i32>(), Some(&3));
+/// assert!(!transposed.is_contiguous());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AnyTensorRef<'a, const D: usize> {
+    /// A raw pointer to the element at index `[0; D]`
+    ///
+    /// Note: this pointer must be aligned, and offsetting it along the strides must point to valid
+    /// values of `T` for every index within the shape
+    ptr: *const u8,
+
+    /// The shape and per-axis strides
+    layout: Layout<D>,
+
+    /// The `size_of()` of the individual elements
+    stride: usize,
+
+    /// A unique id representing the type of the referenced elements
+    ///
+    /// This is used to ensure we can safely unerase back without accidentally transmuting
+    type_id: TypeId,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a, const D: usize> AnyTensorRef<'a, D> {
+    /// View an erased slice as a row-major tensor of a given shape.
+    ///
+    /// The number of elements in the shape must be equal to the length of the slice, otherwise you get `None`.
+    pub fn new(slice: AnySliceRef<'a>, shape: [usize; D]) -> Option<Self> {
+        let layout = Layout::contiguous(shape, slice.stride())?;
+
+        // SAFETY: A contiguous layout with as many elements as the slice covers exactly the slice
+        (layout.len() == slice.len()).then(|| unsafe {
+            Self::from_raw_parts(
+                slice.as_ptr(),
+                layout.shape,
+                layout.strides,
+                slice.stride(),
+                *slice.type_id(),
+            )
+        })
+    }
+
+    /// Construct an erased tensor from its raw parts.
+    ///
+    /// `shape` is the number of elements along each axis, `strides` the distance in bytes between
+    /// subsequent elements along each axis, and `stride` the `size_of()` of the elements themselves.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if:
+    ///  - For every index within `shape`, `ptr` offset by the sum of `index[axis] * strides[axis]` bytes points to a valid and aligned `T`
+    ///  - These elements aren't mutated for the duration of `'a`
+    ///  - `stride` is the correct [`size_of()`](https://doc.rust-lang.org/std/mem/fn.size_of.html) for the element type `T`
+    ///  - `type_id` is the correct [`TypeId`](https://doc.rust-lang.org/stable/std/any/struct.TypeId.html) for the element type `T`
+    pub const unsafe fn from_raw_parts(
+        ptr: *const (),
+        shape: [usize; D],
+        strides: [isize; D],
+        stride: usize,
+        type_id: TypeId,
+    ) -> Self {
+        Self {
+            ptr: ptr.cast::<u8>(),
+            layout: Layout { shape, strides },
+            stride,
+            type_id,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Retrieve an immutable reference to the element at an index.
+    ///
+    /// If the index is out of bounds along any axis, you get `None`.
+    pub fn get(&self, index: [usize; D]) -> Option<AnyRef<'_>> {
+        let offset = self.layout.offset(index)?;
+
+        // SAFETY: The index is within bounds, so the element is valid as per construction
+        let reference = unsafe {
+            AnyRef::from_raw_parts(self.ptr.wrapping_offset(offset).cast::<()>(), self.type_id)
+        };
+
+        Some(reference)
+    }
+
+    /// Restrict one of the axes to a range of indices.
+    ///
+    /// The range is clamped to the length of the axis. If the axis doesn't exist, you get `None`.
+    ///
+    /// ```
+    /// let data : [i32; 6] = [0, 1, 2, 3, 4, 5];
+    /// let slice = sashay::AnySliceRef::erase(data.as_slice());
+    /// let matrix = sashay::AnyTensorRef::new(slice, [2, 3]).unwrap();
+    ///
+    /// // Take the last two columns
+    /// let columns = matrix.slice_axis(1, 1..).unwrap();
+    /// assert_eq!(columns.shape(), &[2, 2]);
+    /// assert_eq!(columns.get([1, 0]).unwrap().unerase::<i32>(), Some(&4));
+    /// ```
+    pub fn slice_axis<R>(self, axis: usize, range: R) -> Option<Self>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (layout, offset) = self.layout.slice_axis(axis, range)?;
+
+        Some(Self {
+            ptr: self.ptr.wrapping_offset(offset),
+            layout,
+            ..self
+        })
+    }
+
+    /// Reverse the order of the axes.
+    ///
+    /// For a matrix, this swaps rows and columns.
+    pub fn transpose(self) -> Self {
+        Self {
+            layout: self.layout.transpose(),
+            ..self
+        }
+    }
+
+    /// Reorder the axes, so that axis `i` of the result is axis `axes[i]` of this tensor.
+    ///
+    /// If `axes` doesn't contain every axis exactly once, you get `None`.
+    pub fn permute_axes(self, axes: [usize; D]) -> Option<Self> {
+        Some(Self {
+            layout: self.layout.permute(axes)?,
+            ..self
+        })
+    }
+
+    /// View the same elements with a different shape.
+    ///
+    /// This is only possible if the tensor is contiguous, and the new shape has the same number of
+    /// elements. Otherwise, you get `None`.
+    ///
+    /// ```
+    /// let data : [i32; 6] = [0, 1, 2, 3, 4, 5];
+    /// let slice = sashay::AnySliceRef::erase(data.as_slice());
+    /// let matrix = sashay::AnyTensorRef::new(slice, [2, 3]).unwrap();
+    ///
+    /// let cube = matrix.reshape([3, 1, 2]).unwrap();
+    /// assert_eq!(cube.get([2, 0, 1]).unwrap().unerase::<i32>(), Some(&5));
+    ///
+    /// // Transposed tensors aren't contiguous
+    /// assert!(matrix.transpose().reshape([6]).is_none());
+    /// ```
+    pub fn reshape<const E: usize>(self, shape: [usize; E]) -> Option<AnyTensorRef<'a, E>> {
+        let layout = Layout::contiguous(shape, self.stride)?;
+
+        // SAFETY: Both layouts are contiguous and have the same number of elements, so they cover the same memory
+        (self.is_contiguous() && layout.len() == self.len()).then(|| unsafe {
+            AnyTensorRef::from_raw_parts(
+                self.as_ptr(),
+                layout.shape,
+                layout.strides,
+                self.stride,
+                self.type_id,
+            )
+        })
+    }
+
+    /// Stretch the tensor to a larger shape, by repeating axes of length 1.
+    ///
+    /// Like broadcasting in NumPy, the axes are matched from the back. Every axis must either have the
+    /// same length as in `shape`, or have length 1, in which case its only element is repeated. Leading axes
+    /// that this tensor doesn't have are added, repeating the whole tensor. Otherwise, you get `None`.
+    ///
+    /// ```
+    /// let row : [i32; 3] = [0, 1, 2];
+    /// let slice = sashay::AnySliceRef::erase(row.as_slice());
+    /// let vector = sashay::AnyTensorRef::new(slice, [3]).unwrap();
+    ///
+    /// let matrix = vector.broadcast([4, 3]).unwrap();
+    /// assert_eq!(matrix.get([3, 2]).unwrap().unerase::<i32>(), Some(&2));
+    /// assert_eq!(matrix.strides()[0], 0);
+    ///
+    /// assert!(vector.broadcast([2]).is_none());
+    /// ```
+    pub fn broadcast<const E: usize>(self, shape: [usize; E]) -> Option<AnyTensorRef<'a, E>> {
+        let layout = self.layout.broadcast(shape)?;
+
+        // SAFETY: Broadcasting only repeats existing elements, and immutable references may alias
+        let tensor = unsafe {
+            AnyTensorRef::from_raw_parts(
+                self.as_ptr(),
+                layout.shape,
+                layout.strides,
+                self.stride,
+                self.type_id,
+            )
+        };
+
+        Some(tensor)
+    }
+
+    /// View the elements as a regular erased slice, in row-major order.
+    ///
+    /// This is only possible if the tensor [is contiguous](AnyTensorRef::is_contiguous). Otherwise, you get `None`.
+    pub fn as_slice(&self) -> Option<AnySliceRef<'_>> {
+        // SAFETY: The elements are contiguous and in row-major order, so they form a valid slice
+        self.is_contiguous().then(|| unsafe {
+            AnySliceRef::from_raw_parts(self.as_ptr(), self.len(), self.stride, self.type_id)
+        })
+    }
+
+    /// Are the elements laid out in row-major order, without any gaps or repetition?
+    pub fn is_contiguous(&self) -> bool {
+        self.layout.is_contiguous(self.stride)
+    }
+
+    /// Retrieve an unsafe pointer to the element at index `[0; D]`.
+    pub const fn as_ptr(&self) -> *const () {
+        self.ptr.cast::<()>()
+    }
+
+    /// The number of elements along each axis.
+    pub const fn shape(&self) -> &[usize; D] {
+        &self.layout.shape
+    }
+
+    /// The distance in bytes between subsequent elements along each axis.
+    pub const fn strides(&self) -> &[isize; D] {
+        &self.layout.strides
+    }
+
+    /// The number of axes.
+    pub const fn rank(&self) -> usize {
+        D
+    }
+
+    /// The total number of elements.
+    pub fn len(&self) -> usize {
+        self.layout.len()
+    }
+
+    /// Does the tensor contain any elements at all?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Was the original element of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        TypeId::of::<T>() == self.type_id
+    }
+
+    /// The `size_of()` of the original elements of type `T`.
+    pub const fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// A unique type id representing the original element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        &self.type_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // All these tests use an (u8, u16) because it has padding
+
+    #[test]
+    fn views() {
+        let data: [(u8, u16); 24] = core::array::from_fn(|i| (i as u8, 0));
+        let slice = AnySliceRef::erase(data.as_slice());
+        let value = |tensor: &AnyTensorRef<'_, 3>, index| {
+            tensor
+                .get(index)
+                .map(|any| any.unerase::<(u8, u16)>().unwrap().0)
+        };
+
+        assert!(AnyTensorRef::new(slice, [2, 3, 5]).is_none());
+        let tensor = AnyTensorRef::new(slice, [2, 3, 4]).unwrap();
+        assert_eq!(tensor.len(), 24);
+        assert_eq!(value(&tensor, [1, 2, 3]), Some(23));
+        assert_eq!(value(&tensor, [2, 0, 0]), None);
+        assert_eq!(
+            tensor.as_slice().unwrap().unerase::<(u8, u16)>(),
+            Some(data.as_slice())
+        );
+
+        let sliced = tensor.slice_axis(2, 1..3).unwrap();
+        assert_eq!(sliced.shape(), &[2, 3, 2]);
+        assert_eq!(value(&sliced, [1, 1, 1]), Some(18));
+        assert!(!sliced.is_contiguous());
+        assert!(tensor.slice_axis(3, ..).is_none());
+
+        let permuted = tensor.permute_axes([2, 0, 1]).unwrap();
+        assert_eq!(permuted.shape(), &[4, 2, 3]);
+        assert_eq!(value(&permuted, [3, 1, 0]), Some(15));
+        assert!(tensor.permute_axes([0, 0, 1]).is_none());
+
+        // Slicing an axis down to a single element makes it contiguous again
+        let single = tensor.slice_axis(0, 1..2).unwrap();
+        assert!(single.is_contiguous());
+        assert_eq!(single.as_slice().unwrap().len(), 12);
+    }
+
+    #[test]
+    fn reshape_broadcast() {
+        let data: [(u8, u16); 6] = core::array::from_fn(|i| (i as u8, 0));
+        let slice = AnySliceRef::erase(data.as_slice());
+
+        let matrix = AnyTensorRef::new(slice, [2, 3]).unwrap();
+        let column = matrix.slice_axis(1, 1..2).unwrap().reshape([2]);
+        assert!(column.is_none());
+
+        let flat = matrix.reshape([6]).unwrap();
+        assert_eq!(flat.rank(), 1);
+        assert!(matrix.reshape([4]).is_none());
+
+        let column = matrix.slice_axis(1, 2..).unwrap();
+        let broadcast = column.broadcast([3, 2, 4]).unwrap();
+        assert_eq!(
+            broadcast.get([2, 1, 3]).unwrap().unerase::<(u8, u16)>(),
+            Some(&(5, 0))
+        );
+        assert!(!broadcast.is_contiguous());
+        assert!(column.broadcast([2, 2]).is_some());
+        assert!(column.broadcast([3, 3]).is_none());
+        assert!(column.broadcast::<1>([2]).is_none());
+    }
+
+    #[test]
+    fn overflow() {
+        let empty: [(u8, u16); 0] = [];
+        let slice = AnySliceRef::erase(empty.as_slice());
+
+        // The number of elements overflows to zero
+        let half = 1 << (usize::BITS / 2);
+        assert!(AnyTensorRef::new(slice, [half, half]).is_none());
+
+        // The byte stride of the first axis overflows
+        let tensor = AnyTensorRef::new(slice, [usize::MAX / 2, 0]).unwrap();
+        assert!(tensor.reshape([0, usize::MAX / 2]).is_none());
+
+        let data = [(0u8, 0u16); 1];
+        let vector = AnyTensorRef::new(AnySliceRef::erase(data.as_slice()), [1]).unwrap();
+        assert!(vector.broadcast([usize::MAX, 2]).is_none());
+    }
+}
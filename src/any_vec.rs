@@ -0,0 +1,420 @@
+use crate::{AnyMut, AnyRef, AnySliceMut, AnySliceRef, TypeInfo};
+use core::{any::TypeId, fmt, ptr};
+
+/// A type-erased growable vector of same-typed elements.
+///
+/// This works like a `Vec<T>` whose element type is only known at runtime. It owns its elements and
+/// drops them when it is dropped, using the [`TypeInfo`] it was constructed with.
+///
+/// ```
+/// let mut vec = sashay::AnyVec::new::<String>();
+///
+/// vec.push(String::from("foo"));
+/// vec.push(String::from("bar"));
+/// assert_eq!(vec.len(), 2);
+///
+/// let strings = vec.as_slice().unerase_into::<String>().unwrap();
+/// assert_eq!(strings, ["foo", "bar"]);
+///
+/// assert_eq!(vec.swap_remove::<String>(0).as_deref(), Some("foo"));
+/// assert_eq!(vec.get(0).unwrap().unerase_into::<String>().unwrap(), "bar");
+/// ```
+pub struct AnyVec {
+    /// A raw pointer to the storage
+    ///
+    /// Note: this pointer must be aligned and point to `capacity` possibly uninitialized values of `T`,
+    /// of which the first `len` are initialized
+    ptr: *mut u8,
+
+    /// The number of initialized elements at the front of the storage
+    len: usize,
+
+    /// The number of elements that fit in the storage
+    capacity: usize,
+
+    /// The type of the elements
+    info: TypeInfo,
+}
+
+impl AnyVec {
+    /// Construct an empty vector of elements of type `T`.
+    ///
+    /// This doesn't allocate until elements are pushed.
+    pub fn new<T: 'static>() -> Self {
+        Self::with_type_info(TypeInfo::of::<T>())
+    }
+
+    /// Construct an empty vector of elements of a described type.
+    pub fn with_type_info(info: TypeInfo) -> Self {
+        Self {
+            ptr: info.allocate_array(0),
+            len: 0,
+            capacity: 0,
+            info,
+        }
+    }
+
+    /// Append an element to the back of the vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not the element type.
+    pub fn push<T: 'static>(&mut self, value: T) {
+        if self.try_push(value).is_err() {
+            panic!("pushed a value of the wrong type");
+        }
+    }
+
+    /// Append an element to the back of the vector.
+    ///
+    /// If `T` is not the element type, you get the value back.
+    pub fn try_push<T: 'static>(&mut self, value: T) -> Result<(), T> {
+        if !self.contains::<T>() {
+            return Err(value);
+        }
+
+        self.reserve(1);
+
+        // SAFETY: The element after the last one is within the storage and uninitialized, and of type T
+        unsafe { self.element(self.len).cast::<T>().write(value) };
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Remove the last element from the vector and return it.
+    ///
+    /// If the vector is empty, or if `T` is not the element type, you get `None`.
+    pub fn pop<T: 'static>(&mut self) -> Option<T> {
+        if self.is_empty() || !self.contains::<T>() {
+            return None;
+        }
+
+        self.len -= 1;
+
+        // SAFETY: The element was initialized, and is no longer part of the vector
+        Some(unsafe { self.element(self.len).cast::<T>().read() })
+    }
+
+    /// Remove an element and return it, replacing it with the last element.
+    ///
+    /// If the index is out of bounds, or if `T` is not the element type, you get `None`.
+    pub fn swap_remove<T: 'static>(&mut self, index: usize) -> Option<T> {
+        if index >= self.len || !self.contains::<T>() {
+            return None;
+        }
+
+        // SAFETY: The element is initialized, and is moved out before it is overwritten
+        let value = unsafe { self.element(index).cast::<T>().read() };
+
+        // SAFETY: The index is in bounds, and the element was moved out
+        unsafe { self.swap_remove_forget(index) };
+
+        Some(value)
+    }
+
+    /// Remove an element and drop it, replacing it with the last element.
+    ///
+    /// Returns whether the index was in bounds.
+    pub fn swap_discard(&mut self, index: usize) -> bool {
+        if index >= self.len {
+            return false;
+        }
+
+        self.swap_remove_staged(index);
+
+        // SAFETY: The removed element ended up past the end, so it's no longer part of the vector
+        unsafe { self.info.drop_in_place(self.element(self.len).cast::<()>()) };
+
+        true
+    }
+
+    /// Remove an element and drop it, shifting all elements after it to the front.
+    ///
+    /// Returns whether the index was in bounds.
+    pub fn discard(&mut self, index: usize) -> bool {
+        if index >= self.len {
+            return false;
+        }
+
+        // Move the element past the end first, so the vector stays consistent if dropping it panics
+        self.remove_staged(index);
+
+        // SAFETY: The removed element ended up past the end, so it's no longer part of the vector
+        unsafe { self.info.drop_in_place(self.element(self.len).cast::<()>()) };
+
+        true
+    }
+
+    /// Shorten the vector to `len` elements, dropping the rest.
+    ///
+    /// If the vector is already shorter, nothing happens.
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.len -= 1;
+
+            // SAFETY: The element was initialized, and is no longer part of the vector
+            unsafe { self.info.drop_in_place(self.element(self.len).cast::<()>()) };
+        }
+    }
+
+    /// Remove and drop all elements.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Make sure at least `additional` more elements fit without reallocating.
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("capacity overflow");
+        if required <= self.capacity {
+            return;
+        }
+
+        let capacity = required.max(self.capacity * 2).max(4);
+        let ptr = self.info.allocate_array(capacity);
+
+        // SAFETY:
+        // - Both storages fit at least `len` elements and don't overlap
+        // - The old storage was allocated with the same type information and capacity
+        unsafe {
+            ptr::copy_nonoverlapping(self.ptr, ptr, self.len * self.stride());
+            self.info.deallocate_array(self.ptr, self.capacity);
+        }
+
+        self.ptr = ptr;
+        self.capacity = capacity;
+    }
+
+    /// Retrieve an immutable reference to an element.
+    pub fn get(&self, index: usize) -> Option<AnyRef<'_>> {
+        // SAFETY: The element is within bounds, so it's initialized
+        (index < self.len).then(|| unsafe {
+            AnyRef::from_raw_parts(
+                self.element(index).cast_const().cast::<()>(),
+                *self.info.type_id(),
+            )
+        })
+    }
+
+    /// Retrieve a mutable reference to an element.
+    pub fn get_mut(&mut self, index: usize) -> Option<AnyMut<'_>> {
+        // SAFETY: The element is within bounds, so it's initialized
+        (index < self.len).then(|| unsafe {
+            AnyMut::from_raw_parts(self.element(index).cast::<()>(), *self.info.type_id())
+        })
+    }
+
+    /// Access the elements as an erased slice.
+    pub fn as_slice(&self) -> AnySliceRef<'_> {
+        // SAFETY: The first `len` elements are initialized, and the type information is valid
+        unsafe {
+            AnySliceRef::from_raw_parts(
+                self.ptr.cast_const().cast::<()>(),
+                self.len,
+                self.info.stride(),
+                *self.info.type_id(),
+            )
+        }
+    }
+
+    /// Access the elements as a mutable erased slice.
+    pub fn as_mut_slice(&mut self) -> AnySliceMut<'_> {
+        // SAFETY: The first `len` elements are initialized, and the type information is valid
+        unsafe {
+            AnySliceMut::from_raw_parts(
+                self.ptr.cast::<()>(),
+                self.len,
+                self.info.stride(),
+                *self.info.type_id(),
+            )
+        }
+    }
+
+    /// How many elements does the vector contain?
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Does the vector contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many elements fit in the vector without reallocating?
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Are the elements of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.info.is::<T>()
+    }
+
+    /// The `size_of()` of the elements.
+    pub const fn stride(&self) -> usize {
+        self.info.stride()
+    }
+
+    /// A unique type id representing the element type.
+    pub const fn type_id(&self) -> &TypeId {
+        self.info.type_id()
+    }
+
+    /// The runtime information about the element type.
+    pub const fn type_info(&self) -> &TypeInfo {
+        &self.info
+    }
+
+    /// Append an element by copying its bytes
+    ///
+    /// # Safety
+    ///
+    /// `value` must point to a valid value of the element type, which is either `Copy` or is never
+    /// used or dropped again afterwards.
+    pub(crate) unsafe fn push_raw(&mut self, value: *const u8) {
+        self.reserve(1);
+
+        // SAFETY: The caller guarantees the value is valid and may be moved, and the element after the
+        // last one is within the storage and uninitialized
+        unsafe { ptr::copy_nonoverlapping(value, self.element(self.len), self.stride()) };
+        self.len += 1;
+    }
+
+    /// Remove an element without dropping it, replacing it with the last element
+    ///
+    /// # Safety
+    ///
+    /// The index must be in bounds, and the element must have been moved out or dropped before.
+    pub(crate) unsafe fn swap_remove_forget(&mut self, index: usize) {
+        self.len -= 1;
+
+        if index != self.len {
+            // SAFETY: Both elements are within the storage, and the last one is no longer part of the vector
+            unsafe {
+                ptr::copy_nonoverlapping(self.element(self.len), self.element(index), self.stride())
+            };
+        }
+    }
+
+    /// Remove an element without dropping it, swapping it with the last element
+    ///
+    /// The index must be in bounds. The removed value stays in the storage right past the end, at
+    /// `element(len())`, where the caller is responsible for dropping it.
+    pub(crate) fn swap_remove_staged(&mut self, index: usize) {
+        self.len -= 1;
+
+        if index != self.len {
+            // SAFETY: Both elements are initialized and don't overlap
+            unsafe {
+                ptr::swap_nonoverlapping(self.element(index), self.element(self.len), self.stride())
+            };
+        }
+    }
+
+    /// Remove an element without dropping it, shifting all elements after it to the front
+    ///
+    /// The index must be in bounds. The removed value stays in the storage right past the end, at
+    /// `element(len())`, where the caller is responsible for dropping it.
+    pub(crate) fn remove_staged(&mut self, index: usize) {
+        for current in index..self.len - 1 {
+            // SAFETY: Both elements are initialized and within bounds
+            unsafe {
+                ptr::swap_nonoverlapping(
+                    self.element(current),
+                    self.element(current + 1),
+                    self.stride(),
+                );
+            }
+        }
+
+        self.len -= 1;
+    }
+
+    /// Shorten the vector to `len` elements without dropping the rest
+    ///
+    /// The removed values stay in the storage from `element(len)` up to the old length, where the
+    /// caller is responsible for dropping them.
+    pub(crate) fn truncate_staged(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    /// A pointer to the element at `index`, which doesn't need to be in bounds
+    pub(crate) fn element(&self, index: usize) -> *mut u8 {
+        self.ptr.wrapping_add(index * self.info.stride())
+    }
+}
+
+impl Drop for AnyVec {
+    fn drop(&mut self) {
+        self.clear();
+
+        // SAFETY: The storage was allocated with the same type information and capacity
+        unsafe { self.info.deallocate_array(self.ptr, self.capacity) };
+    }
+}
+
+impl fmt::Debug for AnyVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyVec")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity)
+            .field("info", &self.info)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{DropCounter, Tracked};
+
+    // These tests use an (u8, u16) because it has padding, and `Tracked` values to count drops
+
+    #[test]
+    fn push_remove() {
+        let mut vec = AnyVec::new::<(u8, u16)>();
+        assert_eq!(vec.capacity(), 0);
+        assert_eq!(vec.try_push(0u8), Err(0u8));
+
+        for value in 0..10u8 {
+            vec.push((value, 0u16));
+        }
+        assert!(vec.capacity() >= 10);
+
+        assert_eq!(vec.swap_remove::<(u8, u16)>(2), Some((2u8, 0u16)));
+        assert_eq!(vec.pop::<(u8, u16)>(), Some((8u8, 0u16)));
+        assert!(vec.swap_discard(0));
+        assert!(vec.discard(1));
+        assert!(!vec.discard(6));
+
+        assert_eq!(
+            vec.as_slice().unerase::<(u8, u16)>().unwrap(),
+            [
+                (7u8, 0u16),
+                (9u8, 0u16),
+                (3u8, 0u16),
+                (4u8, 0u16),
+                (5u8, 0u16),
+                (6u8, 0u16)
+            ]
+        );
+    }
+
+    #[test]
+    fn drops() {
+        static DROPS: DropCounter = DropCounter::new();
+
+        let mut vec = AnyVec::new::<Tracked>();
+        for _ in 0..6 {
+            vec.push(DROPS.track(()));
+        }
+
+        vec.swap_discard(5);
+        vec.discard(0);
+        vec.truncate(3);
+        assert_eq!(DROPS.drops(), 3);
+
+        drop(vec);
+        assert_eq!(DROPS.drops(), 6);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{
+    numeric::{CastTo, Numeric},
+    AnySliceMut, AnySliceRef, NumericError, PrimitiveSlice, PrimitiveSliceMut,
+};
+
+/// How [`convert_into()`] maps values from one numeric type to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Conversion {
+    /// Convert using the `as` operator
+    ///
+    /// Integers that don't fit wrap around, floats are truncated towards zero.
+    Cast,
+
+    /// Convert, clamping values that don't fit to the destination type's minimum or maximum
+    Saturating,
+
+    /// Convert by scaling between the full range of integers and [-1, 1] for floats
+    ///
+    /// Signed integers are scaled by 2^(bits - 1), so that an `i16` of `-32768` becomes `-1.0`.
+    /// Unsigned integers are treated as offset binary (like 8-bit audio), so that their midpoint becomes `0.0`.
+    /// Floats outside of [-1, 1] saturate when converted to integers. Conversions between integer types
+    /// are scaled through `f64`, and between float types this is the same as [`Conversion::Cast`].
+    Normalized,
+}
+
+/// Convert the elements of one erased numeric slice into another.
+///
+/// Both slices can be of any primitive integer or float type, and don't need to have the same element type.
+/// Every element in `source` is converted to the element type of `destination` and written there, using the
+/// given [`Conversion`]. An error is returned if either slice is not numeric, or if they don't have the same length.
+///
+/// ```
+/// use sashay::{AnySliceMut, AnySliceRef, Conversion};
+///
+/// let source : [i16; 3] = [-32768, 0, 16384];
+/// let mut destination = [0f32; 3];
+///
+/// sashay::convert_into(
+///     &AnySliceRef::erase(source.as_slice()),
+///     &mut AnySliceMut::erase(destination.as_mut_slice()),
+///     Conversion::Normalized,
+/// ).unwrap();
+///
+/// assert_eq!(destination, [-1.0, 0.0, 0.5]);
+/// ```
+pub fn convert_into(
+    source: &AnySliceRef<'_>,
+    destination: &mut AnySliceMut<'_>,
+    conversion: Conversion,
+) -> Result<(), NumericError> {
+    if source.len() != destination.len() {
+        return Err(NumericError::LengthMismatch {
+            expected: destination.len(),
+            found: source.len(),
+        });
+    }
+
+    let source_type_id = *source.type_id();
+    let destination_type_id = *destination.type_id();
+
+    let source = source
+        .primitive()
+        .ok_or(NumericError::Unsupported(source_type_id))?;
+    let destination = destination
+        .primitive_mut()
+        .ok_or(NumericError::Unsupported(destination_type_id))?;
+
+    macro_rules! dispatch {
+        ($($variant:ident),+) => {
+            dispatch!(@source [$($variant),+]; $($variant),+)
+        };
+
+        (@source $destinations:tt; $($variant:ident),+) => {
+            match source {
+                $(PrimitiveSlice::$variant(source) => dispatch!(@destination source; $destinations),)+
+                _ => Err(NumericError::Unsupported(source_type_id)),
+            }
+        };
+
+        (@destination $source:ident; [$($variant:ident),+]) => {
+            match destination {
+                $(PrimitiveSliceMut::$variant(destination) => {
+                    convert_slice($source, destination, conversion);
+                    Ok(())
+                })+
+                _ => Err(NumericError::Unsupported(destination_type_id)),
+            }
+        };
+    }
+
+    dispatch!(I8, I16, I32, I64, I128, Isize, U8, U16, U32, U64, U128, Usize, F32, F64)
+}
+
+/// Convert between two typed slices of the same length
+fn convert_slice<S, D>(source: &[S], destination: &mut [D], conversion: Conversion)
+where
+    S: Numeric + CastTo<D>,
+    D: Numeric,
+{
+    let pairs = destination.iter_mut().zip(source);
+
+    match conversion {
+        Conversion::Cast => pairs.for_each(|(d, s)| *d = s.cast()),
+        Conversion::Saturating => pairs.for_each(|(d, s)| *d = s.saturate()),
+        Conversion::Normalized => {
+            pairs.for_each(|(d, s)| *d = D::from_normalized(s.to_normalized()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::any::TypeId;
+
+    fn convert<S: 'static, D: 'static + Copy + Default, const N: usize>(
+        source: [S; N],
+        conversion: Conversion,
+    ) -> [D; N] {
+        let mut destination = [D::default(); N];
+        convert_into(
+            &AnySliceRef::erase(source.as_slice()),
+            &mut AnySliceMut::erase(destination.as_mut_slice()),
+            conversion,
+        )
+        .unwrap();
+        destination
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(
+            convert::<i32, u8, 3>([-1, 200, 300], Conversion::Cast),
+            [255, 200, 44]
+        );
+        assert_eq!(
+            convert::<i32, u8, 3>([-1, 200, 300], Conversion::Saturating),
+            [0, 200, 255]
+        );
+        assert_eq!(
+            convert::<u64, i8, 2>([u64::MAX, 3], Conversion::Saturating),
+            [127, 3]
+        );
+        assert_eq!(
+            convert::<f64, f32, 2>([1e300, -1e300], Conversion::Saturating),
+            [f32::MAX, f32::MIN]
+        );
+        assert_eq!(
+            convert::<f32, i16, 3>([-2.0, 0.5, 1.0], Conversion::Normalized),
+            [-32768, 16384, 32767]
+        );
+        assert_eq!(
+            convert::<u8, f32, 3>([0, 128, 192], Conversion::Normalized),
+            [-1.0, 0.0, 0.5]
+        );
+        assert_eq!(
+            convert::<i16, u8, 2>([-32768, 0], Conversion::Normalized),
+            [0, 128]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut destination = [0u8; 2];
+        let mut destination = AnySliceMut::erase(destination.as_mut_slice());
+
+        assert_eq!(
+            convert_into(
+                &AnySliceRef::erase([1u8].as_slice()),
+                &mut destination,
+                Conversion::Cast
+            ),
+            Err(NumericError::LengthMismatch {
+                expected: 2,
+                found: 1
+            })
+        );
+
+        assert_eq!(
+            convert_into(
+                &AnySliceRef::erase([true, false].as_slice()),
+                &mut destination,
+                Conversion::Cast
+            ),
+            Err(NumericError::Unsupported(TypeId::of::<bool>()))
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use crate::{AnyMut, AnyRef, AnySliceMut, AnySliceRef};
+use alloc::{boxed::Box, vec::Vec};
+use core::{any::TypeId, fmt, mem::size_of};
+
+/// A registry of handlers, called based on the type of erased references.
+///
+/// Register one handler per type with [`ErasedDispatcher::register()`] or [`ErasedDispatcher::register_mut()`],
+/// and then pass erased references to [`ErasedDispatcher::dispatch()`] or [`ErasedDispatcher::dispatch_mut()`].
+/// The handler for the erased type gets called with the unerased reference.
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+///
+/// let total = Rc::new(Cell::new(0));
+///
+/// let mut dispatcher = sashay::ErasedDispatcher::new();
+/// dispatcher.register::<i32>({
+///     let total = total.clone();
+///     move |value| total.set(total.get() + value)
+/// });
+/// dispatcher.register_mut::<String>(|text| text.push('!'));
+///
+/// // Dispatch some erased references
+/// assert!(dispatcher.dispatch(sashay::AnyRef::erase(&3)));
+///
+/// let mut text = String::from("hello");
+/// assert!(dispatcher.dispatch_mut(sashay::AnyMut::erase(&mut text)));
+///
+/// // Types without a handler are not dispatched
+/// assert!(!dispatcher.dispatch(sashay::AnyRef::erase(&'x')));
+///
+/// // Whole slices only need a single lookup
+/// assert!(dispatcher.dispatch_slice(sashay::AnySliceRef::erase([1, 2, 3].as_slice())));
+///
+/// assert_eq!(total.get(), 9);
+/// assert_eq!(text, "hello!");
+/// ```
+#[derive(Default)]
+pub struct ErasedDispatcher {
+    /// The handlers for immutable references, sorted by their type id so we can binary search
+    handlers: Vec<RefHandler>,
+
+    /// The handlers for mutable references, sorted by their type id so we can binary search
+    handlers_mut: Vec<MutHandler>,
+}
+
+/// A registered handler, together with the type information needed to call it
+struct Handler<F: ?Sized> {
+    /// A unique id representing the type the handler accepts
+    type_id: TypeId,
+
+    /// The `size_of()` of the type the handler accepts
+    stride: usize,
+
+    /// The handler, wrapped so that it accepts a whole slice of its type at once
+    call: Box<F>,
+}
+
+/// A handler for immutable references
+type RefHandler = Handler<dyn FnMut(AnySliceRef<'_>)>;
+
+/// A handler for mutable references
+type MutHandler = Handler<dyn FnMut(AnySliceMut<'_>)>;
+
+impl ErasedDispatcher {
+    /// Construct a dispatcher without any handlers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a handler for immutable references to `T`.
+    ///
+    /// If a handler for `T` was already registered, it is replaced.
+    pub fn register<T: 'static>(&mut self, mut handler: impl FnMut(&T) + 'static) {
+        let handler = RefHandler {
+            type_id: TypeId::of::<T>(),
+            stride: size_of::<T>(),
+            call: Box::new(move |slice: AnySliceRef<'_>| {
+                slice
+                    .unerase::<T>()
+                    .into_iter()
+                    .flatten()
+                    .for_each(&mut handler);
+            }),
+        };
+
+        insert(&mut self.handlers, handler);
+    }
+
+    /// Register a handler for mutable references to `T`.
+    ///
+    /// If a handler for `T` was already registered, it is replaced.
+    pub fn register_mut<T: 'static>(&mut self, mut handler: impl FnMut(&mut T) + 'static) {
+        let handler = MutHandler {
+            type_id: TypeId::of::<T>(),
+            stride: size_of::<T>(),
+            call: Box::new(move |mut slice: AnySliceMut<'_>| {
+                slice
+                    .unerase_mut::<T>()
+                    .into_iter()
+                    .flatten()
+                    .for_each(&mut handler);
+            }),
+        };
+
+        insert(&mut self.handlers_mut, handler);
+    }
+
+    /// Remove the handler for immutable references to `T`, returning whether there was one.
+    pub fn unregister<T: 'static>(&mut self) -> bool {
+        remove(&mut self.handlers, &TypeId::of::<T>())
+    }
+
+    /// Remove the handler for mutable references to `T`, returning whether there was one.
+    pub fn unregister_mut<T: 'static>(&mut self) -> bool {
+        remove(&mut self.handlers_mut, &TypeId::of::<T>())
+    }
+
+    /// Call the handler registered for the erased reference's type.
+    ///
+    /// Returns `false` if no handler was registered for the type.
+    pub fn dispatch(&mut self, any: AnyRef<'_>) -> bool {
+        match find(&mut self.handlers, any.type_id()) {
+            Some(handler) => {
+                // SAFETY: The type ids match, so `stride` is the correct size for the element,
+                // and a single valid element is also a valid slice of length one
+                let slice = unsafe {
+                    AnySliceRef::from_raw_parts(any.as_ptr(), 1, handler.stride, handler.type_id)
+                };
+
+                (handler.call)(slice);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Call the handler registered for the erased mutable reference's type.
+    ///
+    /// Returns `false` if no handler was registered for the type.
+    pub fn dispatch_mut(&mut self, mut any: AnyMut<'_>) -> bool {
+        match find(&mut self.handlers_mut, any.type_id()) {
+            Some(handler) => {
+                // SAFETY: The type ids match, so `stride` is the correct size for the element,
+                // and a single valid element is also a valid slice of length one
+                let slice = unsafe {
+                    AnySliceMut::from_raw_parts(
+                        any.as_mut_ptr(),
+                        1,
+                        handler.stride,
+                        handler.type_id,
+                    )
+                };
+
+                (handler.call)(slice);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Call the handler registered for the slice's element type, once for every element.
+    ///
+    /// The handler is only looked up once for the whole slice. Returns `false` if no handler was
+    /// registered for the type.
+    pub fn dispatch_slice(&mut self, slice: AnySliceRef<'_>) -> bool {
+        match find(&mut self.handlers, slice.type_id()) {
+            Some(handler) => {
+                (handler.call)(slice);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Call the mutable handler registered for the slice's element type, once for every element.
+    ///
+    /// The handler is only looked up once for the whole slice. Returns `false` if no handler was
+    /// registered for the type.
+    pub fn dispatch_slice_mut(&mut self, slice: AnySliceMut<'_>) -> bool {
+        match find(&mut self.handlers_mut, slice.type_id()) {
+            Some(handler) => {
+                (handler.call)(slice);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Is there a handler registered for immutable references with this type id?
+    pub fn handles(&self, type_id: &TypeId) -> bool {
+        search(&self.handlers, type_id).is_ok()
+    }
+
+    /// Is there a handler registered for mutable references with this type id?
+    pub fn handles_mut(&self, type_id: &TypeId) -> bool {
+        search(&self.handlers_mut, type_id).is_ok()
+    }
+}
+
+impl fmt::Debug for ErasedDispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErasedDispatcher")
+            .field("handlers", &type_ids(&self.handlers))
+            .field("handlers_mut", &type_ids(&self.handlers_mut))
+            .finish()
+    }
+}
+
+/// The type ids of a list of handlers, for debug printing
+fn type_ids<F: ?Sized>(handlers: &[Handler<F>]) -> Vec<TypeId> {
+    handlers.iter().map(|handler| handler.type_id).collect()
+}
+
+/// Binary search for the index of the handler with a given type id
+fn search<F: ?Sized>(handlers: &[Handler<F>], type_id: &TypeId) -> Result<usize, usize> {
+    handlers.binary_search_by(|handler| handler.type_id.cmp(type_id))
+}
+
+/// Find the handler with a given type id
+fn find<'h, F: ?Sized>(
+    handlers: &'h mut [Handler<F>],
+    type_id: &TypeId,
+) -> Option<&'h mut Handler<F>> {
+    search(handlers, type_id)
+        .ok()
+        .map(|index| &mut handlers[index])
+}
+
+/// Insert a handler, replacing the one with the same type id
+fn insert<F: ?Sized>(handlers: &mut Vec<Handler<F>>, handler: Handler<F>) {
+    match search(handlers, &handler.type_id) {
+        Ok(index) => handlers[index] = handler,
+        Err(index) => handlers.insert(index, handler),
+    }
+}
+
+/// Remove the handler with a given type id, returning whether there was one
+fn remove<F: ?Sized>(handlers: &mut Vec<Handler<F>>, type_id: &TypeId) -> bool {
+    search(handlers, type_id)
+        .map(|index| handlers.remove(index))
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+
+    #[test]
+    fn dispatch_by_type() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+
+        let mut dispatcher = ErasedDispatcher::new();
+        dispatcher.register::<(u8, u16)>({
+            let seen = seen.clone();
+            move |value| seen.borrow_mut().push(*value)
+        });
+        dispatcher.register_mut::<(u8, u16)>(|value| value.0 += 1);
+
+        let mut data = [(1u8, 2u16), (3u8, 4u16)];
+        assert!(dispatcher.dispatch(AnyRef::erase(&data[0])));
+        assert!(dispatcher.dispatch_slice_mut(AnySliceMut::erase(data.as_mut_slice())));
+        assert!(dispatcher.dispatch_slice(AnySliceRef::erase(data.as_slice())));
+        assert!(!dispatcher.dispatch(AnyRef::erase(&0u8)));
+
+        assert_eq!(*seen.borrow(), [(1, 2), (2, 2), (4, 4)]);
+
+        assert!(dispatcher.unregister_mut::<(u8, u16)>());
+        assert!(!dispatcher.handles_mut(&TypeId::of::<(u8, u16)>()));
+        assert!(!dispatcher.dispatch_mut(AnyMut::erase(&mut data[1])));
+        assert!(dispatcher.handles(&TypeId::of::<(u8, u16)>()));
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{AnyMut, AnyRef};
+use alloc::boxed::Box;
+use core::{
+    any::{type_name, TypeId},
+    fmt,
+};
+
+/// A function whose argument and return types are only checked at runtime.
+///
+/// An `ErasedFn` is built from a regular closure taking up to eight arguments by reference. It is called
+/// with a slice of erased arguments and an erased return slot, and checks all of their types against
+/// its signature before calling the closure.
+///
+/// ```
+/// let add = sashay::ErasedFn::new(|a: &i32, b: &i32| a + b);
+/// assert_eq!(add.arity(), 2);
+///
+/// let (a, b) = (3, 4);
+/// let mut sum = 0;
+///
+/// add.call(
+///     &[sashay::AnyRef::erase(&a), sashay::AnyRef::erase(&b)],
+///     sashay::AnyMut::erase(&mut sum),
+/// ).unwrap();
+///
+/// assert_eq!(sum, 7);
+///
+/// // Passing the wrong types results in an error
+/// let error = add.call(&[sashay::AnyRef::erase(&a)], sashay::AnyMut::erase(&mut sum)).unwrap_err();
+/// assert_eq!(error.to_string(), "expected 2 arguments, but got 1");
+/// ```
+pub struct ErasedFn {
+    /// The types of the arguments
+    params: Box<[ParamType]>,
+
+    /// The return type
+    output: ParamType,
+
+    /// The wrapped closure
+    ///
+    /// This assumes the argument and return types have already been checked
+    call: Box<ErasedCall>,
+}
+
+/// A closure taking erased arguments and an erased output slot
+type ErasedCall = dyn Fn(&[AnyRef<'_>], AnyMut<'_>);
+
+impl ErasedFn {
+    /// Erase the argument and return types of a closure.
+    ///
+    /// The closure has to take all of its arguments by reference.
+    pub fn new<Args>(function: impl IntoErasedFn<Args>) -> Self {
+        function.into_erased_fn()
+    }
+
+    /// Call the function.
+    ///
+    /// The arguments are checked against the function's parameters, and `output` is checked against
+    /// the return type. If any of the types mismatch, or the number of arguments is wrong, an error is
+    /// returned and the function is not called. Otherwise, the function's result is written into `output`.
+    pub fn call(&self, args: &[AnyRef<'_>], output: AnyMut<'_>) -> Result<(), CallError> {
+        if args.len() != self.params.len() {
+            return Err(CallError::Arity {
+                expected: self.params.len(),
+                found: args.len(),
+            });
+        }
+
+        for (index, (arg, param)) in args.iter().zip(self.params.iter()).enumerate() {
+            if arg.type_id() != param.type_id() {
+                return Err(CallError::ArgumentType {
+                    index,
+                    expected: *param,
+                    found: *arg.type_id(),
+                });
+            }
+        }
+
+        if output.type_id() != self.output.type_id() {
+            return Err(CallError::OutputType {
+                expected: self.output,
+                found: *output.type_id(),
+            });
+        }
+
+        (self.call)(args, output);
+        Ok(())
+    }
+
+    /// The types of the function's parameters.
+    pub fn params(&self) -> &[ParamType] {
+        &self.params
+    }
+
+    /// The function's return type.
+    pub const fn output(&self) -> &ParamType {
+        &self.output
+    }
+
+    /// The number of arguments the function takes.
+    pub fn arity(&self) -> usize {
+        self.params.len()
+    }
+}
+
+impl fmt::Debug for ErasedFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErasedFn")
+            .field("params", &self.params)
+            .field("output", &self.output)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The type of a parameter or return value of an [`ErasedFn`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParamType {
+    type_id: TypeId,
+    name: &'static str,
+}
+
+impl ParamType {
+    /// The parameter type of `T`.
+    pub fn of<T: 'static>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+        }
+    }
+
+    /// A unique type id representing the type `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        &self.type_id
+    }
+
+    /// The name of the type `T`, as given by [`type_name()`](core::any::type_name).
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// The reasons calling an [`ErasedFn`] can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallError {
+    /// The number of arguments didn't match the number of parameters
+    Arity { expected: usize, found: usize },
+
+    /// The argument at `index` was of the wrong type
+    ArgumentType {
+        index: usize,
+        expected: ParamType,
+        found: TypeId,
+    },
+
+    /// The output slot was of the wrong type
+    OutputType { expected: ParamType, found: TypeId },
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Arity { expected, found } => {
+                write!(f, "expected {expected} arguments, but got {found}")
+            }
+            Self::ArgumentType {
+                index, expected, ..
+            } => write!(f, "expected argument {index} to be a `{}`", expected.name()),
+            Self::OutputType { expected, .. } => {
+                write!(f, "expected the output to be a `{}`", expected.name())
+            }
+        }
+    }
+}
+
+impl core::error::Error for CallError {}
+
+/// Closures that can be turned into an [`ErasedFn`].
+///
+/// This is implemented for all closures taking up to eight arguments by reference, where `Args` is
+/// the tuple of argument types.
+pub trait IntoErasedFn<Args> {
+    /// Erase the argument and return types of the closure.
+    fn into_erased_fn(self) -> ErasedFn;
+}
+
+macro_rules! impl_into_erased_fn {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg),*> IntoErasedFn<($($arg,)*)> for F
+        where
+            F: Fn($(&$arg),*) -> R + 'static,
+            R: 'static,
+            $($arg: 'static,)*
+        {
+            fn into_erased_fn(self) -> ErasedFn {
+                ErasedFn {
+                    params: Box::new([$(ParamType::of::<$arg>()),*]),
+                    output: ParamType::of::<R>(),
+                    #[allow(unused_variables, unused_mut)]
+                    call: Box::new(move |args: &[AnyRef<'_>], mut output: AnyMut<'_>| {
+                        let mut args = args.iter();
+
+                        // SAFETY: `ErasedFn::call()` has checked the number of arguments and all type ids
+                        unsafe {
+                            let result = (self)($(&*args.next().unwrap().as_ptr().cast::<$arg>()),*);
+                            *output.as_mut_ptr().cast::<R>() = result;
+                        }
+                    }),
+                }
+            }
+        }
+    };
+}
+
+impl_into_erased_fn!();
+impl_into_erased_fn!(A);
+impl_into_erased_fn!(A, B);
+impl_into_erased_fn!(A, B, C);
+impl_into_erased_fn!(A, B, C, D);
+impl_into_erased_fn!(A, B, C, D, E);
+impl_into_erased_fn!(A, B, C, D, E, G);
+impl_into_erased_fn!(A, B, C, D, E, G, H);
+impl_into_erased_fn!(A, B, C, D, E, G, H, I);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn signature() {
+        let function =
+            ErasedFn::new(|a: &(u8, u16), b: &String| -> bool { a.0 as usize == b.len() });
+
+        assert_eq!(function.arity(), 2);
+        assert_eq!(function.params()[0], ParamType::of::<(u8, u16)>());
+        assert_eq!(function.params()[1].name(), type_name::<String>());
+        assert_eq!(function.output().type_id(), &TypeId::of::<bool>());
+
+        let nullary = ErasedFn::new(|| {});
+        assert_eq!(nullary.arity(), 0);
+        assert_eq!(nullary.call(&[], AnyMut::erase(&mut ())), Ok(()));
+    }
+
+    #[test]
+    fn type_checks() {
+        let function =
+            ErasedFn::new(|a: &(u8, u16), b: &String| -> bool { a.0 as usize == b.len() });
+
+        let a = (2u8, 0u16);
+        let b = String::from("hi");
+        let mut output = false;
+
+        assert_eq!(
+            function.call(
+                &[AnyRef::erase(&b), AnyRef::erase(&a)],
+                AnyMut::erase(&mut output)
+            ),
+            Err(CallError::ArgumentType {
+                index: 0,
+                expected: ParamType::of::<(u8, u16)>(),
+                found: TypeId::of::<String>()
+            })
+        );
+
+        assert_eq!(
+            function.call(
+                &[AnyRef::erase(&a), AnyRef::erase(&b)],
+                AnyMut::erase(&mut 0u8)
+            ),
+            Err(CallError::OutputType {
+                expected: ParamType::of::<bool>(),
+                found: TypeId::of::<u8>()
+            })
+        );
+
+        function
+            .call(
+                &[AnyRef::erase(&a), AnyRef::erase(&b)],
+                AnyMut::erase(&mut output),
+            )
+            .unwrap();
+        assert!(output);
+    }
+}
@@ -0,0 +1,416 @@
+use core::{
+    any::TypeId,
+    fmt, iter,
+    ops::{Index, IndexMut},
+    slice,
+};
+
+/// An immutable view of a selection of elements from a slice.
+///
+/// The selection is either a list of indices (which may repeat, and can be in any order), or a boolean
+/// mask with an entry for every element of the slice. Either way, the view behaves like a slice of only
+/// the selected elements.
+///
+/// Usually created by unerasing an [`AnyGatherRef`](crate::AnyGatherRef).
+///
+/// ```
+/// let voices : [f32; 4] = [0.1, 0.2, 0.3, 0.4];
+///
+/// let active = sashay::GatherRef::new(voices.as_slice(), &[3, 1]).unwrap();
+/// assert_eq!(active.len(), 2);
+/// assert_eq!(active[0], 0.4);
+///
+/// let masked = sashay::GatherRef::from_mask(voices.as_slice(), &[true, false, true, false]).unwrap();
+/// assert!(masked.iter().eq(&[0.1, 0.3]));
+/// ```
+pub struct GatherRef<'a, T> {
+    /// The slice the elements are selected from
+    slice: &'a [T],
+
+    /// Which elements of the slice are part of the view
+    selection: Selection<'a>,
+}
+
+impl<'a, T> GatherRef<'a, T> {
+    /// Select elements from a slice by their index.
+    ///
+    /// If any of the indices is out of bounds, you get `None`.
+    pub fn new(slice: &'a [T], indices: &'a [usize]) -> Option<Self> {
+        let selection = Selection::indices(indices, slice.len(), false)?;
+        Some(Self { slice, selection })
+    }
+
+    /// Select elements from a slice using a mask, which has an entry for every element.
+    ///
+    /// If the mask has a different length than the slice, you get `None`.
+    pub fn from_mask(slice: &'a [T], mask: &'a [bool]) -> Option<Self> {
+        let selection = Selection::mask(mask, slice.len())?;
+        Some(Self { slice, selection })
+    }
+
+    /// Retrieve a reference to one of the selected elements.
+    ///
+    /// For masks, this has to scan the mask to find the element.
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        self.slice.get(self.selection.get(index)?)
+    }
+
+    /// Iterate over all selected elements, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> {
+        let slice = self.slice;
+        self.selection.iter().map(move |index| &slice[index])
+    }
+
+    /// Iterate over the indices of the selected elements within the slice.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + 'a {
+        self.selection.iter()
+    }
+
+    /// How many elements are selected?
+    pub const fn len(&self) -> usize {
+        self.selection.len()
+    }
+
+    /// Are any elements selected at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Access the slice the elements are selected from.
+    pub const fn source(&self) -> &'a [T] {
+        self.slice
+    }
+
+    /// Assemble a view from a selection whose indices are all within the slice
+    pub(crate) const fn from_parts(slice: &'a [T], selection: Selection<'a>) -> Self {
+        Self { slice, selection }
+    }
+
+    /// The selection, for erasing the view
+    pub(crate) const fn selection(&self) -> Selection<'a> {
+        self.selection
+    }
+}
+
+impl<T> Clone for GatherRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GatherRef<'_, T> {}
+
+impl<T> Index<usize> for GatherRef<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for GatherRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A mutable view of a selection of elements from a slice.
+///
+/// Unlike [`GatherRef`], every element can be selected at most once, so that no element is
+/// aliased mutably.
+///
+/// Usually created by unerasing an [`AnyGatherMut`](crate::AnyGatherMut).
+///
+/// ```
+/// let mut voices : [f32; 4] = [0.1, 0.2, 0.3, 0.4];
+///
+/// let mut active = sashay::GatherMut::new(voices.as_mut_slice(), &[3, 1]).unwrap();
+/// active.scatter_from(&[1.0, 2.0]);
+///
+/// assert_eq!(voices, [0.1, 2.0, 0.3, 1.0]);
+/// ```
+pub struct GatherMut<'a, T> {
+    /// The slice the elements are selected from
+    slice: &'a mut [T],
+
+    /// Which elements of the slice are part of the view, which are all unique
+    selection: Selection<'a>,
+}
+
+impl<'a, T> GatherMut<'a, T> {
+    /// Select elements from a mutable slice by their index.
+    ///
+    /// If any of the indices is out of bounds, or if any index occurs more than once, you get `None`.
+    pub fn new(slice: &'a mut [T], indices: &'a [usize]) -> Option<Self> {
+        let selection = Selection::indices(indices, slice.len(), true)?;
+        Some(Self { slice, selection })
+    }
+
+    /// Select elements from a mutable slice using a mask, which has an entry for every element.
+    ///
+    /// If the mask has a different length than the slice, you get `None`.
+    pub fn from_mask(slice: &'a mut [T], mask: &'a [bool]) -> Option<Self> {
+        let selection = Selection::mask(mask, slice.len())?;
+        Some(Self { slice, selection })
+    }
+
+    /// Borrow this mutable view as an immutable one.
+    pub fn borrow(&self) -> GatherRef<'_, T> {
+        GatherRef {
+            slice: self.slice,
+            selection: self.selection,
+        }
+    }
+
+    /// Retrieve a reference to one of the selected elements.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.borrow().get(index)
+    }
+
+    /// Retrieve a mutable reference to one of the selected elements.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.slice.get_mut(self.selection.get(index)?)
+    }
+
+    /// Iterate over all selected elements, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.borrow().iter()
+    }
+
+    /// Iterate mutably over all selected elements, in order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        let ptr = self.slice.as_mut_ptr();
+
+        // SAFETY:
+        // - The selection only contains indices within the slice
+        // - Every index is selected at most once, so the references are unique
+        self.selection
+            .iter()
+            .map(move |index| unsafe { &mut *ptr.add(index) })
+    }
+
+    /// Iterate over the indices of the selected elements within the slice.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + 'a {
+        self.selection.iter()
+    }
+
+    /// Copy values into the selected elements, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` has a different length than the selection, just like
+    /// [`copy_from_slice()`](https://doc.rust-lang.org/std/primitive.slice.html#method.copy_from_slice).
+    pub fn scatter_from(&mut self, values: &[T])
+    where
+        T: Copy,
+    {
+        assert_eq!(
+            values.len(),
+            self.len(),
+            "source slice length does not match the selection"
+        );
+
+        for (element, value) in self.iter_mut().zip(values) {
+            *element = *value;
+        }
+    }
+
+    /// How many elements are selected?
+    pub const fn len(&self) -> usize {
+        self.selection.len()
+    }
+
+    /// Are any elements selected at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Assemble a view from a selection whose indices are all unique and within the slice
+    pub(crate) fn from_parts(slice: &'a mut [T], selection: Selection<'a>) -> Self {
+        Self { slice, selection }
+    }
+
+    /// The selection, for erasing the view
+    pub(crate) const fn selection(&self) -> Selection<'a> {
+        self.selection
+    }
+
+    /// Give up the view, returning the slice the elements are selected from
+    pub(crate) fn into_source(self) -> &'a mut [T] {
+        self.slice
+    }
+}
+
+impl<T> Index<usize> for GatherMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T> IndexMut<usize> for GatherMut<'_, T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("index out of bounds")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for GatherMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Which elements of a slice are part of a gather view
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Selection<'a> {
+    /// The selected indices, or a mask over all elements
+    picks: Picks<'a>,
+
+    /// The number of selected elements
+    len: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Picks<'a> {
+    Indices(&'a [usize]),
+    Mask(&'a [bool]),
+}
+
+impl<'a> Selection<'a> {
+    /// Select elements by index, if all are below `source_len` and, if required, unique
+    pub fn indices(indices: &'a [usize], source_len: usize, unique: bool) -> Option<Self> {
+        if indices.iter().any(|&index| index >= source_len) {
+            return None;
+        }
+
+        // Sorted indices are the common case, and are trivially unique
+        let sorted = indices.windows(2).all(|pair| pair[0] < pair[1]);
+        if unique && !sorted {
+            for (position, index) in indices.iter().enumerate() {
+                if indices[position + 1..].contains(index) {
+                    return None;
+                }
+            }
+        }
+
+        Some(Self {
+            picks: Picks::Indices(indices),
+            len: indices.len(),
+        })
+    }
+
+    /// Select elements by mask, if it covers exactly `source_len` elements
+    pub fn mask(mask: &'a [bool], source_len: usize) -> Option<Self> {
+        (mask.len() == source_len).then(|| Self {
+            picks: Picks::Mask(mask),
+            len: mask.iter().filter(|&&selected| selected).count(),
+        })
+    }
+
+    /// The number of selected elements
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The index in the source of the `n`-th selected element
+    pub fn get(&self, n: usize) -> Option<usize> {
+        match self.picks {
+            Picks::Indices(indices) => indices.get(n).copied(),
+            Picks::Mask(mask) => SelectedIndices::Mask(mask.iter().enumerate()).nth(n),
+        }
+    }
+
+    /// Iterate over the indices in the source of all selected elements
+    pub fn iter(&self) -> SelectedIndices<'a> {
+        match self.picks {
+            Picks::Indices(indices) => SelectedIndices::Indices(indices.iter()),
+            Picks::Mask(mask) => SelectedIndices::Mask(mask.iter().enumerate()),
+        }
+    }
+}
+
+/// An iterator over the selected indices of a [`Selection`]
+pub(crate) enum SelectedIndices<'a> {
+    Indices(slice::Iter<'a, usize>),
+    Mask(iter::Enumerate<slice::Iter<'a, bool>>),
+}
+
+impl Iterator for SelectedIndices<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        match self {
+            Self::Indices(indices) => indices.next().copied(),
+            Self::Mask(mask) => mask.find_map(|(index, &selected)| selected.then_some(index)),
+        }
+    }
+}
+
+/// The reasons writing into a gather view can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GatherError {
+    /// The values have a different type than the selected elements
+    TypeMismatch { expected: TypeId, found: TypeId },
+
+    /// The number of values doesn't match the number of selected elements
+    LengthMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for GatherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected a slice of {expected:?}, but got {found:?}")
+            }
+            Self::LengthMismatch { expected, found } => {
+                write!(
+                    f,
+                    "expected a slice of {expected} elements, but got {found}"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for GatherError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection() {
+        let indices = Selection::indices(&[4, 0, 4], 5, false).unwrap();
+        assert_eq!(indices.len(), 3);
+        assert_eq!(indices.get(2), Some(4));
+        assert!(indices.iter().eq([4, 0, 4]));
+
+        assert!(Selection::indices(&[4, 0, 4], 5, true).is_none());
+        assert!(Selection::indices(&[4, 0, 3], 5, true).is_some());
+        assert!(Selection::indices(&[5], 5, false).is_none());
+
+        let mask = Selection::mask(&[false, true, true, false], 4).unwrap();
+        assert_eq!(mask.len(), 2);
+        assert_eq!(mask.get(1), Some(2));
+        assert_eq!(mask.get(2), None);
+        assert!(mask.iter().eq([1, 2]));
+        assert!(Selection::mask(&[true], 4).is_none());
+    }
+
+    #[test]
+    fn typed() {
+        let mut data = [0u16, 1, 2, 3, 4];
+
+        let view = GatherRef::new(data.as_slice(), &[2, 2, 0]).unwrap();
+        assert!(view.iter().eq(&[2, 2, 0]));
+        assert!(GatherMut::new(data.as_mut_slice(), &[2, 2]).is_none());
+
+        let mut view =
+            GatherMut::from_mask(data.as_mut_slice(), &[true, false, false, true, false]).unwrap();
+        view[1] = 30;
+        view.iter_mut().for_each(|value| *value += 1);
+        assert!(view.indices().eq([0, 3]));
+        assert_eq!(data, [1, 1, 2, 31, 4]);
+    }
+}
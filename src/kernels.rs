@@ -0,0 +1,286 @@
+//! Arithmetic on erased numeric slices.
+//!
+//! These kernels work on [`AnySliceRef`] and [`AnySliceMut`] of any primitive integer or float type,
+//! for when the element type of a buffer is only known at runtime (such as when mixing audio). Each
+//! kernel looks up the element type once, and then runs a typed loop over the whole slice.
+//!
+//! Slices of any other element type result in [`NumericError::Unsupported`]. Kernels taking two slices
+//! require both to have the same element type and length.
+//!
+//! ```
+//! use sashay::{kernels, AnySliceMut, AnySliceRef};
+//!
+//! let mut mix = [0i16; 3];
+//! let voice = [100i16, -200, 300];
+//!
+//! let mut destination = AnySliceMut::erase(mix.as_mut_slice());
+//! let source = AnySliceRef::erase(voice.as_slice());
+//!
+//! kernels::add_assign(&mut destination, &source).unwrap();
+//! kernels::fma(&mut destination, &source, 0.5).unwrap();
+//! assert_eq!(mix, [150, -300, 450]);
+//!
+//! assert_eq!(kernels::peak_abs(&AnySliceRef::erase(mix.as_slice())), Ok(450.0));
+//! ```
+
+use crate::{
+    numeric::{self, Numeric},
+    AnySliceMut, AnySliceRef, NumericError, PrimitiveSlice, PrimitiveSliceMut,
+};
+
+/// Match a primitive slice on its numeric variants, running `$body` with the typed slice bound to `$typed`
+macro_rules! dispatch {
+    ($primitive:expr, $kind:ident, $type_id:expr, |$typed:ident| $body:expr) => {
+        dispatch!(@arms $primitive, $kind, $type_id, $typed, $body;
+            I8, I16, I32, I64, I128, Isize, U8, U16, U32, U64, U128, Usize, F32, F64)
+    };
+
+    (@arms $primitive:expr, $kind:ident, $type_id:expr, $typed:ident, $body:expr; $($variant:ident),+) => {
+        match $primitive {
+            $(Some($kind::$variant($typed)) => $body,)+
+            _ => Err(NumericError::Unsupported($type_id)),
+        }
+    };
+}
+
+/// Add the elements of `source` to those of `destination`.
+///
+/// Integers saturate instead of overflowing.
+pub fn add_assign(
+    destination: &mut AnySliceMut<'_>,
+    source: &AnySliceRef<'_>,
+) -> Result<(), NumericError> {
+    check_len(destination, source)?;
+
+    let type_id = *destination.type_id();
+    dispatch!(
+        destination.primitive_mut(),
+        PrimitiveSliceMut,
+        type_id,
+        |destination| zip_with(destination, source, |d, s| d.mix(s))
+    )
+}
+
+/// Multiply the elements of `destination` by `gain`.
+///
+/// Integer results are rounded to the nearest value, and saturate instead of overflowing.
+pub fn mul_assign_scalar(destination: &mut AnySliceMut<'_>, gain: f64) -> Result<(), NumericError> {
+    let type_id = *destination.type_id();
+    dispatch!(
+        destination.primitive_mut(),
+        PrimitiveSliceMut,
+        type_id,
+        |destination| {
+            destination.iter_mut().for_each(|d| *d = d.scale(gain));
+            Ok(())
+        }
+    )
+}
+
+/// Add the elements of `source`, multiplied by `gain`, to those of `destination`.
+///
+/// Integer products are rounded to the nearest value, and integers saturate instead of overflowing.
+pub fn fma(
+    destination: &mut AnySliceMut<'_>,
+    source: &AnySliceRef<'_>,
+    gain: f64,
+) -> Result<(), NumericError> {
+    check_len(destination, source)?;
+
+    let type_id = *destination.type_id();
+    dispatch!(
+        destination.primitive_mut(),
+        PrimitiveSliceMut,
+        type_id,
+        |destination| zip_with(destination, source, |d, s| d.mix(s.scale(gain)))
+    )
+}
+
+/// Set all elements of `destination` to zero.
+pub fn fill_zero(destination: &mut AnySliceMut<'_>) -> Result<(), NumericError> {
+    let type_id = *destination.type_id();
+    dispatch!(
+        destination.primitive_mut(),
+        PrimitiveSliceMut,
+        type_id,
+        |destination| {
+            fill(destination);
+            Ok(())
+        }
+    )
+}
+
+/// The sum of all elements, or `0.0` for an empty slice.
+pub fn sum(source: &AnySliceRef<'_>) -> Result<f64, NumericError> {
+    reduce(source, 0.0, |sum, value| sum + value)
+}
+
+/// The smallest element, or `f64::INFINITY` for an empty slice.
+pub fn min(source: &AnySliceRef<'_>) -> Result<f64, NumericError> {
+    reduce(source, f64::INFINITY, f64::min)
+}
+
+/// The largest element, or `f64::NEG_INFINITY` for an empty slice.
+pub fn max(source: &AnySliceRef<'_>) -> Result<f64, NumericError> {
+    reduce(source, f64::NEG_INFINITY, f64::max)
+}
+
+/// The largest absolute value of all elements, or `0.0` for an empty slice.
+pub fn peak_abs(source: &AnySliceRef<'_>) -> Result<f64, NumericError> {
+    reduce(source, 0.0, |peak, value| peak.max(value.abs()))
+}
+
+/// The root mean square of all elements, or `0.0` for an empty slice.
+pub fn rms(source: &AnySliceRef<'_>) -> Result<f64, NumericError> {
+    let squares = reduce(source, 0.0, |sum, value| sum + value * value)?;
+
+    match source.len() {
+        0 => Ok(0.0),
+        len => Ok(numeric::sqrt(squares / len as f64)),
+    }
+}
+
+/// Make sure two slices have the same number of elements
+fn check_len(destination: &AnySliceMut<'_>, source: &AnySliceRef<'_>) -> Result<(), NumericError> {
+    if source.len() != destination.len() {
+        return Err(NumericError::LengthMismatch {
+            expected: destination.len(),
+            found: source.len(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Combine every element of `destination` with the element of `source` at the same index
+///
+/// The lengths are expected to have been checked already.
+fn zip_with<T: Numeric>(
+    destination: &mut [T],
+    source: &AnySliceRef<'_>,
+    combine: impl Fn(T, T) -> T,
+) -> Result<(), NumericError> {
+    let Some(source) = source.unerase::<T>() else {
+        return Err(NumericError::TypeMismatch {
+            expected: core::any::TypeId::of::<T>(),
+            found: *source.type_id(),
+        });
+    };
+
+    destination
+        .iter_mut()
+        .zip(source)
+        .for_each(|(d, s)| *d = combine(*d, *s));
+
+    Ok(())
+}
+
+/// Overwrite all elements with zero
+fn fill<T: Numeric>(destination: &mut [T]) {
+    destination.fill(T::ZERO);
+}
+
+/// Fold all elements of a numeric slice, converted to `f64`
+fn reduce(
+    source: &AnySliceRef<'_>,
+    init: f64,
+    fold: impl Fn(f64, f64) -> f64,
+) -> Result<f64, NumericError> {
+    dispatch!(
+        source.primitive(),
+        PrimitiveSlice,
+        *source.type_id(),
+        |source| {
+            Ok(source
+                .iter()
+                .fold(init, |acc, value| fold(acc, value.to_f64())))
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::any::TypeId;
+
+    #[test]
+    fn assign() {
+        let mut data = [100u8, 200, 250];
+        let mut destination = AnySliceMut::erase(data.as_mut_slice());
+
+        add_assign(
+            &mut destination,
+            &AnySliceRef::erase([10u8, 10, 10].as_slice()),
+        )
+        .unwrap();
+        assert_eq!(data, [110, 210, 255]);
+
+        let mut destination = AnySliceMut::erase(data.as_mut_slice());
+        mul_assign_scalar(&mut destination, 0.5).unwrap();
+        assert_eq!(data, [55, 105, 128]);
+
+        let mut data = [1.0f32, 2.0];
+        let mut destination = AnySliceMut::erase(data.as_mut_slice());
+        fma(
+            &mut destination,
+            &AnySliceRef::erase([4.0f32, -4.0].as_slice()),
+            0.25,
+        )
+        .unwrap();
+        assert_eq!(data, [2.0, 1.0]);
+
+        let mut destination = AnySliceMut::erase(data.as_mut_slice());
+        fill_zero(&mut destination).unwrap();
+
+        assert_eq!(
+            add_assign(
+                &mut destination,
+                &AnySliceRef::erase([1.0f64, 2.0].as_slice())
+            ),
+            Err(NumericError::TypeMismatch {
+                expected: TypeId::of::<f32>(),
+                found: TypeId::of::<f64>()
+            })
+        );
+        assert_eq!(
+            fma(
+                &mut destination,
+                &AnySliceRef::erase([1.0f32].as_slice()),
+                1.0
+            ),
+            Err(NumericError::LengthMismatch {
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(data, [0.0, 0.0]);
+
+        assert_eq!(
+            fill_zero(&mut AnySliceMut::erase([true].as_mut_slice())),
+            Err(NumericError::Unsupported(TypeId::of::<bool>()))
+        );
+    }
+
+    #[test]
+    fn reductions() {
+        let data = [3i32, -4, 0, 1];
+        let source = AnySliceRef::erase(data.as_slice());
+
+        assert_eq!(sum(&source), Ok(0.0));
+        assert_eq!(min(&source), Ok(-4.0));
+        assert_eq!(max(&source), Ok(3.0));
+        assert_eq!(peak_abs(&source), Ok(4.0));
+        let root_mean_square = rms(&source).unwrap();
+        assert!((root_mean_square * root_mean_square - 6.5).abs() < 1e-12);
+        assert!((numeric::sqrt(2.0) - core::f64::consts::SQRT_2).abs() < 1e-15);
+
+        let empty = AnySliceRef::erase::<f32>(&[]);
+        assert_eq!(rms(&empty), Ok(0.0));
+        assert_eq!(min(&empty), Ok(f64::INFINITY));
+
+        assert_eq!(
+            sum(&AnySliceRef::erase(['a'].as_slice())),
+            Err(NumericError::Unsupported(TypeId::of::<char>()))
+        );
+    }
+}
@@ -34,19 +34,122 @@
 //! assert_eq!(erased.get(2).unwrap().unerase::<i32>(), Some(&2));
 //! ```
 //!
+//! ## Kernels
+//!
+//! Unlike the rest of the crate, the arithmetic on erased numeric slices lives in its own public
+//! [`kernels`] module instead of at the crate root. Their names (such as `sum`, `min` and `max`) only
+//! make sense with the module in front, as in `kernels::sum(&slice)`.
+//!
 //! ## Dependencies
 //!
-//! `sashay` is `#![no_std]` and has 0 dependencies.
+//! `sashay` is `#![no_std]` and has 0 dependencies. The containers that need heap allocation
+//! (such as [`AnyResources`]) live behind the `alloc` feature, which is enabled by default.
 
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod any_array_vec;
+#[cfg(feature = "alloc")]
+mod any_buffer_pool;
+mod any_gather_mut;
+mod any_gather_ref;
+mod any_list;
 mod any_mut;
 mod any_ref;
+#[cfg(feature = "alloc")]
+mod any_resources;
+mod any_ring_buffer;
+mod any_slice_2d_mut;
+mod any_slice_2d_ref;
 mod any_slice_mut;
 mod any_slice_ref;
+mod any_slice_uninit;
+#[cfg(feature = "alloc")]
+mod any_slot_map;
+#[cfg(feature = "alloc")]
+mod any_sparse_set;
+mod any_spsc_queue;
+mod any_strided_mut;
+mod any_strided_ref;
+#[cfg(feature = "alloc")]
+mod any_table;
+mod any_tensor_mut;
+mod any_tensor_ref;
+#[cfg(feature = "alloc")]
+mod any_vec;
+mod convert;
+#[cfg(feature = "alloc")]
+mod erased_dispatcher;
+#[cfg(feature = "alloc")]
+mod erased_fn;
+mod gather;
+pub mod kernels;
+mod match_erased;
+mod numeric;
+mod planar;
+mod primitive;
 mod range;
+mod slice_2d;
+mod strided;
+mod tensor;
+#[cfg(test)]
+mod testing;
+mod transparent;
+#[cfg(feature = "alloc")]
+mod transparent_registry;
+mod type_info;
+mod type_set;
+mod zip;
 
+pub use any_array_vec::AnyArrayVec;
+#[cfg(feature = "alloc")]
+pub use any_buffer_pool::{AnyBufferPool, AnyPooledBuffer};
+pub use any_gather_mut::AnyGatherMut;
+pub use any_gather_ref::AnyGatherRef;
+pub use any_list::{AnyEntry, AnyList, AnyListGroup};
 pub use any_mut::AnyMut;
 pub use any_ref::AnyRef;
+#[cfg(feature = "alloc")]
+pub use any_resources::{
+    AnyResourceMut, AnyResourceRef, AnyResources, BorrowError, ResourceMut, ResourceRef,
+};
+pub use any_ring_buffer::{AnyRingBuffer, RingBufferError};
+pub use any_slice_2d_mut::AnySlice2dMut;
+pub use any_slice_2d_ref::AnySlice2dRef;
 pub use any_slice_mut::AnySliceMut;
 pub use any_slice_ref::AnySliceRef;
+pub use any_slice_uninit::{AnySliceUninit, UninitError};
+#[cfg(feature = "alloc")]
+pub use any_slot_map::{AnySlotMap, Key, SlotMapError};
+#[cfg(feature = "alloc")]
+pub use any_sparse_set::{AnySparseSet, SparseSetError};
+pub use any_spsc_queue::{AnyConsumer, AnyProducer, AnySpscQueue};
+pub use any_strided_mut::AnyStridedMut;
+pub use any_strided_ref::AnyStridedRef;
+#[cfg(feature = "alloc")]
+pub use any_table::{AnyTable, TableError};
+pub use any_tensor_mut::AnyTensorMut;
+pub use any_tensor_ref::AnyTensorRef;
+#[cfg(feature = "alloc")]
+pub use any_vec::AnyVec;
+pub use convert::{convert_into, Conversion};
+#[cfg(feature = "alloc")]
+pub use erased_dispatcher::ErasedDispatcher;
+#[cfg(feature = "alloc")]
+pub use erased_fn::{CallError, ErasedFn, IntoErasedFn, ParamType};
+pub use gather::{GatherError, GatherMut, GatherRef};
+#[doc(hidden)]
+pub use match_erased::MatchErased;
+pub use numeric::NumericError;
+pub use planar::{swap_deinterleave, swap_interleave, AnyPlanarMut, AnyPlanarRef, PlanarError};
+pub use primitive::{PrimitiveKind, PrimitiveMut, PrimitiveRef, PrimitiveSlice, PrimitiveSliceMut};
+pub use slice_2d::{Slice2dMut, Slice2dRef};
+pub use strided::{StridedMut, StridedRef};
+pub use transparent::TransparentAs;
+#[cfg(feature = "alloc")]
+pub use transparent_registry::TransparentRegistry;
+pub use type_info::TypeInfo;
+pub use type_set::{AnySetSliceMut, AnySetSliceRef, Member, TypeSet};
+pub use zip::{unerase_zip, UneraseZip, ZipElement, ZipError, ZipIter};
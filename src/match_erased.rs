@@ -0,0 +1,214 @@
+use crate::{AnyMut, AnyRef, AnySliceMut, AnySliceRef};
+use core::{
+    any::TypeId,
+    slice::{from_raw_parts, from_raw_parts_mut},
+};
+
+/// Match an erased reference or slice against a list of candidate types.
+///
+/// Every arm has the form `T as binding => expression`, and the match has to end with a `_ => fallback` arm.
+/// The first arm whose type matches the erased type is evaluated, with `binding` set to the unerased reference.
+/// Each arm costs a single [`TypeId`] comparison.
+///
+/// The macro works with [`AnyRef`](crate::AnyRef), [`AnyMut`](crate::AnyMut), [`AnySliceRef`](crate::AnySliceRef)
+/// and [`AnySliceMut`](crate::AnySliceMut), which unerase to `&T`, `&mut T`, `&[T]` and `&mut [T]` respectively.
+/// The erased value is consumed, so pass `&mut any` (or call `borrow_mut()`) if you want to keep using it.
+///
+/// ```
+/// fn describe(any: sashay::AnyRef) -> String {
+///     sashay::match_erased!(any, {
+///         i32 as x => format!("an i32 of {x}"),
+///         char as c => format!("the character {c}"),
+///         _ => String::from("something else"),
+///     })
+/// }
+///
+/// assert_eq!(describe(sashay::AnyRef::erase(&7)), "an i32 of 7");
+/// assert_eq!(describe(sashay::AnyRef::erase(&'🦀')), "the character 🦀");
+/// assert_eq!(describe(sashay::AnyRef::erase(&true)), "something else");
+///
+/// // Mutable slices unerase to mutable slices
+/// let mut data = [1.0f32, 2.0];
+/// let mut any = sashay::AnySliceMut::erase(data.as_mut_slice());
+///
+/// sashay::match_erased!(&mut any, {
+///     f32 as slice => slice.fill(0.0),
+///     f64 as slice => slice.fill(0.0),
+///     _ => panic!("not a float slice"),
+/// });
+///
+/// assert_eq!(data, [0.0, 0.0]);
+/// ```
+#[macro_export]
+macro_rules! match_erased {
+    ($erased:expr, { $($arms:tt)* }) => {{
+        let erased = $erased;
+        let type_id = $crate::MatchErased::erased_type_id(&erased);
+        $crate::match_erased!(@arms erased, type_id; $($arms)*)
+    }};
+
+    (@arms $erased:ident, $type_id:ident; _ => $fallback:expr $(,)?) => {
+        $fallback
+    };
+
+    (@arms $erased:ident, $type_id:ident; $ty:ty as $binding:pat => $arm:expr, $($rest:tt)*) => {
+        if $type_id == ::core::any::TypeId::of::<$ty>() {
+            // SAFETY: We've just checked the erased type id against the one of `$ty`
+            let $binding = unsafe { $crate::MatchErased::unerase_unchecked::<$ty>($erased) };
+            $arm
+        } else {
+            $crate::match_erased!(@arms $erased, $type_id; $($rest)*)
+        }
+    };
+}
+
+/// The erased types that can be used with [`match_erased!`].
+///
+/// This is an implementation detail of the macro and not meant to be used directly.
+///
+/// # Safety
+///
+/// [`erased_type_id()`](MatchErased::erased_type_id) must return the type id of the original type of the
+/// erased value, because the macro calls [`unerase_unchecked()`](MatchErased::unerase_unchecked) based on it.
+#[doc(hidden)]
+pub unsafe trait MatchErased<'a> {
+    /// The unerased type, given the original type `T`
+    type Unerased<T: 'static>;
+
+    /// The type id of the erased value
+    fn erased_type_id(&self) -> TypeId;
+
+    /// Unerase without checking the type id
+    ///
+    /// # Safety
+    ///
+    /// `T` must be the original type of the erased value
+    unsafe fn unerase_unchecked<T: 'static>(self) -> Self::Unerased<T>;
+}
+
+// SAFETY: The type id is the one the value was erased with
+unsafe impl<'a> MatchErased<'a> for AnyRef<'a> {
+    type Unerased<T: 'static> = &'a T;
+
+    fn erased_type_id(&self) -> TypeId {
+        *self.type_id()
+    }
+
+    unsafe fn unerase_unchecked<T: 'static>(self) -> &'a T {
+        &*self.as_ptr().cast::<T>()
+    }
+}
+
+// SAFETY: See above
+unsafe impl<'a> MatchErased<'a> for AnyMut<'a> {
+    type Unerased<T: 'static> = &'a mut T;
+
+    fn erased_type_id(&self) -> TypeId {
+        *self.type_id()
+    }
+
+    unsafe fn unerase_unchecked<T: 'static>(mut self) -> &'a mut T {
+        &mut *self.as_mut_ptr().cast::<T>()
+    }
+}
+
+// SAFETY: See above
+unsafe impl<'a, 'b> MatchErased<'a> for &'a mut AnyMut<'b> {
+    type Unerased<T: 'static> = &'a mut T;
+
+    fn erased_type_id(&self) -> TypeId {
+        *self.type_id()
+    }
+
+    unsafe fn unerase_unchecked<T: 'static>(self) -> &'a mut T {
+        &mut *self.as_mut_ptr().cast::<T>()
+    }
+}
+
+// SAFETY: See above
+unsafe impl<'a> MatchErased<'a> for AnySliceRef<'a> {
+    type Unerased<T: 'static> = &'a [T];
+
+    fn erased_type_id(&self) -> TypeId {
+        *self.type_id()
+    }
+
+    unsafe fn unerase_unchecked<T: 'static>(self) -> &'a [T] {
+        from_raw_parts(self.as_ptr().cast::<T>(), self.len())
+    }
+}
+
+// SAFETY: See above
+unsafe impl<'a> MatchErased<'a> for AnySliceMut<'a> {
+    type Unerased<T: 'static> = &'a mut [T];
+
+    fn erased_type_id(&self) -> TypeId {
+        *self.type_id()
+    }
+
+    unsafe fn unerase_unchecked<T: 'static>(mut self) -> &'a mut [T] {
+        from_raw_parts_mut(self.as_mut_ptr().cast::<T>(), self.len())
+    }
+}
+
+// SAFETY: See above
+unsafe impl<'a, 'b> MatchErased<'a> for &'a mut AnySliceMut<'b> {
+    type Unerased<T: 'static> = &'a mut [T];
+
+    fn erased_type_id(&self) -> TypeId {
+        *self.type_id()
+    }
+
+    unsafe fn unerase_unchecked<T: 'static>(self) -> &'a mut [T] {
+        from_raw_parts_mut(self.as_mut_ptr().cast::<T>(), self.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn refs() {
+        let mut data = (1u8, 2u16);
+
+        let any = AnyRef::erase(&data);
+        let value = match_erased!(any, {
+            u8 as _ => 0,
+            (u8, u16) as (a, b) => *a as u16 + *b,
+            _ => 0,
+        });
+        assert_eq!(value, 3);
+
+        let mut any = AnyMut::erase(&mut data);
+        match_erased!(&mut any, {
+            (u8, u16) as data => data.0 = 5,
+            _ => unreachable!(),
+        });
+        let fallback = match_erased!(any, {
+            u8 as _ => false,
+            _ => true,
+        });
+        assert!(fallback);
+        assert_eq!(data, (5, 2));
+    }
+
+    #[test]
+    fn slices() {
+        let mut data = [(1u8, 2u16), (3u8, 4u16)];
+
+        let any = AnySliceRef::erase(data.as_slice());
+        assert_eq!(
+            match_erased!(any, { (u8, u16) as slice => slice.len(), _ => 0 }),
+            2
+        );
+
+        let any = AnySliceMut::erase(data.as_mut_slice());
+        match_erased!(any, {
+            u8 as _ => unreachable!(),
+            (u8, u16) as slice => slice.reverse(),
+            _ => unreachable!(),
+        });
+        assert_eq!(data, [(3u8, 4u16), (1u8, 2u16)]);
+    }
+}
@@ -0,0 +1,222 @@
+use core::{any::TypeId, fmt};
+
+/// The primitive integer and floating point types, with the operations the numeric functions need
+pub(crate) trait Numeric: Copy + PartialOrd + 'static {
+    /// The additive identity
+    const ZERO: Self;
+
+    /// Convert to `f64` using the `as` operator
+    fn to_f64(self) -> f64;
+
+    /// Add two values, saturating instead of overflowing for integers
+    fn mix(self, other: Self) -> Self;
+
+    /// Multiply by a gain, rounding and saturating for integers
+    fn scale(self, gain: f64) -> Self;
+
+    /// Scale into the range [-1, 1]
+    ///
+    /// Signed integers are divided by 2^(bits - 1), unsigned integers are treated as offset binary
+    /// (so their midpoint maps to 0), and floats are left as is.
+    fn to_normalized(self) -> f64;
+
+    /// The inverse of `to_normalized()`, saturating values outside of the range [-1, 1] for integers
+    fn from_normalized(value: f64) -> Self;
+}
+
+/// Conversion from one numeric type to another
+pub(crate) trait CastTo<D> {
+    /// Convert using the `as` operator
+    fn cast(self) -> D;
+
+    /// Convert, clamping values that don't fit to the destination's minimum or maximum
+    fn saturate(self) -> D;
+}
+
+macro_rules! impl_numeric {
+    (signed: $($ty:ty),+) => {
+        $(
+            impl Numeric for $ty {
+                impl_numeric!(@int $ty);
+
+                fn to_normalized(self) -> f64 {
+                    self as f64 / -(<$ty>::MIN as f64)
+                }
+
+                fn from_normalized(value: f64) -> Self {
+                    round(value * -(<$ty>::MIN as f64)) as $ty
+                }
+            }
+        )+
+    };
+
+    (unsigned: $($ty:ty),+) => {
+        $(
+            impl Numeric for $ty {
+                impl_numeric!(@int $ty);
+
+                fn to_normalized(self) -> f64 {
+                    let half = (<$ty>::MAX / 2) as f64 + 1.0;
+                    (self as f64 - half) / half
+                }
+
+                fn from_normalized(value: f64) -> Self {
+                    let half = (<$ty>::MAX / 2) as f64 + 1.0;
+                    round(value * half + half) as $ty
+                }
+            }
+        )+
+    };
+
+    (float: $($ty:ty),+) => {
+        $(
+            impl Numeric for $ty {
+                const ZERO: Self = 0.0;
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn mix(self, other: Self) -> Self {
+                    self + other
+                }
+
+                fn scale(self, gain: f64) -> Self {
+                    self * gain as $ty
+                }
+
+                fn to_normalized(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_normalized(value: f64) -> Self {
+                    value as $ty
+                }
+            }
+        )+
+    };
+
+    (@int $ty:ty) => {
+        const ZERO: Self = 0;
+
+        fn to_f64(self) -> f64 {
+            self as f64
+        }
+
+        fn mix(self, other: Self) -> Self {
+            self.saturating_add(other)
+        }
+
+        fn scale(self, gain: f64) -> Self {
+            round(self as f64 * gain) as $ty
+        }
+    };
+}
+
+impl_numeric!(signed: i8, i16, i32, i64, i128, isize);
+impl_numeric!(unsigned: u8, u16, u32, u64, u128, usize);
+impl_numeric!(float: f32, f64);
+
+macro_rules! impl_cast_to {
+    ($src_kind:ident: [$($src:ty),+] => $dst_kind:ident: $dst:tt) => {
+        $(impl_cast_to!(@src $src_kind $dst_kind $src => $dst);)+
+    };
+
+    (@src $src_kind:ident $dst_kind:ident $src:ty => [$($dst:ty),+]) => {
+        $(
+            impl CastTo<$dst> for $src {
+                #[allow(clippy::unnecessary_cast)]
+                fn cast(self) -> $dst {
+                    self as $dst
+                }
+
+                fn saturate(self) -> $dst {
+                    impl_cast_to!(@saturate $src_kind $dst_kind self => $dst)
+                }
+            }
+        )+
+    };
+
+    // Integers don't always fit, so fall back to the minimum or maximum
+    (@saturate int int $value:ident => $dst:ty) => {
+        <$dst>::try_from($value).unwrap_or(if $value > 0 { <$dst>::MAX } else { <$dst>::MIN })
+    };
+
+    // Casting to a smaller float would overflow to infinity
+    (@saturate float float $value:ident => $dst:ty) => {
+        ($value as f64).clamp(<$dst>::MIN as f64, <$dst>::MAX as f64) as $dst
+    };
+
+    // Float to int casts already saturate, and ints always fit into floats
+    (@saturate $src_kind:ident $dst_kind:ident $value:ident => $dst:ty) => {
+        $value as $dst
+    };
+}
+
+impl_cast_to!(int: [i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize] => int: [i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize]);
+impl_cast_to!(int: [i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize] => float: [f32, f64]);
+impl_cast_to!(float: [f32, f64] => int: [i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize]);
+impl_cast_to!(float: [f32, f64] => float: [f32, f64]);
+
+/// Round half away from zero
+///
+/// `f64::round()` is not available in `core`, but because the result is cast to an integer
+/// anyway, we can rely on the truncation of `as`.
+fn round(value: f64) -> f64 {
+    if value < 0.0 {
+        value - 0.5
+    } else {
+        value + 0.5
+    }
+}
+
+/// The square root of a value
+///
+/// `f64::sqrt()` is not available in `core`, so this uses a few Newton-Raphson iterations,
+/// starting from an estimate that halves the exponent.
+pub(crate) fn sqrt(value: f64) -> f64 {
+    if value.is_nan() || value < 0.0 {
+        return f64::NAN;
+    } else if value == 0.0 || value.is_infinite() {
+        return value;
+    }
+
+    let mut estimate = f64::from_bits((value.to_bits() >> 1) + (1023 << 51));
+    for _ in 0..6 {
+        estimate = 0.5 * (estimate + value / estimate);
+    }
+
+    estimate
+}
+
+/// The reasons a numeric operation on erased slices can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericError {
+    /// The element type is not a primitive integer or float
+    Unsupported(TypeId),
+
+    /// The slices have different element types
+    TypeMismatch { expected: TypeId, found: TypeId },
+
+    /// The slices have a different number of elements
+    LengthMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for NumericError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(type_id) => write!(f, "{type_id:?} is not a numeric type"),
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected a slice of {expected:?}, but got {found:?}")
+            }
+            Self::LengthMismatch { expected, found } => {
+                write!(
+                    f,
+                    "expected a slice of {expected} elements, but got {found}"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for NumericError {}
@@ -0,0 +1,427 @@
+use crate::{AnySliceMut, AnySliceRef};
+use core::{any::TypeId, fmt, mem::MaybeUninit, ptr};
+
+/// A group of type-erased immutable channels, stored planar.
+///
+/// Multichannel data (like stereo audio) can be laid out in two ways: interleaved, where the elements of
+/// all channels alternate within a single slice (`LRLRLR`), or planar, where every channel has its own
+/// slice (`LLL` and `RRR`). An `AnyPlanarRef` groups `N` erased slices of the same element type and length.
+///
+/// Interleaved data can be viewed per channel with [`AnySliceRef::interleaved_channel()`], and converted
+/// between layouts with [`swap_interleave()`] and [`swap_deinterleave()`].
+///
+/// ```
+/// let left : [f32; 3] = [0.0, 0.1, 0.2];
+/// let right : [f32; 3] = [1.0, 1.1, 1.2];
+///
+/// let planar = sashay::AnyPlanarRef::new([
+///     sashay::AnySliceRef::erase(left.as_slice()),
+///     sashay::AnySliceRef::erase(right.as_slice()),
+/// ]).unwrap();
+///
+/// assert_eq!(planar.frames(), 3);
+/// assert_eq!(planar.unerase::<f32>(), Some([left.as_slice(), right.as_slice()]));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AnyPlanarRef<'a, const N: usize> {
+    /// The channels, which all have the same element type and length
+    channels: [AnySliceRef<'a>; N],
+}
+
+impl<'a, const N: usize> AnyPlanarRef<'a, N> {
+    /// Group a number of channels.
+    ///
+    /// All channels must have the same element type and length, otherwise an error is returned.
+    /// There must be at least one channel, which is checked at compile time.
+    pub fn new(channels: [AnySliceRef<'a>; N]) -> Result<Self, PlanarError> {
+        const { assert!(N > 0, "a planar buffer needs at least one channel") };
+
+        check_channels(
+            channels
+                .each_ref()
+                .map(|channel| (*channel.type_id(), channel.len())),
+        )?;
+        Ok(Self { channels })
+    }
+
+    /// Unerase all channels back to immutable slices.
+    ///
+    /// If the original element type was `T`, the slices are returned. Otherwise, you get `None`.
+    pub fn unerase<T: 'static>(&self) -> Option<[&'a [T]; N]> {
+        self.contains::<T>()
+            .then(|| self.channels.map(|channel| channel.unerase_into().unwrap()))
+    }
+
+    /// Retrieve one of the channels.
+    pub fn channel(&self, index: usize) -> Option<AnySliceRef<'a>> {
+        self.channels.get(index).copied()
+    }
+
+    /// All of the channels.
+    pub const fn channels(&self) -> &[AnySliceRef<'a>; N] {
+        &self.channels
+    }
+
+    /// The number of channels.
+    pub const fn channel_count(&self) -> usize {
+        N
+    }
+
+    /// The number of elements in each channel.
+    pub const fn frames(&self) -> usize {
+        self.channels[0].len()
+    }
+
+    /// Were the original channel elements of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.channels[0].contains::<T>()
+    }
+
+    /// A unique type id representing the original channel element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        self.channels[0].type_id()
+    }
+}
+
+/// A group of type-erased mutable channels, stored planar.
+///
+/// This is the mutable counterpart of [`AnyPlanarRef`].
+///
+/// ```
+/// let mut left = [0u8; 2];
+/// let mut right = [0u8; 2];
+///
+/// let mut planar = sashay::AnyPlanarMut::new([
+///     sashay::AnySliceMut::erase(left.as_mut_slice()),
+///     sashay::AnySliceMut::erase(right.as_mut_slice()),
+/// ]).unwrap();
+///
+/// let [l, r] = planar.unerase_mut::<u8>().unwrap();
+/// l.fill(1);
+/// r.fill(2);
+///
+/// assert_eq!((left, right), ([1, 1], [2, 2]));
+/// ```
+#[derive(Debug)]
+pub struct AnyPlanarMut<'a, const N: usize> {
+    /// The channels, which all have the same element type and length
+    channels: [AnySliceMut<'a>; N],
+}
+
+impl<'a, const N: usize> AnyPlanarMut<'a, N> {
+    /// Group a number of mutable channels.
+    ///
+    /// All channels must have the same element type and length, otherwise an error is returned.
+    /// There must be at least one channel, which is checked at compile time.
+    pub fn new(channels: [AnySliceMut<'a>; N]) -> Result<Self, PlanarError> {
+        const { assert!(N > 0, "a planar buffer needs at least one channel") };
+
+        check_channels(
+            channels
+                .each_ref()
+                .map(|channel| (*channel.type_id(), channel.len())),
+        )?;
+        Ok(Self { channels })
+    }
+
+    /// Borrow the channels immutably.
+    pub fn borrow(&self) -> AnyPlanarRef<'_, N> {
+        AnyPlanarRef {
+            channels: self.channels.each_ref().map(AnySliceMut::borrow),
+        }
+    }
+
+    /// Unerase all channels back to immutable slices.
+    pub fn unerase<T: 'static>(&self) -> Option<[&[T]; N]> {
+        self.borrow().unerase()
+    }
+
+    /// Unerase all channels back to mutable slices.
+    ///
+    /// If the original element type was `T`, the slices are returned. Otherwise, you get `None`.
+    pub fn unerase_mut<T: 'static>(&mut self) -> Option<[&mut [T]; N]> {
+        self.contains::<T>().then(|| {
+            self.channels
+                .each_mut()
+                .map(|channel| channel.unerase_mut().unwrap())
+        })
+    }
+
+    /// Retrieve one of the channels immutably.
+    pub fn channel(&self, index: usize) -> Option<AnySliceRef<'_>> {
+        self.channels.get(index).map(AnySliceMut::borrow)
+    }
+
+    /// Retrieve one of the channels mutably.
+    pub fn channel_mut(&mut self, index: usize) -> Option<AnySliceMut<'_>> {
+        self.channels.get_mut(index).map(AnySliceMut::borrow_mut)
+    }
+
+    /// All of the channels, mutably.
+    pub fn channels_mut(&mut self) -> [AnySliceMut<'_>; N] {
+        self.channels.each_mut().map(AnySliceMut::borrow_mut)
+    }
+
+    /// The number of channels.
+    pub const fn channel_count(&self) -> usize {
+        N
+    }
+
+    /// The number of elements in each channel.
+    pub const fn frames(&self) -> usize {
+        self.channels[0].len()
+    }
+
+    /// Were the original channel elements of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.channels[0].contains::<T>()
+    }
+
+    /// A unique type id representing the original channel element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        self.channels[0].type_id()
+    }
+}
+
+/// Swap the elements of planar channels into an interleaved slice.
+///
+/// The interleaved slice must have the same element type as the channels, and room for exactly one
+/// element per channel per frame.
+///
+/// This works for any element type, because elements are swapped byte-wise instead of copied. Afterwards,
+/// the channels hold what was in the interleaved slice before, in planar order.
+///
+/// ```
+/// let mut left = [1u8, 2, 3];
+/// let mut right = [4u8, 5, 6];
+/// let mut interleaved = [0u8; 6];
+///
+/// let mut planar = sashay::AnyPlanarMut::new([
+///     sashay::AnySliceMut::erase(left.as_mut_slice()),
+///     sashay::AnySliceMut::erase(right.as_mut_slice()),
+/// ]).unwrap();
+///
+/// sashay::swap_interleave(&mut planar, &mut sashay::AnySliceMut::erase(interleaved.as_mut_slice())).unwrap();
+/// assert_eq!(interleaved, [1, 4, 2, 5, 3, 6]);
+/// ```
+pub fn swap_interleave<const N: usize>(
+    planar: &mut AnyPlanarMut<'_, N>,
+    interleaved: &mut AnySliceMut<'_>,
+) -> Result<(), PlanarError> {
+    swap_layouts(planar, interleaved)
+}
+
+/// Swap the elements of an interleaved slice into planar channels.
+///
+/// The interleaved slice must have the same element type as the channels, and hold exactly one
+/// element per channel per frame.
+///
+/// This works for any element type, because elements are swapped byte-wise instead of copied. Afterwards,
+/// the interleaved slice holds what was in the channels before, in interleaved order.
+///
+/// ```
+/// let mut interleaved = [1u8, 4, 2, 5, 3, 6];
+/// let mut left = [0u8; 3];
+/// let mut right = [0u8; 3];
+///
+/// let mut planar = sashay::AnyPlanarMut::new([
+///     sashay::AnySliceMut::erase(left.as_mut_slice()),
+///     sashay::AnySliceMut::erase(right.as_mut_slice()),
+/// ]).unwrap();
+///
+/// sashay::swap_deinterleave(&mut sashay::AnySliceMut::erase(interleaved.as_mut_slice()), &mut planar).unwrap();
+/// assert_eq!((left, right), ([1, 2, 3], [4, 5, 6]));
+/// ```
+pub fn swap_deinterleave<const N: usize>(
+    interleaved: &mut AnySliceMut<'_>,
+    planar: &mut AnyPlanarMut<'_, N>,
+) -> Result<(), PlanarError> {
+    swap_layouts(planar, interleaved)
+}
+
+/// Swap every channel element with its counterpart in the interleaved slice
+fn swap_layouts<const N: usize>(
+    planar: &mut AnyPlanarMut<'_, N>,
+    interleaved: &mut AnySliceMut<'_>,
+) -> Result<(), PlanarError> {
+    if interleaved.type_id() != planar.type_id() {
+        return Err(PlanarError::TypeMismatch {
+            expected: *planar.type_id(),
+            found: *interleaved.type_id(),
+        });
+    }
+
+    let frames = planar.frames();
+    if interleaved.len() != frames * N {
+        return Err(PlanarError::LengthMismatch {
+            expected: frames * N,
+            found: interleaved.len(),
+        });
+    }
+
+    let stride = interleaved.stride();
+    let interleaved = interleaved.as_mut_ptr().cast::<MaybeUninit<u8>>();
+
+    for (index, channel) in planar.channels.iter_mut().enumerate() {
+        let channel = channel.as_mut_ptr().cast::<MaybeUninit<u8>>();
+
+        for frame in 0..frames {
+            // SAFETY:
+            // - Both offsets are within their slices, because the lengths were checked above
+            // - The element types are the same, so swapping `stride` bytes swaps two whole values
+            // - The channels and interleaved slice are separate mutable borrows, so they don't overlap
+            unsafe {
+                ptr::swap_nonoverlapping(
+                    channel.add(frame * stride),
+                    interleaved.add((frame * N + index) * stride),
+                    stride,
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that all channels have the same type and length as the first one
+fn check_channels<const N: usize>(channels: [(TypeId, usize); N]) -> Result<(), PlanarError> {
+    let (type_id, len) = channels[0];
+
+    for (channel_type_id, channel_len) in channels {
+        if channel_type_id != type_id {
+            return Err(PlanarError::TypeMismatch {
+                expected: type_id,
+                found: channel_type_id,
+            });
+        } else if channel_len != len {
+            return Err(PlanarError::LengthMismatch {
+                expected: len,
+                found: channel_len,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// The reasons grouping or converting planar channels can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanarError {
+    /// A slice has a different element type than the channels
+    TypeMismatch { expected: TypeId, found: TypeId },
+
+    /// A slice has the wrong number of elements
+    LengthMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for PlanarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected a slice of {expected:?}, but got {found:?}")
+            }
+            Self::LengthMismatch { expected, found } => {
+                write!(
+                    f,
+                    "expected a slice of {expected} elements, but got {found}"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for PlanarError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channels() {
+        let (a, b) = ([1u16, 2], [3u16, 4]);
+
+        let planar =
+            AnyPlanarRef::new([AnySliceRef::erase(&a[..]), AnySliceRef::erase(&b[..])]).unwrap();
+        assert_eq!(planar.channel_count(), 2);
+        assert_eq!(planar.type_id(), &TypeId::of::<u16>());
+        assert_eq!(planar.channel(1).unwrap().unerase::<u16>(), Some(&b[..]));
+        assert!(planar.channel(2).is_none());
+        assert_eq!(planar.unerase::<u8>(), None);
+
+        assert_eq!(
+            AnyPlanarRef::new([
+                AnySliceRef::erase(&a[..]),
+                AnySliceRef::erase(&[0u8, 0][..])
+            ])
+            .unwrap_err(),
+            PlanarError::TypeMismatch {
+                expected: TypeId::of::<u16>(),
+                found: TypeId::of::<u8>()
+            }
+        );
+        assert_eq!(
+            AnyPlanarRef::new([AnySliceRef::erase(&a[..]), AnySliceRef::erase(&b[..1])])
+                .unwrap_err(),
+            PlanarError::LengthMismatch {
+                expected: 2,
+                found: 1
+            }
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        // Use a type that isn't `Copy` and has padding, to make sure nothing is duplicated
+        #[derive(Debug, PartialEq)]
+        struct Sample(u8, u16);
+
+        let mut a = [Sample(1, 10), Sample(2, 20)];
+        let mut b = [Sample(3, 30), Sample(4, 40)];
+        let mut interleaved = [Sample(0, 0), Sample(0, 0), Sample(0, 0), Sample(0, 0)];
+
+        let mut planar = AnyPlanarMut::new([
+            AnySliceMut::erase(a.as_mut_slice()),
+            AnySliceMut::erase(b.as_mut_slice()),
+        ])
+        .unwrap();
+
+        let mut any = AnySliceMut::erase(interleaved.as_mut_slice());
+        swap_interleave(&mut planar, &mut any).unwrap();
+
+        assert_eq!(
+            any.unerase::<Sample>().unwrap(),
+            [Sample(1, 10), Sample(3, 30), Sample(2, 20), Sample(4, 40)]
+        );
+        assert_eq!(
+            planar.unerase::<Sample>().unwrap()[1],
+            [Sample(0, 0), Sample(0, 0)]
+        );
+
+        swap_deinterleave(&mut any, &mut planar).unwrap();
+        assert_eq!(
+            planar.unerase::<Sample>().unwrap(),
+            [
+                [Sample(1, 10), Sample(2, 20)].as_slice(),
+                [Sample(3, 30), Sample(4, 40)].as_slice()
+            ]
+        );
+
+        let mut wrong = [0u8; 4];
+        assert_eq!(
+            swap_interleave(&mut planar, &mut AnySliceMut::erase(wrong.as_mut_slice())),
+            Err(PlanarError::TypeMismatch {
+                expected: TypeId::of::<Sample>(),
+                found: TypeId::of::<u8>()
+            })
+        );
+
+        let mut short = [Sample(0, 0)];
+        assert_eq!(
+            swap_deinterleave(&mut AnySliceMut::erase(short.as_mut_slice()), &mut planar),
+            Err(PlanarError::LengthMismatch {
+                expected: 4,
+                found: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{AnyMut, AnyRef, AnySliceMut, AnySliceRef};
+use core::{
+    any::{type_name, TypeId},
+    mem::size_of,
+};
+
+macro_rules! primitives {
+    ($($variant:ident($ty:ty)),+ $(,)?) => {
+        /// The kind of a Rust primitive type.
+        ///
+        /// This covers all integer and floating point types, `bool` and `char`.
+        ///
+        /// ```
+        /// use std::any::TypeId;
+        /// use sashay::PrimitiveKind;
+        ///
+        /// assert_eq!(PrimitiveKind::from_type_id(&TypeId::of::<u16>()), Some(PrimitiveKind::U16));
+        /// assert_eq!(PrimitiveKind::of::<String>(), None);
+        /// ```
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum PrimitiveKind {
+            $(
+                #[doc = concat!("`", stringify!($ty), "`")]
+                $variant,
+            )+
+        }
+
+        impl PrimitiveKind {
+            /// All primitive kinds.
+            pub const ALL: &'static [PrimitiveKind] = &[$(Self::$variant),+];
+
+            /// The primitive kind of the type with this type id, if it's a primitive.
+            pub fn from_type_id(type_id: &TypeId) -> Option<Self> {
+                $(
+                    if *type_id == TypeId::of::<$ty>() {
+                        return Some(Self::$variant);
+                    }
+                )+
+
+                None
+            }
+
+            /// A unique type id representing the primitive type.
+            pub fn type_id(self) -> TypeId {
+                match self {
+                    $(Self::$variant => TypeId::of::<$ty>(),)+
+                }
+            }
+
+            /// The `size_of()` of the primitive type.
+            pub const fn size(self) -> usize {
+                match self {
+                    $(Self::$variant => size_of::<$ty>(),)+
+                }
+            }
+
+            /// The name of the primitive type, such as `"f32"`.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => type_name::<$ty>(),)+
+                }
+            }
+        }
+
+        /// An immutable reference to a primitive, unerased from an [`AnyRef`].
+        ///
+        /// Created by [`AnyRef::primitive()`] or [`AnyMut::primitive()`].
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub enum PrimitiveRef<'a> {
+            $(
+                #[doc = concat!("A `&", stringify!($ty), "`")]
+                $variant(&'a $ty),
+            )+
+        }
+
+        impl<'a> PrimitiveRef<'a> {
+            /// Unerase a reference to whichever primitive type it refers to.
+            ///
+            /// If the original referee isn't a primitive, you get `None`.
+            pub fn from_any(any: AnyRef<'a>) -> Option<Self> {
+                match PrimitiveKind::from_type_id(any.type_id())? {
+                    $(PrimitiveKind::$variant => any.unerase_into().map(Self::$variant),)+
+                }
+            }
+
+            /// The kind of primitive referred to.
+            pub const fn kind(&self) -> PrimitiveKind {
+                match self {
+                    $(Self::$variant(_) => PrimitiveKind::$variant,)+
+                }
+            }
+        }
+
+        /// A mutable reference to a primitive, unerased from an [`AnyMut`].
+        ///
+        /// Created by [`AnyMut::primitive_mut()`].
+        #[derive(Debug, PartialEq)]
+        pub enum PrimitiveMut<'a> {
+            $(
+                #[doc = concat!("A `&mut ", stringify!($ty), "`")]
+                $variant(&'a mut $ty),
+            )+
+        }
+
+        impl<'a> PrimitiveMut<'a> {
+            /// Unerase a mutable reference to whichever primitive type it refers to.
+            ///
+            /// If the original referee isn't a primitive, you get `None`.
+            pub fn from_any(any: AnyMut<'a>) -> Option<Self> {
+                match PrimitiveKind::from_type_id(any.type_id())? {
+                    $(PrimitiveKind::$variant => any.unerase_into().map(Self::$variant),)+
+                }
+            }
+
+            /// The kind of primitive referred to.
+            pub const fn kind(&self) -> PrimitiveKind {
+                match self {
+                    $(Self::$variant(_) => PrimitiveKind::$variant,)+
+                }
+            }
+        }
+
+        /// An immutable slice of primitives, unerased from an [`AnySliceRef`].
+        ///
+        /// Created by [`AnySliceRef::primitive()`] or [`AnySliceMut::primitive()`].
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub enum PrimitiveSlice<'a> {
+            $(
+                #[doc = concat!("A `&[", stringify!($ty), "]`")]
+                $variant(&'a [$ty]),
+            )+
+        }
+
+        impl<'a> PrimitiveSlice<'a> {
+            /// Unerase a slice to whichever primitive element type it has.
+            ///
+            /// If the original elements aren't primitives, you get `None`.
+            pub fn from_any(any: AnySliceRef<'a>) -> Option<Self> {
+                match PrimitiveKind::from_type_id(any.type_id())? {
+                    $(PrimitiveKind::$variant => any.unerase_into().map(Self::$variant),)+
+                }
+            }
+
+            /// The kind of primitive in the slice.
+            pub const fn kind(&self) -> PrimitiveKind {
+                match self {
+                    $(Self::$variant(_) => PrimitiveKind::$variant,)+
+                }
+            }
+
+            /// How many elements does the slice contain?
+            pub const fn len(&self) -> usize {
+                match self {
+                    $(Self::$variant(slice) => slice.len(),)+
+                }
+            }
+
+            /// Does the slice contain any elements at all?
+            pub const fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+        }
+
+        /// A mutable slice of primitives, unerased from an [`AnySliceMut`].
+        ///
+        /// Created by [`AnySliceMut::primitive_mut()`].
+        #[derive(Debug, PartialEq)]
+        pub enum PrimitiveSliceMut<'a> {
+            $(
+                #[doc = concat!("A `&mut [", stringify!($ty), "]`")]
+                $variant(&'a mut [$ty]),
+            )+
+        }
+
+        impl<'a> PrimitiveSliceMut<'a> {
+            /// Unerase a mutable slice to whichever primitive element type it has.
+            ///
+            /// If the original elements aren't primitives, you get `None`.
+            pub fn from_any(any: AnySliceMut<'a>) -> Option<Self> {
+                match PrimitiveKind::from_type_id(any.type_id())? {
+                    $(PrimitiveKind::$variant => any.unerase_into().map(Self::$variant),)+
+                }
+            }
+
+            /// The kind of primitive in the slice.
+            pub const fn kind(&self) -> PrimitiveKind {
+                match self {
+                    $(Self::$variant(_) => PrimitiveKind::$variant,)+
+                }
+            }
+
+            /// How many elements does the slice contain?
+            pub const fn len(&self) -> usize {
+                match self {
+                    $(Self::$variant(slice) => slice.len(),)+
+                }
+            }
+
+            /// Does the slice contain any elements at all?
+            pub const fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+        }
+    };
+}
+
+primitives! {
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    Isize(isize),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Usize(usize),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    Char(char),
+}
+
+impl PrimitiveKind {
+    /// The primitive kind of `T`, if it's a primitive.
+    pub fn of<T: 'static>() -> Option<Self> {
+        Self::from_type_id(&TypeId::of::<T>())
+    }
+
+    /// Is this a signed or unsigned integer type?
+    pub const fn is_integer(self) -> bool {
+        self.is_signed_integer() || self.is_unsigned_integer()
+    }
+
+    /// Is this a signed integer type?
+    pub const fn is_signed_integer(self) -> bool {
+        matches!(
+            self,
+            Self::I8 | Self::I16 | Self::I32 | Self::I64 | Self::I128 | Self::Isize
+        )
+    }
+
+    /// Is this an unsigned integer type?
+    pub const fn is_unsigned_integer(self) -> bool {
+        matches!(
+            self,
+            Self::U8 | Self::U16 | Self::U32 | Self::U64 | Self::U128 | Self::Usize
+        )
+    }
+
+    /// Is this a floating point type?
+    pub const fn is_float(self) -> bool {
+        matches!(self, Self::F32 | Self::F64)
+    }
+
+    /// Is this an integer or floating point type?
+    pub const fn is_numeric(self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds() {
+        for kind in PrimitiveKind::ALL {
+            assert_eq!(PrimitiveKind::from_type_id(&kind.type_id()), Some(*kind));
+        }
+
+        assert_eq!(PrimitiveKind::of::<(u8, u16)>(), None);
+        assert_eq!(PrimitiveKind::Usize.size(), size_of::<usize>());
+        assert_eq!(PrimitiveKind::Char.name(), "char");
+        assert!(PrimitiveKind::I128.is_signed_integer());
+        assert!(!PrimitiveKind::Bool.is_numeric());
+    }
+
+    #[test]
+    fn unerase() {
+        let mut data = [1u16, 2];
+
+        let any = AnySliceRef::erase(data.as_slice());
+        assert_eq!(any.primitive(), Some(PrimitiveSlice::U16(&[1, 2])));
+        assert_eq!(any.get(1).unwrap().primitive(), Some(PrimitiveRef::U16(&2)));
+
+        let mut any = AnySliceMut::erase(data.as_mut_slice());
+        if let Some(PrimitiveSliceMut::U16(slice)) = any.primitive_mut() {
+            slice[0] = 3;
+        }
+        if let Some(PrimitiveMut::U16(value)) = any.get_mut(1).unwrap().primitive_mut() {
+            *value = 4;
+        }
+        assert_eq!(data, [3, 4]);
+
+        let data = [(1u8, 2u16)];
+        assert_eq!(AnySliceRef::erase(data.as_slice()).primitive(), None);
+        assert_eq!(AnyRef::erase(&data[0]).primitive(), None);
+    }
+}
@@ -21,6 +21,21 @@ where
     start..end
 }
 
+/// Split `0..len` into consecutive ranges of `chunk_size`, where the last one may be shorter
+///
+/// Panics if `chunk_size` is zero, just like [`slice::chunks()`].
+pub fn chunk_ranges(
+    len: usize,
+    chunk_size: usize,
+) -> impl DoubleEndedIterator<Item = Range<usize>> + ExactSizeIterator {
+    assert!(chunk_size != 0, "chunk size must be non-zero");
+
+    (0..len.div_ceil(chunk_size)).map(move |chunk| {
+        let start = chunk * chunk_size;
+        start..(start + chunk_size).min(len)
+    })
+}
+
 #[test]
 fn range_types() {
     assert_eq!(constrain_range(5, 0..2), 0..2); // Range
@@ -32,3 +47,10 @@ fn range_types() {
     assert_eq!(constrain_range(5, ..4), 0..4); // RangeTo
     assert_eq!(constrain_range(5, ..=2), 0..3); // RangeToInclusive
 }
+
+#[test]
+fn chunks() {
+    assert!(chunk_ranges(7, 3).eq([0..3, 3..6, 6..7]));
+    assert!(chunk_ranges(6, 3).rev().eq([3..6, 0..3]));
+    assert_eq!(chunk_ranges(0, 3).len(), 0);
+}
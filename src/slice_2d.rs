@@ -0,0 +1,334 @@
+use core::{
+    fmt,
+    marker::PhantomData,
+    mem::{align_of, size_of, size_of_val},
+    ops::{Index, IndexMut},
+    slice::{from_raw_parts, from_raw_parts_mut},
+};
+
+/// An immutable two-dimensional view into rows of elements.
+///
+/// The rows are `width` elements long and lie `pitch` bytes apart, so any padding at the end of a row
+/// is skipped over. This is a common layout for images and other grid-like data.
+///
+/// Usually created by unerasing an [`AnySlice2dRef`](crate::AnySlice2dRef).
+///
+/// ```
+/// // Two rows of two elements, each padded to three
+/// let data : [u16; 6] = [1, 2, 0, 3, 4, 0];
+/// let view = sashay::Slice2dRef::new(data.as_slice(), 2, 2, 6).unwrap();
+///
+/// assert_eq!(view.row(1), Some([3, 4].as_slice()));
+/// assert_eq!(view[(0, 1)], 3);
+/// ```
+pub struct Slice2dRef<'a, T> {
+    /// A raw pointer to the first element of the first row
+    ptr: *const T,
+
+    /// The number of elements in each row
+    width: usize,
+
+    /// The number of rows
+    height: usize,
+
+    /// The distance in bytes from the start of one row to the next
+    pitch: usize,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a [T]>,
+}
+
+impl<'a, T> Slice2dRef<'a, T> {
+    /// View a slice as rows of `width` elements, that start `pitch` bytes apart.
+    ///
+    /// If the rows don't fit within the slice, or if `pitch` would misalign the rows, you get `None`.
+    pub fn new(slice: &'a [T], width: usize, height: usize, pitch: usize) -> Option<Self> {
+        let stride = size_of::<T>();
+        let fits = fits(size_of_val(slice), width, height, stride, pitch)
+            && pitch.is_multiple_of(align_of::<T>());
+
+        // SAFETY: We've checked that all rows fall within the slice, and are aligned
+        fits.then(|| unsafe { Self::from_raw_parts(slice.as_ptr(), width, height, pitch) })
+    }
+
+    /// Construct a two-dimensional view from its raw parts.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if, for every row below `height`, `ptr` offset by
+    /// `row * pitch` bytes points to `width` valid and aligned consecutive values of `T`, which aren't
+    /// mutated for the duration of `'a`.
+    pub const unsafe fn from_raw_parts(
+        ptr: *const T,
+        width: usize,
+        height: usize,
+        pitch: usize,
+    ) -> Self {
+        Self {
+            ptr,
+            width,
+            height,
+            pitch,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Retrieve one of the rows.
+    pub fn row(&self, y: usize) -> Option<&'a [T]> {
+        // SAFETY: The row is within bounds, so it's valid as per construction
+        (y < self.height).then(|| unsafe { from_raw_parts(self.row_ptr(y), self.width) })
+    }
+
+    /// Retrieve a reference to the element in column `x` and row `y`.
+    pub fn get(&self, x: usize, y: usize) -> Option<&'a T> {
+        self.row(y)?.get(x)
+    }
+
+    /// Iterate over all rows, from top to bottom.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &'a [T]> + ExactSizeIterator {
+        let view = *self;
+
+        // SAFETY: The row is within bounds, so it's valid as per construction
+        (0..self.height).map(move |y| unsafe { from_raw_parts(view.row_ptr(y), view.width) })
+    }
+
+    /// Retrieve an unsafe pointer to the first element of the first row.
+    pub const fn as_ptr(&self) -> *const T {
+        self.ptr
+    }
+
+    /// The number of elements in each row.
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows.
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The distance in bytes from the start of one row to the next.
+    pub const fn pitch(&self) -> usize {
+        self.pitch
+    }
+
+    /// Does the view contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// A pointer to the first element of row `y`, which doesn't need to be in bounds
+    fn row_ptr(&self, y: usize) -> *const T {
+        self.ptr
+            .cast::<u8>()
+            .wrapping_add(y * self.pitch)
+            .cast::<T>()
+    }
+}
+
+impl<T> Clone for Slice2dRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Slice2dRef<'_, T> {}
+
+impl<T> Index<(usize, usize)> for Slice2dRef<'_, T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).expect("index out of bounds")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Slice2dRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.rows()).finish()
+    }
+}
+
+/// A mutable two-dimensional view into rows of elements.
+///
+/// The rows are `width` elements long and lie `pitch` bytes apart. See [`Slice2dRef`] for more information.
+///
+/// Usually created by unerasing an [`AnySlice2dMut`](crate::AnySlice2dMut).
+///
+/// ```
+/// let mut data : [u16; 6] = [1, 2, 0, 3, 4, 0];
+/// let mut view = sashay::Slice2dMut::new(data.as_mut_slice(), 2, 2, 6).unwrap();
+///
+/// view.rows_mut().for_each(|row| row.fill(7));
+/// view[(1, 0)] = 8;
+///
+/// assert_eq!(data, [7, 8, 0, 7, 7, 0]);
+/// ```
+pub struct Slice2dMut<'a, T> {
+    /// A raw pointer to the first element of the first row
+    ptr: *mut T,
+
+    /// The number of elements in each row
+    width: usize,
+
+    /// The number of rows
+    height: usize,
+
+    /// The distance in bytes from the start of one row to the next
+    pitch: usize,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a mut [T]>,
+}
+
+impl<'a, T> Slice2dMut<'a, T> {
+    /// View a mutable slice as rows of `width` elements, that start `pitch` bytes apart.
+    ///
+    /// If the rows don't fit within the slice, overlap, or if `pitch` would misalign the rows, you get `None`.
+    pub fn new(slice: &'a mut [T], width: usize, height: usize, pitch: usize) -> Option<Self> {
+        let stride = size_of::<T>();
+        let fits = fits(size_of_val(slice), width, height, stride, pitch)
+            && pitch.is_multiple_of(align_of::<T>());
+
+        // SAFETY: We've checked that all rows fall within the slice, are aligned and don't overlap
+        fits.then(|| unsafe { Self::from_raw_parts(slice.as_mut_ptr(), width, height, pitch) })
+    }
+
+    /// Construct a mutable two-dimensional view from its raw parts.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if, for every row below `height`, `ptr` offset by
+    /// `row * pitch` bytes points to `width` valid and aligned consecutive values of `T`, none of the
+    /// rows overlap, and they are not accessed through any other pointer for the duration of `'a`.
+    pub unsafe fn from_raw_parts(ptr: *mut T, width: usize, height: usize, pitch: usize) -> Self {
+        Self {
+            ptr,
+            width,
+            height,
+            pitch,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Borrow this mutable view as an immutable one.
+    pub fn borrow(&self) -> Slice2dRef<'_, T> {
+        // SAFETY: All parts are valid, and we have an immutable reference to self
+        unsafe {
+            Slice2dRef::from_raw_parts(self.ptr.cast_const(), self.width, self.height, self.pitch)
+        }
+    }
+
+    /// Retrieve one of the rows.
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        self.borrow().row(y)
+    }
+
+    /// Retrieve one of the rows mutably.
+    pub fn row_mut(&mut self, y: usize) -> Option<&mut [T]> {
+        // SAFETY: The row is within bounds, so it's valid as per construction
+        (y < self.height).then(|| unsafe { from_raw_parts_mut(self.row_ptr(y), self.width) })
+    }
+
+    /// Retrieve a reference to the element in column `x` and row `y`.
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.borrow().get(x, y)
+    }
+
+    /// Retrieve a mutable reference to the element in column `x` and row `y`.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.row_mut(y)?.get_mut(x)
+    }
+
+    /// Iterate over all rows, from top to bottom.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        self.borrow().rows()
+    }
+
+    /// Iterate mutably over all rows, from top to bottom.
+    pub fn rows_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut [T]> + ExactSizeIterator {
+        let (ptr, width, pitch) = (self.ptr, self.width, self.pitch);
+
+        // SAFETY:
+        // - The row is within bounds, so it's valid as per construction
+        // - Every row is only visited once, and rows don't overlap, so the slices are unique
+        (0..self.height).map(move |y| unsafe {
+            from_raw_parts_mut(ptr.cast::<u8>().wrapping_add(y * pitch).cast::<T>(), width)
+        })
+    }
+
+    /// Retrieve an unsafe pointer to the first element of the first row.
+    pub const fn as_ptr(&self) -> *const T {
+        self.ptr.cast_const()
+    }
+
+    /// Retrieve an unsafe mutable pointer to the first element of the first row.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr
+    }
+
+    /// The number of elements in each row.
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows.
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The distance in bytes from the start of one row to the next.
+    pub const fn pitch(&self) -> usize {
+        self.pitch
+    }
+
+    /// Does the view contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// A pointer to the first element of row `y`, which doesn't need to be in bounds
+    fn row_ptr(&self, y: usize) -> *mut T {
+        self.ptr
+            .cast::<u8>()
+            .wrapping_add(y * self.pitch)
+            .cast::<T>()
+    }
+}
+
+impl<T> Index<(usize, usize)> for Slice2dMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).expect("index out of bounds")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Slice2dMut<'_, T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y).expect("index out of bounds")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Slice2dMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.rows()).finish()
+    }
+}
+
+/// Do `height` rows of `width` elements, `pitch` bytes apart, fit within `bytes` without overlapping?
+pub(crate) fn fits(bytes: usize, width: usize, height: usize, stride: usize, pitch: usize) -> bool {
+    let Some(row_bytes) = width.checked_mul(stride) else {
+        return false;
+    };
+
+    if height == 0 {
+        return true;
+    } else if height > 1 && pitch < row_bytes {
+        return false;
+    }
+
+    (height - 1)
+        .checked_mul(pitch)
+        .and_then(|offset| offset.checked_add(row_bytes))
+        .is_some_and(|end| end <= bytes)
+}
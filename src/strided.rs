@@ -0,0 +1,368 @@
+use crate::range::{chunk_ranges, constrain_range};
+use core::{
+    fmt,
+    marker::PhantomData,
+    mem::size_of,
+    ops::{Index, IndexMut, RangeBounds},
+};
+
+/// An immutable view of elements that are evenly spaced in memory.
+///
+/// This behaves like a `&[T]`, except that subsequent elements don't have to be adjacent. Instead, they
+/// are a fixed number of bytes apart, called the `step`. The step can be larger than `size_of::<T>()`
+/// (to skip over other elements), or even negative (to walk through memory back to front).
+///
+/// Usually created by unerasing an [`AnyStridedRef`](crate::AnyStridedRef).
+///
+/// ```
+/// let data : [i32; 6] = [0, 1, 2, 3, 4, 5];
+///
+/// // Take every other element
+/// let any = sashay::AnySliceRef::erase(data.as_slice());
+/// let strided = any.interleaved_channel(0, 2).unwrap().unerase_into::<i32>().unwrap();
+///
+/// assert_eq!(strided.len(), 3);
+/// assert_eq!(strided[1], 2);
+/// assert_eq!(strided, [0, 2, 4]);
+/// ```
+pub struct StridedRef<'a, T> {
+    /// A raw pointer to the first element
+    ptr: *const T,
+
+    /// The number of elements
+    len: usize,
+
+    /// The distance in bytes from one element to the next
+    step: isize,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a [T]>,
+}
+
+impl<'a, T> StridedRef<'a, T> {
+    /// Construct a strided view from its raw parts.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if, for every index below `len`, `ptr` offset by
+    /// `index * step` bytes points to a valid and aligned `T` that isn't mutated for the duration of `'a`.
+    pub const unsafe fn from_raw_parts(ptr: *const T, len: usize, step: isize) -> Self {
+        Self {
+            ptr,
+            len,
+            step,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Retrieve a reference to one of the elements.
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        // SAFETY: The index is within bounds, so the pointer is valid as per construction
+        (index < self.len).then(|| unsafe { &*self.element(index) })
+    }
+
+    /// Iterate over all elements, in order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &'a T> + ExactSizeIterator {
+        let view = *self;
+
+        // SAFETY: The index is within bounds, so the pointer is valid as per construction
+        (0..self.len).map(move |index| unsafe { &*view.element(index) })
+    }
+
+    /// Access a smaller view within a given range.
+    pub fn subslice<R>(&self, range: R) -> StridedRef<'a, T>
+    where
+        R: RangeBounds<usize>,
+    {
+        let range = constrain_range(self.len, range);
+
+        // SAFETY: `constrain_range()` ensures that the new elements are a subset of the current ones
+        unsafe { Self::from_raw_parts(self.element(range.start), range.len(), self.step) }
+    }
+
+    /// Iterate over consecutive smaller views of `chunk_size` elements each.
+    ///
+    /// The last chunk is shorter if the length isn't a multiple of `chunk_size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn chunks(
+        &self,
+        chunk_size: usize,
+    ) -> impl DoubleEndedIterator<Item = StridedRef<'a, T>> + ExactSizeIterator {
+        let view = *self;
+        chunk_ranges(self.len, chunk_size).map(move |range| view.subslice(range))
+    }
+
+    /// How many elements does the view contain?
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Does the view contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Retrieve an unsafe pointer to the first element.
+    pub const fn as_ptr(&self) -> *const T {
+        self.ptr
+    }
+
+    /// The distance in bytes from one element to the next.
+    pub const fn step(&self) -> isize {
+        self.step
+    }
+
+    /// A pointer to the element at `index`, which doesn't need to be in bounds
+    fn element(&self, index: usize) -> *const T {
+        self.ptr
+            .cast::<u8>()
+            .wrapping_offset(index as isize * self.step)
+            .cast::<T>()
+    }
+}
+
+impl<T> Clone for StridedRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for StridedRef<'_, T> {}
+
+impl<T> Index<usize> for StridedRef<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for StridedRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for StridedRef<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq<[T; N]> for StridedRef<'_, T> {
+    fn eq(&self, other: &[T; N]) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<'a, T> From<&'a [T]> for StridedRef<'a, T> {
+    fn from(slice: &'a [T]) -> Self {
+        // SAFETY: Slice elements are exactly `size_of::<T>()` bytes apart
+        unsafe { Self::from_raw_parts(slice.as_ptr(), slice.len(), size_of::<T>() as isize) }
+    }
+}
+
+/// A mutable view of elements that are evenly spaced in memory.
+///
+/// This behaves like a `&mut [T]`, except that subsequent elements don't have to be adjacent. Instead,
+/// they are a fixed number of bytes apart, called the `step`. See [`StridedRef`] for more information.
+///
+/// Usually created by unerasing an [`AnyStridedMut`](crate::AnyStridedMut).
+///
+/// ```
+/// let mut data : [i32; 6] = [0, 1, 2, 3, 4, 5];
+///
+/// // Zero out every other element
+/// let mut any = sashay::AnySliceMut::erase(data.as_mut_slice());
+/// let mut channel = any.interleaved_channel_mut(1, 2).unwrap();
+///
+/// channel.unerase_mut::<i32>().unwrap().iter_mut().for_each(|value| *value = 0);
+///
+/// assert_eq!(data, [0, 0, 2, 0, 4, 0]);
+/// ```
+pub struct StridedMut<'a, T> {
+    /// A raw pointer to the first element
+    ptr: *mut T,
+
+    /// The number of elements
+    len: usize,
+
+    /// The distance in bytes from one element to the next
+    step: isize,
+
+    /// Phantom data to ensure that we stick to the correct lifetime
+    _phantom: PhantomData<&'a mut [T]>,
+}
+
+impl<'a, T> StridedMut<'a, T> {
+    /// Construct a mutable strided view from its raw parts.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if, for every index below `len`, `ptr` offset by
+    /// `index * step` bytes points to a valid and aligned `T`, none of these elements overlap, and
+    /// they are not accessed through any other pointer for the duration of `'a`.
+    pub unsafe fn from_raw_parts(ptr: *mut T, len: usize, step: isize) -> Self {
+        Self {
+            ptr,
+            len,
+            step,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Borrow this mutable view as an immutable one.
+    pub fn borrow(&self) -> StridedRef<'_, T> {
+        // SAFETY: All parts are valid, and we have an immutable reference to self
+        unsafe { StridedRef::from_raw_parts(self.ptr.cast_const(), self.len, self.step) }
+    }
+
+    /// Retrieve a reference to one of the elements.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        // SAFETY: The index is within bounds, so the pointer is valid as per construction
+        (index < self.len).then(|| unsafe { &*self.element(index) })
+    }
+
+    /// Retrieve a mutable reference to one of the elements.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        // SAFETY: The index is within bounds, so the pointer is valid as per construction
+        (index < self.len).then(|| unsafe { &mut *self.element(index) })
+    }
+
+    /// Iterate over all elements, in order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.borrow().iter()
+    }
+
+    /// Iterate mutably over all elements, in order.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> + ExactSizeIterator {
+        let (ptr, step) = (self.ptr, self.step);
+
+        // SAFETY:
+        // - The index is within bounds, so the pointer is valid as per construction
+        // - Every index is only visited once, and elements don't overlap, so the references are unique
+        (0..self.len).map(move |index| unsafe {
+            &mut *ptr
+                .cast::<u8>()
+                .wrapping_offset(index as isize * step)
+                .cast::<T>()
+        })
+    }
+
+    /// Access a smaller mutable view within a given range.
+    pub fn subslice_mut<R>(&mut self, range: R) -> StridedMut<'_, T>
+    where
+        R: RangeBounds<usize>,
+    {
+        let range = constrain_range(self.len, range);
+
+        // SAFETY: `constrain_range()` ensures that the new elements are a subset of the current ones
+        unsafe { StridedMut::from_raw_parts(self.element(range.start), range.len(), self.step) }
+    }
+
+    /// Iterate over consecutive smaller views of `chunk_size` elements each.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn chunks(
+        &self,
+        chunk_size: usize,
+    ) -> impl DoubleEndedIterator<Item = StridedRef<'_, T>> + ExactSizeIterator {
+        self.borrow().chunks(chunk_size)
+    }
+
+    /// Iterate over consecutive smaller mutable views of `chunk_size` elements each.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn chunks_mut(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl DoubleEndedIterator<Item = StridedMut<'_, T>> + ExactSizeIterator {
+        let (ptr, step) = (self.ptr, self.step);
+
+        // SAFETY:
+        // - The ranges are within bounds, so the elements are valid as per construction
+        // - The ranges never overlap, so the views are unique
+        chunk_ranges(self.len, chunk_size).map(move |range| unsafe {
+            StridedMut::from_raw_parts(
+                ptr.cast::<u8>()
+                    .wrapping_offset(range.start as isize * step)
+                    .cast::<T>(),
+                range.len(),
+                step,
+            )
+        })
+    }
+
+    /// How many elements does the view contain?
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Does the view contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Retrieve an unsafe pointer to the first element.
+    pub const fn as_ptr(&self) -> *const T {
+        self.ptr.cast_const()
+    }
+
+    /// Retrieve an unsafe mutable pointer to the first element.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr
+    }
+
+    /// The distance in bytes from one element to the next.
+    pub const fn step(&self) -> isize {
+        self.step
+    }
+
+    /// A pointer to the element at `index`, which doesn't need to be in bounds
+    fn element(&self, index: usize) -> *mut T {
+        self.ptr
+            .cast::<u8>()
+            .wrapping_offset(index as isize * self.step)
+            .cast::<T>()
+    }
+}
+
+impl<T> Index<usize> for StridedMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T> IndexMut<usize> for StridedMut<'_, T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("index out of bounds")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for StridedMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq<[T; N]> for StridedMut<'_, T> {
+    fn eq(&self, other: &[T; N]) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<'a, T> From<&'a mut [T]> for StridedMut<'a, T> {
+    fn from(slice: &'a mut [T]) -> Self {
+        // SAFETY: Slice elements are exactly `size_of::<T>()` bytes apart, and don't overlap
+        unsafe { Self::from_raw_parts(slice.as_mut_ptr(), slice.len(), size_of::<T>() as isize) }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::range::constrain_range;
+use core::ops::RangeBounds;
+
+/// The shape and byte strides of an n-dimensional view, shared by the tensor types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Layout<const D: usize> {
+    /// The number of elements along each axis
+    pub shape: [usize; D],
+
+    /// The distance in bytes between subsequent elements along each axis
+    pub strides: [isize; D],
+}
+
+impl<const D: usize> Layout<D> {
+    /// A row-major layout without gaps, for elements of `stride` bytes
+    ///
+    /// Returns `None` if the number of elements or the byte strides overflow.
+    pub fn contiguous(shape: [usize; D], stride: usize) -> Option<Self> {
+        checked_len(&shape)?;
+
+        let mut strides = [0; D];
+        let mut step = isize::try_from(stride).ok()?;
+
+        for axis in (0..D).rev() {
+            strides[axis] = step;
+            step = step.checked_mul(isize::try_from(shape[axis]).ok()?)?;
+        }
+
+        Some(Self { shape, strides })
+    }
+
+    /// The total number of elements
+    pub fn len(&self) -> usize {
+        // Layouts are only constructed with shapes whose number of elements fits
+        self.shape.iter().product()
+    }
+
+    /// The byte offset of the element at `index`, if it's within bounds
+    pub fn offset(&self, index: [usize; D]) -> Option<isize> {
+        let mut offset = 0;
+
+        for ((index, len), stride) in index.into_iter().zip(self.shape).zip(self.strides) {
+            if index >= len {
+                return None;
+            }
+
+            offset += index as isize * stride;
+        }
+
+        Some(offset)
+    }
+
+    /// Restrict an axis to a range, returning the new layout and the byte offset of its first element
+    ///
+    /// Returns `None` if the axis doesn't exist.
+    pub fn slice_axis<R>(&self, axis: usize, range: R) -> Option<(Self, isize)>
+    where
+        R: RangeBounds<usize>,
+    {
+        let len = *self.shape.get(axis)?;
+        let range = constrain_range(len, range);
+
+        let mut layout = *self;
+        layout.shape[axis] = range.len();
+
+        Some((layout, range.start as isize * self.strides[axis]))
+    }
+
+    /// Reorder the axes, so that new axis `i` is old axis `axes[i]`
+    ///
+    /// Returns `None` if `axes` isn't a permutation of `0..D`.
+    pub fn permute(&self, axes: [usize; D]) -> Option<Self> {
+        let mut seen = [false; D];
+        for &axis in &axes {
+            if axis >= D || core::mem::replace(&mut seen[axis], true) {
+                return None;
+            }
+        }
+
+        Some(Self {
+            shape: axes.map(|axis| self.shape[axis]),
+            strides: axes.map(|axis| self.strides[axis]),
+        })
+    }
+
+    /// Reverse the order of the axes
+    pub fn transpose(&self) -> Self {
+        let mut layout = *self;
+        layout.shape.reverse();
+        layout.strides.reverse();
+        layout
+    }
+
+    /// Stretch the layout to a larger shape, by repeating axes of length 1
+    ///
+    /// Axes are matched from the back, and missing leading axes are added. Returns `None` if
+    /// the shapes are incompatible, or if the number of elements overflows.
+    pub fn broadcast<const E: usize>(&self, shape: [usize; E]) -> Option<Layout<E>> {
+        let lead = E.checked_sub(D)?;
+        checked_len(&shape)?;
+        let mut strides = [0; E];
+
+        for axis in 0..D {
+            let (from, to) = (self.shape[axis], shape[lead + axis]);
+
+            if from == to {
+                strides[lead + axis] = self.strides[axis];
+            } else if from != 1 {
+                return None;
+            }
+        }
+
+        Some(Layout { shape, strides })
+    }
+
+    /// Are the elements laid out row-major, without gaps?
+    pub fn is_contiguous(&self, stride: usize) -> bool {
+        if self.len() == 0 {
+            return true;
+        }
+
+        let mut step = stride as isize;
+        for axis in (0..D).rev() {
+            // The stride of an axis of length 1 is never used
+            if self.shape[axis] != 1 {
+                if self.strides[axis] != step {
+                    return false;
+                }
+
+                // The step past the first axis is never compared, so it may overflow, but any
+                // earlier step that overflows can't be matched by the remaining strides
+                match isize::try_from(self.shape[axis])
+                    .ok()
+                    .and_then(|len| step.checked_mul(len))
+                {
+                    Some(next) => step = next,
+                    None => return axis == 0,
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// The number of elements in a shape, if it doesn't overflow
+fn checked_len(shape: &[usize]) -> Option<usize> {
+    shape
+        .iter()
+        .try_fold(1usize, |len, &axis| len.checked_mul(axis))
+}
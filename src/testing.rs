@@ -0,0 +1,45 @@
+//! Fixtures shared by the unit tests
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts how many of the values it tracks have been dropped
+///
+/// Every test declares its own `static` counter, so tests running in parallel don't interfere.
+pub(crate) struct DropCounter(AtomicUsize);
+
+impl DropCounter {
+    pub const fn new() -> Self {
+        Self(AtomicUsize::new(0))
+    }
+
+    /// Wrap a value, so that dropping it increments this counter
+    pub fn track<T>(&'static self, value: T) -> Tracked<T> {
+        Tracked {
+            value,
+            counter: self,
+        }
+    }
+
+    /// The number of tracked values that have been dropped so far
+    pub fn drops(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A value that increments its [`DropCounter`] when dropped
+pub(crate) struct Tracked<T = ()> {
+    pub value: T,
+    counter: &'static DropCounter,
+}
+
+impl<T> Drop for Tracked<T> {
+    fn drop(&mut self) {
+        self.counter.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<T: Clone> Clone for Tracked<T> {
+    fn clone(&self) -> Self {
+        self.counter.track(self.value.clone())
+    }
+}
@@ -0,0 +1,40 @@
+/// Declares that a type can be safely viewed as another type `U`, because their layouts are compatible.
+///
+/// This is meant for `#[repr(transparent)]` newtypes, so that erased references or slices of the
+/// newtype can be reinterpreted as references or slices of the inner type (or the other way round),
+/// without copying. See for example [`AnySliceRef::reinterpret()`](crate::AnySliceRef::reinterpret).
+///
+/// Arrays of compatible types are compatible as well.
+///
+/// # Safety
+///
+/// Implementing this trait is only sound if:
+///  - `Self` and `U` have the same size and alignment
+///  - Every valid value of `Self` is a valid value of `U`, and vice versa, because mutable
+///    reinterpretation allows writing either type through the other
+///
+/// # Example
+///
+/// ```
+/// #[repr(transparent)]
+/// struct Sample(f32);
+///
+/// // SAFETY: Sample is a transparent wrapper around f32
+/// unsafe impl sashay::TransparentAs<f32> for Sample {}
+///
+/// let samples = [Sample(0.5), Sample(1.0)];
+/// let any = sashay::AnySliceRef::erase(samples.as_slice());
+/// assert!(any.unerase::<f32>().is_none());
+///
+/// let floats = any.reinterpret::<Sample, f32>().unwrap();
+/// assert_eq!(floats.unerase::<f32>(), Some([0.5, 1.0].as_slice()));
+/// ```
+pub unsafe trait TransparentAs<U: 'static>: 'static {}
+
+// SAFETY: Arrays are laid out as N adjacent elements, so compatible elements make compatible arrays
+unsafe impl<T, U, const N: usize> TransparentAs<[U; N]> for [T; N]
+where
+    T: TransparentAs<U>,
+    U: 'static,
+{
+}
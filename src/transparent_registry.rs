@@ -0,0 +1,98 @@
+use crate::TransparentAs;
+use alloc::vec::Vec;
+use core::any::TypeId;
+
+/// A runtime registry of which types can be reinterpreted as which other types.
+///
+/// The [`TransparentAs`] trait declares layout compatibility at compile time, but reinterpreting with it
+/// requires naming the original type. When types are only known by their erased type id (for example
+/// when plugins from different crates pass erased buffers around), register the compatible pairs here
+/// instead, and reinterpret with methods like [`AnySliceRef::reinterpret_with()`](crate::AnySliceRef::reinterpret_with).
+///
+/// ```
+/// #[repr(transparent)]
+/// struct Hz(f64);
+///
+/// // SAFETY: Hz is a transparent wrapper around f64
+/// unsafe impl sashay::TransparentAs<f64> for Hz {}
+///
+/// let mut registry = sashay::TransparentRegistry::new();
+/// registry.register::<Hz, f64>();
+///
+/// let frequencies = [Hz(440.0), Hz(880.0)];
+/// let any = sashay::AnySliceRef::erase(frequencies.as_slice());
+///
+/// let floats = any.reinterpret_with::<f64>(&registry).unwrap();
+/// assert_eq!(floats.unerase::<f64>(), Some([440.0, 880.0].as_slice()));
+///
+/// // Only registered directions are allowed
+/// assert!(floats.reinterpret_with::<Hz>(&registry).is_none());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct TransparentRegistry {
+    /// Pairs of compatible type ids, sorted so we can binary search
+    pairs: Vec<(TypeId, TypeId)>,
+}
+
+impl TransparentRegistry {
+    /// Construct a registry without any compatible types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register that `T` can be reinterpreted as `U`.
+    pub fn register<T, U>(&mut self)
+    where
+        T: TransparentAs<U>,
+        U: 'static,
+    {
+        let pair = (TypeId::of::<T>(), TypeId::of::<U>());
+
+        if let Err(index) = self.pairs.binary_search(&pair) {
+            self.pairs.insert(index, pair);
+        }
+    }
+
+    /// Can values of type `from` be reinterpreted as values of type `to`?
+    ///
+    /// Every type is compatible with itself.
+    pub fn is_compatible(&self, from: TypeId, to: TypeId) -> bool {
+        from == to || self.pairs.binary_search(&(from, to)).is_ok()
+    }
+
+    /// How many compatible pairs are registered?
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Are there any compatible pairs registered at all?
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(transparent)]
+    struct Wrapper((u8, u16));
+
+    // SAFETY: Wrapper is a transparent wrapper around (u8, u16)
+    unsafe impl TransparentAs<(u8, u16)> for Wrapper {}
+
+    #[test]
+    fn register() {
+        let mut registry = TransparentRegistry::new();
+        let (wrapper, inner) = (TypeId::of::<Wrapper>(), TypeId::of::<(u8, u16)>());
+        assert!(!registry.is_compatible(wrapper, inner));
+        assert!(registry.is_compatible(inner, inner));
+
+        registry.register::<Wrapper, (u8, u16)>();
+        registry.register::<Wrapper, (u8, u16)>();
+        registry.register::<[Wrapper; 2], [(u8, u16); 2]>();
+        assert_eq!(registry.len(), 2);
+        assert!(registry.is_compatible(wrapper, inner));
+        assert!(!registry.is_compatible(inner, wrapper));
+    }
+}
@@ -0,0 +1,167 @@
+use core::{
+    any::TypeId,
+    mem::{align_of, size_of},
+    ptr,
+};
+
+/// Runtime information about a type, for containers that own erased values.
+///
+/// Besides the [`TypeId`] and stride that all erased types carry, owning containers need to know the
+/// alignment of their elements, how to drop them, and whether they can be duplicated by copying bytes.
+///
+/// ```
+/// let info = sashay::TypeInfo::of_copy::<u32>();
+///
+/// assert!(info.is::<u32>());
+/// assert_eq!(info.stride(), 4);
+/// assert!(info.is_copy());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TypeInfo {
+    /// A unique id representing the type
+    type_id: TypeId,
+
+    /// The `size_of()` of the type
+    stride: usize,
+
+    /// The `align_of()` of the type
+    align: usize,
+
+    /// Is the type known to be `Copy`?
+    copy: bool,
+
+    /// Drops a value of the type in place
+    drop: unsafe fn(*mut ()),
+}
+
+impl TypeInfo {
+    /// Describe a type `T`.
+    ///
+    /// The type is not considered `Copy`, even if it is. Use [`TypeInfo::of_copy()`] for that.
+    pub fn of<T: 'static>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            stride: size_of::<T>(),
+            align: align_of::<T>(),
+            copy: false,
+            drop: drop_erased::<T>,
+        }
+    }
+
+    /// Describe a type `T` that is `Copy`, so it can be duplicated by copying its bytes.
+    pub fn of_copy<T: Copy + 'static>() -> Self {
+        Self {
+            copy: true,
+            ..Self::of::<T>()
+        }
+    }
+
+    /// Is this a description of type `T`?
+    pub fn is<T: 'static>(&self) -> bool {
+        TypeId::of::<T>() == self.type_id
+    }
+
+    /// A unique type id representing the type.
+    pub const fn type_id(&self) -> &TypeId {
+        &self.type_id
+    }
+
+    /// The `size_of()` of the type.
+    pub const fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// The `align_of()` of the type.
+    pub const fn align(&self) -> usize {
+        self.align
+    }
+
+    /// Is the type known to be `Copy`?
+    pub const fn is_copy(&self) -> bool {
+        self.copy
+    }
+
+    /// Drop a value of the type in place.
+    ///
+    /// # Safety
+    ///
+    /// Calling this is only defined behaviour if `ptr` points to a valid and aligned value of the
+    /// described type, which is not used again afterwards.
+    pub unsafe fn drop_in_place(&self, ptr: *mut ()) {
+        // SAFETY: The caller guarantees the pointer is valid, and the drop function matches the type
+        unsafe { (self.drop)(ptr) }
+    }
+
+    /// The memory layout of an array of `capacity` values of the type
+    #[cfg(feature = "alloc")]
+    fn array_layout(&self, capacity: usize) -> alloc::alloc::Layout {
+        self.stride
+            .checked_mul(capacity)
+            .and_then(|size| alloc::alloc::Layout::from_size_align(size, self.align).ok())
+            .expect("capacity overflow")
+    }
+
+    /// Allocate aligned, uninitialized storage for `capacity` values of the type
+    ///
+    /// Zero-sized arrays don't allocate, but still return an aligned, dangling pointer.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn allocate_array(&self, capacity: usize) -> *mut u8 {
+        let layout = self.array_layout(capacity);
+        if layout.size() == 0 {
+            return ptr::without_provenance_mut(layout.align());
+        }
+
+        // SAFETY: The layout has a non-zero size
+        let ptr = unsafe { alloc::alloc::alloc(layout) };
+        if ptr.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+
+        ptr
+    }
+
+    /// Deallocate storage created with [`TypeInfo::allocate_array()`]
+    ///
+    /// # Safety
+    ///
+    /// The pointer must come from `allocate_array()` on the same type information with the same capacity.
+    #[cfg(feature = "alloc")]
+    pub(crate) unsafe fn deallocate_array(&self, ptr: *mut u8, capacity: usize) {
+        let layout = self.array_layout(capacity);
+        if layout.size() != 0 {
+            // SAFETY: The caller guarantees the pointer was allocated with this layout
+            unsafe { alloc::alloc::dealloc(ptr, layout) };
+        }
+    }
+}
+
+/// Drop an erased value of type `T` in place
+unsafe fn drop_erased<T>(ptr: *mut ()) {
+    // SAFETY: The caller guarantees the pointer points to a valid `T`
+    unsafe { ptr::drop_in_place(ptr.cast::<T>()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{DropCounter, Tracked};
+    use core::mem::ManuallyDrop;
+
+    #[test]
+    fn describe_drop() {
+        static DROPS: DropCounter = DropCounter::new();
+
+        let info = TypeInfo::of::<(u8, u16)>();
+        assert_eq!(info.stride(), size_of::<(u8, u16)>());
+        assert_eq!(info.align(), align_of::<(u8, u16)>());
+        assert!(!info.is_copy());
+        assert!(info.is::<(u8, u16)>());
+        assert!(!info.is::<u8>());
+        assert!(TypeInfo::of_copy::<(u8, u16)>().is_copy());
+
+        let info = TypeInfo::of::<Tracked>();
+        let mut value = ManuallyDrop::new(DROPS.track(()));
+        unsafe { info.drop_in_place((&mut *value as *mut Tracked).cast::<()>()) };
+        assert_eq!(DROPS.drops(), 1);
+    }
+}
@@ -0,0 +1,438 @@
+use crate::{AnySliceMut, AnySliceRef};
+use core::{any::TypeId, fmt, marker::PhantomData, ops::RangeBounds};
+
+/// Declare a closed set of types.
+///
+/// The set can be used with [`AnySetSliceRef`] and [`AnySetSliceMut`] to only allow erasing slices of its
+/// member types. Next to the set itself, the macro declares two enums: one holding an immutable typed slice
+/// per member and one holding a mutable typed slice per member. Resolving an erased slice returns one of these
+/// enums, which can then be matched exhaustively.
+///
+/// ```
+/// sashay::type_set! {
+///     /// The sample formats we support
+///     pub struct Samples => SampleSlice, SampleSliceMut {
+///         F32(f32),
+///         F64(f64),
+///         I16(i16),
+///         I32(i32),
+///     }
+/// }
+///
+/// fn peak(samples: sashay::AnySetSliceRef<Samples>) -> f64 {
+///     // No fallback arm needed
+///     match samples.resolve() {
+///         SampleSlice::F32(s) => s.iter().fold(0.0, |peak, x| x.abs().max(peak)) as f64,
+///         SampleSlice::F64(s) => s.iter().fold(0.0, |peak, x| x.abs().max(peak)),
+///         SampleSlice::I16(s) => s.iter().map(|x| x.unsigned_abs()).max().unwrap_or(0) as f64 / 32768.0,
+///         SampleSlice::I32(s) => s.iter().map(|x| x.unsigned_abs()).max().unwrap_or(0) as f64 / 2147483648.0,
+///     }
+/// }
+///
+/// let data = [0.5f32, -0.75];
+/// assert_eq!(peak(sashay::AnySetSliceRef::erase(data.as_slice())), 0.75);
+///
+/// // Doesn't compile, because u8 is not a member of the set
+/// // sashay::AnySetSliceRef::<Samples>::erase([0u8].as_slice());
+/// ```
+#[macro_export]
+macro_rules! type_set {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $set:ident => $slice:ident, $slice_mut:ident {
+            $($variant:ident($ty:ty)),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        $vis struct $set;
+
+        #[doc = concat!("An immutable slice of one of the types in [`", stringify!($set), "`].")]
+        #[derive(Clone, Copy)]
+        $vis enum $slice<'a> {
+            $(
+                #[doc = concat!("A slice of `", stringify!($ty), "`")]
+                $variant(&'a [$ty]),
+            )+
+        }
+
+        #[doc = concat!("A mutable slice of one of the types in [`", stringify!($set), "`].")]
+        $vis enum $slice_mut<'a> {
+            $(
+                #[doc = concat!("A slice of `", stringify!($ty), "`")]
+                $variant(&'a mut [$ty]),
+            )+
+        }
+
+        impl $crate::TypeSet for $set {
+            type Slice<'a> = $slice<'a>;
+            type SliceMut<'a> = $slice_mut<'a>;
+
+            fn contains(type_id: &::core::any::TypeId) -> bool {
+                $(*type_id == ::core::any::TypeId::of::<$ty>())||+
+            }
+
+            fn resolve(slice: $crate::AnySliceRef<'_>) -> $slice<'_> {
+                $(
+                    if let Some(slice) = slice.unerase_into::<$ty>() {
+                        return $slice::$variant(slice);
+                    }
+                )+
+
+                panic!(concat!("slice is not of a type in `", stringify!($set), "`"))
+            }
+
+            fn resolve_mut(slice: $crate::AnySliceMut<'_>) -> $slice_mut<'_> {
+                $(
+                    if slice.contains::<$ty>() {
+                        return $slice_mut::$variant(slice.unerase_into::<$ty>().unwrap());
+                    }
+                )+
+
+                panic!(concat!("slice is not of a type in `", stringify!($set), "`"))
+            }
+        }
+
+        // SAFETY: Every member type is handled by `contains()` and both resolve functions above
+        $(unsafe impl $crate::Member<$set> for $ty {})+
+    };
+}
+
+/// A closed set of types, declared with [`type_set!`].
+pub trait TypeSet: 'static {
+    /// An enum with an immutable typed slice per member type
+    type Slice<'a>;
+
+    /// An enum with a mutable typed slice per member type
+    type SliceMut<'a>;
+
+    /// Is the type with this type id a member of the set?
+    fn contains(type_id: &TypeId) -> bool;
+
+    /// Unerase a slice to whichever member type it has.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice's element type is not a member of the set.
+    fn resolve(slice: AnySliceRef<'_>) -> Self::Slice<'_>;
+
+    /// Unerase a mutable slice to whichever member type it has.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice's element type is not a member of the set.
+    fn resolve_mut(slice: AnySliceMut<'_>) -> Self::SliceMut<'_>;
+}
+
+/// Marks a type as a member of the type set `S`.
+///
+/// This is implemented by [`type_set!`] for all of the set's members, and shouldn't be implemented by hand.
+///
+/// # Safety
+///
+/// Implementing this trait is only sound if `S` recognizes `Self` as a member, so [`TypeSet::contains()`]
+/// returns true for its type id, and [`TypeSet::resolve()`] and [`TypeSet::resolve_mut()`] accept its slices.
+pub unsafe trait Member<S: TypeSet>: 'static {}
+
+/// A type-erased immutable slice whose element type is restricted to the members of a [`TypeSet`].
+///
+/// It behaves like an [`AnySliceRef`], except that it can only be constructed from slices of the set's
+/// member types. Because of that, it can be resolved back to a typed slice without a fallback case.
+/// See [`type_set!`] for an example.
+pub struct AnySetSliceRef<'a, S> {
+    /// The erased slice, whose element type is guaranteed to be in `S`
+    any: AnySliceRef<'a>,
+
+    /// Phantom data to remember the type set
+    _set: PhantomData<fn() -> S>,
+}
+
+impl<'a, S: TypeSet> AnySetSliceRef<'a, S> {
+    /// Erase the type of an immutable slice's elements.
+    ///
+    /// This only accepts slices whose element type is a member of the set.
+    pub fn erase<T: Member<S>>(slice: &'a [T]) -> Self {
+        Self {
+            any: AnySliceRef::erase(slice),
+            _set: PhantomData,
+        }
+    }
+
+    /// Restrict an erased slice to a type set.
+    ///
+    /// If the slice's element type isn't a member of the set, you get `None`.
+    pub fn from_any(any: AnySliceRef<'a>) -> Option<Self> {
+        S::contains(any.type_id()).then_some(Self {
+            any,
+            _set: PhantomData,
+        })
+    }
+
+    /// Unerase back to a typed immutable slice, wrapped in the set's slice enum.
+    pub fn resolve(&self) -> S::Slice<'_> {
+        S::resolve(self.any)
+    }
+
+    /// Unerase back into a typed immutable slice, wrapped in the set's slice enum.
+    ///
+    /// This behaves essentially the same as [`AnySetSliceRef::resolve()`],
+    /// except that ownership is tranferred into the slice.
+    pub fn resolve_into(self) -> S::Slice<'a> {
+        S::resolve(self.any)
+    }
+
+    /// Access a subslice within a given range.
+    pub fn subslice<R>(&self, range: R) -> AnySetSliceRef<'_, S>
+    where
+        R: RangeBounds<usize>,
+    {
+        AnySetSliceRef {
+            any: self.any.subslice(range),
+            _set: PhantomData,
+        }
+    }
+
+    /// Access the slice as a regular erased slice, forgetting about the type set.
+    pub const fn as_any(&self) -> AnySliceRef<'a> {
+        self.any
+    }
+
+    /// How many elements does the slice contain?
+    pub const fn len(&self) -> usize {
+        self.any.len()
+    }
+
+    /// Does the slice contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.any.is_empty()
+    }
+
+    /// Was the original slice element of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.any.contains::<T>()
+    }
+
+    /// A unique type id representing the original slice element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        self.any.type_id()
+    }
+}
+
+impl<S> Clone for AnySetSliceRef<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for AnySetSliceRef<'_, S> {}
+
+impl<S> fmt::Debug for AnySetSliceRef<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AnySetSliceRef").field(&self.any).finish()
+    }
+}
+
+impl<'a, S: TypeSet, T: Member<S>> From<&'a [T]> for AnySetSliceRef<'a, S> {
+    fn from(slice: &'a [T]) -> Self {
+        Self::erase(slice)
+    }
+}
+
+impl<'a, S> From<AnySetSliceRef<'a, S>> for AnySliceRef<'a> {
+    fn from(slice: AnySetSliceRef<'a, S>) -> Self {
+        slice.any
+    }
+}
+
+/// A type-erased mutable slice whose element type is restricted to the members of a [`TypeSet`].
+///
+/// It behaves like an [`AnySliceMut`], except that it can only be constructed from slices of the set's
+/// member types. Because of that, it can be resolved back to a typed slice without a fallback case.
+///
+/// ```
+/// sashay::type_set! {
+///     pub struct Ints => IntSlice, IntSliceMut {
+///         U8(u8),
+///         I32(i32),
+///     }
+/// }
+///
+/// let mut data = [1i32, 2, 3];
+/// let mut any = sashay::AnySetSliceMut::<Ints>::erase(data.as_mut_slice());
+///
+/// match any.resolve_mut() {
+///     IntSliceMut::U8(s) => s.fill(0),
+///     IntSliceMut::I32(s) => s.fill(0),
+/// }
+///
+/// assert_eq!(data, [0, 0, 0]);
+/// ```
+pub struct AnySetSliceMut<'a, S> {
+    /// The erased slice, whose element type is guaranteed to be in `S`
+    any: AnySliceMut<'a>,
+
+    /// Phantom data to remember the type set
+    _set: PhantomData<fn() -> S>,
+}
+
+impl<'a, S: TypeSet> AnySetSliceMut<'a, S> {
+    /// Erase the type of a mutable slice's elements.
+    ///
+    /// This only accepts slices whose element type is a member of the set.
+    pub fn erase<T: Member<S>>(slice: &'a mut [T]) -> Self {
+        Self {
+            any: AnySliceMut::erase(slice),
+            _set: PhantomData,
+        }
+    }
+
+    /// Restrict an erased slice to a type set.
+    ///
+    /// If the slice's element type isn't a member of the set, the slice is handed back as an error.
+    pub fn from_any(any: AnySliceMut<'a>) -> Result<Self, AnySliceMut<'a>> {
+        if S::contains(any.type_id()) {
+            Ok(Self {
+                any,
+                _set: PhantomData,
+            })
+        } else {
+            Err(any)
+        }
+    }
+
+    /// Unerase back to a typed immutable slice, wrapped in the set's slice enum.
+    pub fn resolve(&self) -> S::Slice<'_> {
+        S::resolve(self.any.borrow())
+    }
+
+    /// Unerase back to a typed mutable slice, wrapped in the set's mutable slice enum.
+    pub fn resolve_mut(&mut self) -> S::SliceMut<'_> {
+        S::resolve_mut(self.any.borrow_mut())
+    }
+
+    /// Unerase back into a typed mutable slice, wrapped in the set's mutable slice enum.
+    ///
+    /// This behaves essentially the same as [`AnySetSliceMut::resolve_mut()`],
+    /// except that ownership is tranferred into the slice.
+    pub fn resolve_into(self) -> S::SliceMut<'a> {
+        S::resolve_mut(self.any)
+    }
+
+    /// Borrow this mutable slice as an immutable one.
+    pub fn borrow(&self) -> AnySetSliceRef<'_, S> {
+        AnySetSliceRef {
+            any: self.any.borrow(),
+            _set: PhantomData,
+        }
+    }
+
+    /// Borrow this mutable slice as a mutable slice with lifetime 'self.
+    pub fn borrow_mut(&mut self) -> AnySetSliceMut<'_, S> {
+        AnySetSliceMut {
+            any: self.any.borrow_mut(),
+            _set: PhantomData,
+        }
+    }
+
+    /// Access a mutable subslice within a given range.
+    pub fn subslice_mut<R>(&mut self, range: R) -> AnySetSliceMut<'_, S>
+    where
+        R: RangeBounds<usize>,
+    {
+        AnySetSliceMut {
+            any: self.any.subslice_mut(range),
+            _set: PhantomData,
+        }
+    }
+
+    /// Access the slice as a regular erased slice, forgetting about the type set.
+    pub fn into_any(self) -> AnySliceMut<'a> {
+        self.any
+    }
+
+    /// How many elements does the slice contain?
+    pub const fn len(&self) -> usize {
+        self.any.len()
+    }
+
+    /// Does the slice contain any elements at all?
+    pub const fn is_empty(&self) -> bool {
+        self.any.is_empty()
+    }
+
+    /// Was the original slice element of type `T`?
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.any.contains::<T>()
+    }
+
+    /// The `size_of()` of the original slice elements of type `T`.
+    pub const fn stride(&self) -> usize {
+        self.any.stride()
+    }
+
+    /// A unique type id representing the original slice element `T`.
+    pub const fn type_id(&self) -> &TypeId {
+        self.any.type_id()
+    }
+}
+
+impl<S> fmt::Debug for AnySetSliceMut<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AnySetSliceMut").field(&self.any).finish()
+    }
+}
+
+impl<'a, S: TypeSet, T: Member<S>> From<&'a mut [T]> for AnySetSliceMut<'a, S> {
+    fn from(slice: &'a mut [T]) -> Self {
+        Self::erase(slice)
+    }
+}
+
+impl<'a, S> From<AnySetSliceMut<'a, S>> for AnySliceMut<'a> {
+    fn from(slice: AnySetSliceMut<'a, S>) -> Self {
+        slice.any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::type_set! {
+        struct Padded => PaddedSlice, PaddedSliceMut {
+            Small((u8, u16)),
+            Large((u8, u64)),
+        }
+    }
+
+    #[test]
+    fn restrict() {
+        let data = [(1u8, 2u16), (3u8, 4u16)];
+
+        assert!(AnySetSliceRef::<Padded>::from_any(AnySliceRef::erase([0u8].as_slice())).is_none());
+
+        let any = AnySetSliceRef::<Padded>::from_any(AnySliceRef::erase(data.as_slice())).unwrap();
+        assert_eq!(any.len(), 2);
+        assert!(any.contains::<(u8, u16)>());
+
+        match any.subslice(1..).resolve() {
+            PaddedSlice::Small(slice) => assert_eq!(slice, &data[1..]),
+            PaddedSlice::Large(slice) => unreachable!("{slice:?}"),
+        }
+    }
+
+    #[test]
+    fn resolve_mut() {
+        let mut data = [(1u8, 2u64), (3u8, 4u64)];
+        let mut any = AnySetSliceMut::<Padded>::erase(data.as_mut_slice());
+
+        match any.resolve_mut() {
+            PaddedSliceMut::Small(slice) => unreachable!("{slice:?}"),
+            PaddedSliceMut::Large(slice) => slice[0].1 = 10,
+        }
+
+        let any = AnySetSliceMut::<Padded>::from_any(any.into_any()).unwrap();
+        assert!(matches!(any.resolve(), PaddedSlice::Large(_)));
+        assert_eq!(data, [(1, 10), (3, 4)]);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{AnySliceMut, AnySliceRef};
+use core::{
+    any::TypeId,
+    fmt,
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem::take,
+    slice::{Iter, IterMut},
+};
+
+/// Unerase several slices at once and iterate over them in lockstep.
+///
+/// The query `Q` is a tuple of `&T` and `&mut T` elements. Every `&T` takes the next slice from
+/// `refs`, and every `&mut T` the next slice from `muts`, in order. All type ids, the number of slices
+/// and their lengths are verified up front, after which you get a single iterator of typed tuples.
+///
+/// Mutable access is always disjoint, because every `&mut T` comes from its own [`AnySliceMut`].
+///
+/// ```
+/// let positions = [0.0f32, 10.0, 20.0];
+/// let mut velocities = [1.0f32, 2.0, 3.0];
+/// let masses = [1u8, 2, 4];
+///
+/// let refs = [sashay::AnySliceRef::erase(positions.as_slice()), sashay::AnySliceRef::erase(masses.as_slice())];
+/// let mut muts = [sashay::AnySliceMut::erase(velocities.as_mut_slice())];
+///
+/// let query = sashay::unerase_zip::<(&f32, &mut f32, &u8)>(&refs, &mut muts).unwrap();
+/// for (position, velocity, mass) in query {
+///     *velocity = *position / f32::from(*mass);
+/// }
+///
+/// assert_eq!(velocities, [0.0, 5.0, 5.0]);
+/// ```
+pub fn unerase_zip<'a, Q: UneraseZip<'a>>(
+    refs: &'a [AnySliceRef<'_>],
+    muts: &'a mut [AnySliceMut<'_>],
+) -> Result<ZipIter<'a, Q>, ZipError> {
+    if refs.len() != Q::REFS {
+        return Err(ZipError::RefCountMismatch {
+            expected: Q::REFS,
+            found: refs.len(),
+        });
+    } else if muts.len() != Q::MUTS {
+        return Err(ZipError::MutCountMismatch {
+            expected: Q::MUTS,
+            found: muts.len(),
+        });
+    }
+
+    let mut len = None;
+    let slices = Q::take(&mut refs.iter(), &mut muts.iter_mut(), &mut len)?;
+
+    Ok(ZipIter {
+        slices,
+        len: len.unwrap_or(0),
+        _phantom: PhantomData,
+    })
+}
+
+/// Tuples of `&T` and `&mut T` that can be used as a query for [`unerase_zip()`].
+///
+/// This is implemented for tuples of up to eight [`ZipElement`]s.
+pub trait UneraseZip<'a>: Sized {
+    /// The tuple of typed slices being iterated over
+    type Slices;
+
+    /// The number of immutable slices in the query
+    const REFS: usize;
+
+    /// The number of mutable slices in the query
+    const MUTS: usize;
+
+    /// Unerase the next slices for every element, checking their types and lengths.
+    fn take<'r, 'm>(
+        refs: &mut Iter<'a, AnySliceRef<'r>>,
+        muts: &mut IterMut<'a, AnySliceMut<'m>>,
+        len: &mut Option<usize>,
+    ) -> Result<Self::Slices, ZipError>;
+
+    /// Split the first tuple off all slices.
+    fn split_first(slices: &mut Self::Slices) -> Option<Self>;
+}
+
+/// A single element of an [`UneraseZip`] query, either `&T` or `&mut T`.
+pub trait ZipElement<'a>: Sized {
+    /// The typed slice this element comes from
+    type Slice: Default;
+
+    /// Is this an immutable element, taken from the immutable slices?
+    const SHARED: bool;
+
+    /// Unerase the next slice, checking its type.
+    fn take<'r, 'm>(
+        refs: &mut Iter<'a, AnySliceRef<'r>>,
+        muts: &mut IterMut<'a, AnySliceMut<'m>>,
+    ) -> Result<Self::Slice, ZipError>;
+
+    /// The number of elements in a slice.
+    fn len(slice: &Self::Slice) -> usize;
+
+    /// Split the first element off a slice.
+    fn split_first(slice: &mut Self::Slice) -> Option<Self>;
+}
+
+impl<'a, T: 'static> ZipElement<'a> for &'a T {
+    type Slice = &'a [T];
+
+    const SHARED: bool = true;
+
+    fn take<'r, 'm>(
+        refs: &mut Iter<'a, AnySliceRef<'r>>,
+        _muts: &mut IterMut<'a, AnySliceMut<'m>>,
+    ) -> Result<&'a [T], ZipError> {
+        let slice = refs.next().expect("the number of slices was checked");
+        slice.unerase::<T>().ok_or(ZipError::TypeMismatch {
+            expected: TypeId::of::<T>(),
+            found: *slice.type_id(),
+        })
+    }
+
+    fn len(slice: &&'a [T]) -> usize {
+        slice.len()
+    }
+
+    fn split_first(slice: &mut &'a [T]) -> Option<&'a T> {
+        let (first, rest) = slice.split_first()?;
+        *slice = rest;
+        Some(first)
+    }
+}
+
+impl<'a, T: 'static> ZipElement<'a> for &'a mut T {
+    type Slice = &'a mut [T];
+
+    const SHARED: bool = false;
+
+    fn take<'r, 'm>(
+        _refs: &mut Iter<'a, AnySliceRef<'r>>,
+        muts: &mut IterMut<'a, AnySliceMut<'m>>,
+    ) -> Result<&'a mut [T], ZipError> {
+        let slice = muts.next().expect("the number of slices was checked");
+        let found = *slice.type_id();
+        slice.unerase_mut::<T>().ok_or(ZipError::TypeMismatch {
+            expected: TypeId::of::<T>(),
+            found,
+        })
+    }
+
+    fn len(slice: &&'a mut [T]) -> usize {
+        slice.len()
+    }
+
+    fn split_first(slice: &mut &'a mut [T]) -> Option<&'a mut T> {
+        let (first, rest) = take(slice).split_first_mut()?;
+        *slice = rest;
+        Some(first)
+    }
+}
+
+macro_rules! impl_unerase_zip {
+    ($($element:ident $slice:ident),+) => {
+        impl<'a, $($element: ZipElement<'a>),+> UneraseZip<'a> for ($($element,)+) {
+            type Slices = ($($element::Slice,)+);
+
+            const REFS: usize = 0 $(+ $element::SHARED as usize)+;
+            const MUTS: usize = 0 $(+ !$element::SHARED as usize)+;
+
+            fn take<'r, 'm>(
+                refs: &mut Iter<'a, AnySliceRef<'r>>,
+                muts: &mut IterMut<'a, AnySliceMut<'m>>,
+                len: &mut Option<usize>,
+            ) -> Result<Self::Slices, ZipError> {
+                $(
+                    let $slice = $element::take(refs, muts)?;
+                    let expected = *len.get_or_insert($element::len(&$slice));
+                    if $element::len(&$slice) != expected {
+                        return Err(ZipError::LengthMismatch {
+                            expected,
+                            found: $element::len(&$slice),
+                        });
+                    }
+                )+
+
+                Ok(($($slice,)+))
+            }
+
+            fn split_first(slices: &mut Self::Slices) -> Option<Self> {
+                let ($($slice,)+) = slices;
+                Some(($($element::split_first($slice)?,)+))
+            }
+        }
+    };
+}
+
+impl_unerase_zip!(A a);
+impl_unerase_zip!(A a, B b);
+impl_unerase_zip!(A a, B b, C c);
+impl_unerase_zip!(A a, B b, C c, D d);
+impl_unerase_zip!(A a, B b, C c, D d, E e);
+impl_unerase_zip!(A a, B b, C c, D d, E e, F f);
+impl_unerase_zip!(A a, B b, C c, D d, E e, F f, G g);
+impl_unerase_zip!(A a, B b, C c, D d, E e, F f, G g, H h);
+
+/// An iterator over tuples of elements from several unerased slices, created by [`unerase_zip()`].
+pub struct ZipIter<'a, Q: UneraseZip<'a>> {
+    /// The remaining typed slices, which all have the same length
+    slices: Q::Slices,
+
+    /// The number of remaining tuples
+    len: usize,
+
+    /// Phantom data to tie the query type to the iterator
+    _phantom: PhantomData<fn() -> Q>,
+}
+
+impl<'a, Q: UneraseZip<'a>> Iterator for ZipIter<'a, Q> {
+    type Item = Q;
+
+    fn next(&mut self) -> Option<Q> {
+        let item = Q::split_first(&mut self.slices)?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, Q: UneraseZip<'a>> ExactSizeIterator for ZipIter<'a, Q> {}
+
+impl<'a, Q: UneraseZip<'a>> FusedIterator for ZipIter<'a, Q> {}
+
+impl<'a, Q: UneraseZip<'a>> fmt::Debug for ZipIter<'a, Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZipIter")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The reasons unerasing several slices at once can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZipError {
+    /// The number of immutable slices doesn't match the number of `&T` elements in the query
+    RefCountMismatch { expected: usize, found: usize },
+
+    /// The number of mutable slices doesn't match the number of `&mut T` elements in the query
+    MutCountMismatch { expected: usize, found: usize },
+
+    /// A slice has a different type than its element in the query
+    TypeMismatch { expected: TypeId, found: TypeId },
+
+    /// A slice has a different length than the first one
+    LengthMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for ZipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RefCountMismatch { expected, found } => {
+                write!(f, "expected {expected} immutable slices, but got {found}")
+            }
+            Self::MutCountMismatch { expected, found } => {
+                write!(f, "expected {expected} mutable slices, but got {found}")
+            }
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected a slice of {expected:?}, but got {found:?}")
+            }
+            Self::LengthMismatch { expected, found } => {
+                write!(f, "expected a slice of length {expected}, but got {found}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ZipError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // All these tests use an (u8, u16) because it has padding
+
+    #[test]
+    fn zip() {
+        let first = [(1u8, 2u16), (3u8, 4u16)];
+        let mut second = [(0u8, 0u16); 2];
+        let mut third = [0u32; 2];
+
+        let refs = [AnySliceRef::erase(first.as_slice())];
+        let mut muts = [
+            AnySliceMut::erase(second.as_mut_slice()),
+            AnySliceMut::erase(third.as_mut_slice()),
+        ];
+
+        let query =
+            unerase_zip::<(&mut (u8, u16), &(u8, u16), &mut u32)>(&refs, &mut muts).unwrap();
+        assert_eq!(query.len(), 2);
+        for (second, first, third) in query {
+            *second = (first.0 * 2, first.1 * 2);
+            *third = u32::from(first.1);
+        }
+
+        assert_eq!(second, [(2u8, 4u16), (6u8, 8u16)]);
+        assert_eq!(third, [2u32, 4]);
+    }
+
+    #[test]
+    fn errors() {
+        let first = [(1u8, 2u16), (3u8, 4u16)];
+        let mut second = [(0u8, 0u16); 3];
+
+        let refs = [AnySliceRef::erase(first.as_slice())];
+        let mut muts = [AnySliceMut::erase(second.as_mut_slice())];
+
+        assert_eq!(
+            unerase_zip::<(&(u8, u16),)>(&refs, &mut muts).unwrap_err(),
+            ZipError::MutCountMismatch {
+                expected: 0,
+                found: 1
+            }
+        );
+        assert_eq!(
+            unerase_zip::<(&(u8, u16), &(u8, u16))>(&refs, &mut []).unwrap_err(),
+            ZipError::RefCountMismatch {
+                expected: 2,
+                found: 1
+            }
+        );
+        assert_eq!(
+            unerase_zip::<(&(u8, u16), &mut u8)>(&refs, &mut muts).unwrap_err(),
+            ZipError::TypeMismatch {
+                expected: TypeId::of::<u8>(),
+                found: TypeId::of::<(u8, u16)>()
+            }
+        );
+        assert_eq!(
+            unerase_zip::<(&(u8, u16), &mut (u8, u16))>(&refs, &mut muts).unwrap_err(),
+            ZipError::LengthMismatch {
+                expected: 2,
+                found: 3
+            }
+        );
+    }
+}